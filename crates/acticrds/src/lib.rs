@@ -1,9 +1,26 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
 
+use actitopo::Topology;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
 use kube::CustomResource;
-use schemars::JsonSchema;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use serde_json::json;
+use validator::{Validate, ValidationError};
+
+mod placement;
+mod policy;
+mod reservation;
+
+pub use placement::{
+    ActiPlacement, ActiPlacementSpec, ActiPlacementStatus, ElementRef, ENFORCED_CONDITION,
+};
+pub use policy::{ActiPolicy, ActiPolicySpec, AllocationStrategy, DetectionMode, SmtPolicy};
+pub use reservation::{ActiReservation, ActiReservationSpec, ReservationPurpose, ReservedCpuSet};
 
 /// ActiNodeSpec defines the desired state of an ActiNode.
 #[derive(
@@ -19,22 +36,418 @@ use validator::Validate;
     derive = "Default",
     shortname = "an",
     shortname = "actin",
-    shortname = "anode"
+    shortname = "anode",
+    printcolumn = r#"{"name":"Ready", "type":"string", "jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Pods", "type":"integer", "jsonPath":".status.assignedPods"}"#,
+    printcolumn = r#"{"name":"Pinned CPUs", "type":"integer", "jsonPath":".status.pinnedCpus"}"#,
+    printcolumn = r#"{"name":"NUMA Nodes", "type":"integer", "jsonPath":".status.numaNodes"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
 )]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_overlaps"))]
 pub struct ActiNodeSpec {
-    /// Assignments include the Pods that are executed on the Node related to an ActiNode, along
-    /// with the OS indices of the cores where each of them is pinned.
-    pub assignments: HashMap<String, Vec<u32>>,
+    /// Assignments include the containers of the Pods that are executed on the Node related to an
+    /// ActiNode, along with the OS indices of the cores where each of them is pinned.
+    #[validate]
+    #[schemars(schema_with = "assignments_schema")]
+    pub assignments: Vec<Assignment>,
+
+    /// Topology is the full hardware topology of the Node related to an ActiNode, as detected by
+    /// ActiK8s' registrant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "topology_schema")]
+    pub topology: Option<Topology>,
+
+    /// PartialTopology is the hardware topology of the Node related to an ActiNode, limited to the
+    /// elements at isolation boundaries, as detected by ActiK8s' registrant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "topology_schema")]
+    pub partial_topology: Option<Topology>,
+}
+
+/// Assignment describes the pinning of a single container of a Pod to a set of cores of the Node
+/// related to an ActiNode.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, JsonSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct Assignment {
+    /// PodUid is the UID of the Pod that the assigned container belongs to.
+    #[validate(length(min = 1))]
+    pub pod_uid: String,
+
+    /// Namespace is the namespace of the Pod that the assigned container belongs to.
+    #[validate(length(min = 1, max = 63))]
+    pub namespace: String,
+
+    /// Name is the name of the Pod that the assigned container belongs to.
+    #[validate(length(min = 1, max = 253))]
+    pub name: String,
+
+    /// Container is the name of the assigned container, within its Pod.
+    #[validate(length(min = 1, max = 63))]
+    pub container: String,
+
+    /// Cpus are the OS indices of the cores where the container is pinned.
+    #[validate(length(min = 1))]
+    pub cpus: Vec<u32>,
+
+    /// Mems are the indices of the NUMA nodes where the container's memory is bound (i.e., its
+    /// `cpuset.mems`); if empty, its memory is not bound.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mems: Vec<u32>,
+
+    /// Class denotes whether (and how) other workloads must be fenced off the cores where the
+    /// container is pinned.
+    #[serde(default)]
+    pub class: AssignmentClass,
+}
+
+impl Assignment {
+    /// Returns `true` if the cores where the container is pinned must not be shared with any other
+    /// exclusive assignment.
+    pub fn is_exclusive(&self) -> bool {
+        self.class == AssignmentClass::Exclusive
+    }
+
+    /// Returns the key of the [`Pinning`] of the Pod that the assigned container belongs to, in
+    /// [`ActiNodeStatus::pinnings`] (i.e., `<namespace>/<name>`).
+    pub fn pod_key(&self) -> String {
+        format!("{}/{}", self.namespace, self.name)
+    }
+}
+
+/// AssignmentClass is the QoS class of an [`Assignment`], which regulates how its cores are shared
+/// with other workloads.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash, Clone, Copy, JsonSchema)]
+pub enum AssignmentClass {
+    /// The cores of the assignment are dedicated to it; they must not be shared with any other
+    /// exclusive assignment, and other workloads should be fenced off them.
+    Exclusive,
+
+    /// The cores of the assignment may be shared with other shared or best-effort assignments.
+    #[default]
+    Shared,
+
+    /// The assignment makes use of whatever cycles are left idle on its cores.
+    BestEffort,
+}
+
+/// The error returned by [`check_overlaps`] when two exclusive [`Assignment`]s share a core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverlapError {
+    /// The OS index of the shared core.
+    pub cpu: u32,
+    /// The index of the first of the two conflicting assignments.
+    pub first: usize,
+    /// The index of the second of the two conflicting assignments.
+    pub second: usize,
+}
+
+impl fmt::Display for OverlapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exclusive assignments #{} and #{} are both pinned on core {}",
+            self.first, self.second, self.cpu
+        )
+    }
+}
+
+impl std::error::Error for OverlapError {}
+
+/// Checks that no two exclusive [`Assignment`]s are pinned on the same core.
+///
+/// Non-exclusive assignments are allowed to share cores with any other assignment.
+///
+/// # Errors
+///
+/// Returns an [`OverlapError`] describing the first conflict found.
+pub fn check_overlaps(assignments: &[Assignment]) -> Result<(), OverlapError> {
+    let mut owners = HashMap::new();
+    for (idx, assignment) in assignments
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.is_exclusive())
+    {
+        for &cpu in &assignment.cpus {
+            match owners.insert(cpu, idx) {
+                Some(first) if first != idx => {
+                    return Err(OverlapError {
+                        cpu,
+                        first,
+                        second: idx,
+                    })
+                }
+                _ => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The schema-level validation rule of [`ActiNodeSpec`], backed by [`check_overlaps`].
+fn validate_overlaps(spec: &ActiNodeSpec) -> Result<(), ValidationError> {
+    check_overlaps(&spec.assignments).map_err(|err| {
+        let mut verr = ValidationError::new("exclusive_overlap");
+        verr.message = Some(Cow::Owned(err.to_string()));
+        verr.add_param(Cow::Borrowed("cpu"), &err.cpu);
+        verr
+    })
+}
+
+/// The error returned by [`check_pinnings`] when a Pod is pinned on a core where none of its
+/// containers is assigned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinningError {
+    /// The key of the offending [`Pinning`] (i.e., `<namespace>/<name>` of its Pod).
+    pub pod: String,
+    /// The OS index of the core.
+    pub cpu: u32,
+}
+
+impl fmt::Display for PinningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Pod {} is pinned on core {}, where none of its containers is assigned",
+            self.pod, self.cpu
+        )
+    }
+}
+
+impl std::error::Error for PinningError {}
+
+/// Checks that each [`Pinning`] refers to an assigned Pod, and is confined to the cores where
+/// the containers of the Pod are assigned.
+///
+/// Since assignments are per container while pinnings are per Pod, each pinned core is checked
+/// against the cores of all the assignments of its Pod, rather than against any single one of
+/// them.
+///
+/// # Errors
+///
+/// Returns a [`PinningError`] describing the first stray core found, in the order of the keys of
+/// the pinnings.
+pub fn check_pinnings(
+    assignments: &[Assignment],
+    pinnings: &HashMap<String, Pinning>,
+) -> Result<(), PinningError> {
+    let mut assigned: HashMap<_, BTreeSet<u32>> = HashMap::new();
+    for assignment in assignments {
+        assigned
+            .entry(assignment.pod_key())
+            .or_default()
+            .extend(&assignment.cpus);
+    }
+    let (mut pods, unassigned) = (pinnings.keys().collect::<Vec<_>>(), BTreeSet::new());
+    pods.sort_unstable();
+    for pod in pods {
+        let cpus = assigned.get(pod).unwrap_or(&unassigned);
+        if let Some(&cpu) = pinnings[pod].cpus.iter().find(|cpu| !cpus.contains(cpu)) {
+            return Err(PinningError {
+                pod: pod.clone(),
+                cpu,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The CEL rules that are attached to the schema of [`ActiNodeSpec::assignments`], so that invalid
+/// assignments are rejected by the API server itself, as `(rule, message)` pairs.
+///
+/// CEL validation rules require Kubernetes v1.25 or later, where they are enabled by default
+/// (through the `CustomResourceValidationExpressions` feature gate); earlier API servers silently
+/// drop them, so the rules are only enforced by ActiK8s' controllers there.
+///
+/// Note that pinnings are not checked against assignments here (see [`check_pinnings`]): a rule
+/// relating them would have to be attached to the root of the schema, with a worst-case cost that
+/// is quadratic in the numbers of Pods and cores of the Node, hence way beyond the API server's
+/// budget for realistic Nodes; besides, it would reject the removal of any assignment whose Pod is
+/// still pinned in the status.
+const ASSIGNMENTS_RULES: &[(&str, &str)] = &[(
+    "self.all(a, size(a.cpus) > 0)",
+    "each assignment must be pinned on at least one core",
+)];
+
+/// Converts the given `(rule, message)` pairs into the value of an `x-kubernetes-validations`
+/// schema extension.
+fn validation_rules(rules: &[(&str, &str)]) -> serde_json::Value {
+    rules
+        .iter()
+        .map(|(rule, message)| json!({ "rule": rule, "message": message }))
+        .collect()
+}
+
+/// Returns the schema of [`ActiNodeSpec::assignments`], extended with [`ASSIGNMENTS_RULES`].
+fn assignments_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = gen.subschema_for::<Vec<Assignment>>().into_object();
+    schema.extensions.insert(
+        "x-kubernetes-validations".to_owned(),
+        validation_rules(ASSIGNMENTS_RULES),
+    );
+    Schema::Object(schema)
+}
+
+/// Returns the schema of a serialized `actitopo::Topology`, as provided by `actitopo`.
+///
+/// Elements of kinds that are unknown to this version of `actitopo` (see
+/// `actitopo::Element::Unknown`) are also accepted, and preserved as they are rather than pruned
+/// by the API server.
+fn topology_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema =
+        serde_json::to_value(gen.subschema_for::<Topology>()).expect("invalid Topology schema");
+    let element = &mut schema["properties"]["nodes"]["items"]["properties"]["data"];
+    element["x-kubernetes-preserve-unknown-fields"] = true.into();
+    if let Some(kinds) = element["oneOf"].as_array_mut() {
+        let known = kinds
+            .iter()
+            .map(|kind| json!({ "required": kind["required"] }))
+            .collect::<Vec<_>>();
+        kinds.push(json!({ "not": { "anyOf": known } }));
+    }
+    serde_json::from_value(schema).expect("invalid Topology schema")
 }
 
 /// ActiNodeStatus describes the observed state of an ActiNode.
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ActiNodeStatus {
-    /// Pinnings include the actual assignments of Pods to physical cores, as observed (and
-    /// enforced) by ActiK8s' `internal` controller.
-    pub pinnings: HashMap<String, Vec<u32>>,
+    /// Pinnings include the actual assignments of Pods to physical cores and NUMA nodes, as
+    /// observed (and enforced) by ActiK8s' `internal` controller, keyed by `<namespace>/<name>`
+    /// of their Pods.
+    ///
+    /// Each pinning is expected to be confined to the cores where the containers of its Pod are
+    /// assigned; pinnings of Pods whose assignments have been removed from the spec linger until
+    /// the controller prunes them.
+    pub pinnings: HashMap<String, Pinning>,
+
+    /// Conditions describe the latest observations of the ActiNode's state, following the
+    /// standard Kubernetes conventions (e.g., a `Ready` condition).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+
+    /// AssignedPods is the number of Pods assigned to the ActiNode, as summarized for display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_pods: Option<u32>,
+
+    /// PinnedCpus is the number of distinct cores where Pods are pinned, as summarized for
+    /// display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_cpus: Option<u32>,
+
+    /// NumaNodes is the number of NUMA nodes in the ActiNode's topology, as summarized for display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numa_nodes: Option<u32>,
+}
+
+/// Pinning describes the cores and NUMA nodes where a Pod is actually pinned.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Pinning {
+    /// Cpus are the OS indices of the cores where the Pod is pinned.
+    pub cpus: Vec<u32>,
+
+    /// Mems are the indices of the NUMA nodes where the Pod's memory is bound; if empty, its
+    /// memory is not bound.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mems: Vec<u32>,
+}
+
+/// The type of the condition that signifies that an ActiNode is ready to be used.
+pub const READY_CONDITION: &str = "Ready";
+
+impl ActiNodeStatus {
+    /// Returns the condition of the given type, if it exists.
+    pub fn condition(&self, type_: &str) -> Option<&Condition> {
+        self.conditions.iter().find(|c| c.type_ == type_)
+    }
+
+    /// Returns `true` if the condition of the given type exists and its status is `True`.
+    pub fn is_condition_true(&self, type_: &str) -> bool {
+        matches!(self.condition(type_), Some(c) if c.status == "True")
+    }
+
+    /// Add the given condition, or replace the existing one of the same type.
+    ///
+    /// Similarly to `meta.SetStatusCondition` in apimachinery, the `lastTransitionTime` of an
+    /// existing condition is retained, unless its status changes.
+    pub fn set_condition(&mut self, condition: Condition) {
+        match self
+            .conditions
+            .iter_mut()
+            .find(|c| c.type_ == condition.type_)
+        {
+            Some(existing) => {
+                let last_transition_time = (existing.status == condition.status)
+                    .then(|| existing.last_transition_time.clone());
+                *existing = condition;
+                if let Some(last_transition_time) = last_transition_time {
+                    existing.last_transition_time = last_transition_time;
+                }
+            }
+            None => self.conditions.push(condition),
+        }
+    }
+
+    /// Remove the condition of the given type, returning it if it existed.
+    pub fn remove_condition(&mut self, type_: &str) -> Option<Condition> {
+        let idx = self.conditions.iter().position(|c| c.type_ == type_)?;
+        Some(self.conditions.remove(idx))
+    }
+}
+
+impl ActiNode {
+    /// Update the summary fields of the ActiNode's status (i.e., those displayed by
+    /// `kubectl get actinodes`) to reflect its current spec and pinnings, initializing the status
+    /// if it does not exist yet.
+    pub fn refresh_summary(&mut self) {
+        let status = self.status.get_or_insert_with(Default::default);
+        let pinned_cpus = status
+            .pinnings
+            .values()
+            .flat_map(|pinning| &pinning.cpus)
+            .collect::<BTreeSet<_>>();
+        let assigned_pods = self
+            .spec
+            .assignments
+            .iter()
+            .map(|a| a.pod_uid.as_str())
+            .collect::<BTreeSet<_>>();
+        status.assigned_pods = Some(assigned_pods.len() as u32);
+        status.pinned_cpus = Some(pinned_cpus.len() as u32);
+        status.numa_nodes = self
+            .spec
+            .topology
+            .as_ref()
+            .map(|topo| topo.numa_node_ids().count() as u32);
+    }
+
+    /// Remove from the ActiNode's status the pinnings of the Pods that are no longer assigned to
+    /// it (e.g., after their assignments have been removed from its spec), returning their keys in
+    /// order.
+    pub fn prune_pinnings(&mut self) -> Vec<String> {
+        let status = match self.status.as_mut() {
+            Some(status) => status,
+            None => return vec![],
+        };
+        let assigned = self
+            .spec
+            .assignments
+            .iter()
+            .map(Assignment::pod_key)
+            .collect::<BTreeSet<_>>();
+        let mut pruned = status
+            .pinnings
+            .keys()
+            .filter(|pod| !assigned.contains(*pod))
+            .cloned()
+            .collect::<Vec<_>>();
+        pruned.sort_unstable();
+        for pod in &pruned {
+            status.pinnings.remove(pod);
+        }
+        pruned
+    }
 }
 
 #[cfg(test)]
@@ -43,7 +456,10 @@ mod tests {
     use kube::CustomResourceExt;
     use validator::Validate;
 
-    use super::ActiNode;
+    use super::{
+        check_overlaps, check_pinnings, ActiNode, ActiNodeStatus, Assignment, AssignmentClass,
+        OverlapError, Pinning, PinningError, READY_CONDITION,
+    };
 
     #[test]
     fn print_an_crd_yaml() {
@@ -97,4 +513,427 @@ mod tests {
         eprintln!("{crd}");
         Ok(())
     }
+
+    #[test]
+    fn set_conditions() {
+        use k8s_openapi::apimachinery::pkg::apis::meta::v1::{Condition, Time};
+        use k8s_openapi::chrono::{TimeZone, Utc};
+
+        let condition = |status: &str, reason: &str, secs| Condition {
+            type_: READY_CONDITION.to_owned(),
+            status: status.to_owned(),
+            reason: reason.to_owned(),
+            message: String::new(),
+            observed_generation: None,
+            last_transition_time: Time(Utc.timestamp_opt(secs, 0).unwrap()),
+        };
+
+        let mut status = ActiNodeStatus::default();
+        assert!(!status.is_condition_true(READY_CONDITION));
+
+        status.set_condition(condition("False", "Detecting", 1));
+        assert!(!status.is_condition_true(READY_CONDITION));
+
+        status.set_condition(condition("True", "Registered", 2));
+        status.set_condition(condition("True", "StillRegistered", 3));
+        assert_eq!(status.conditions.len(), 1);
+        assert!(status.is_condition_true(READY_CONDITION));
+        let ready = status.condition(READY_CONDITION).expect("Ready not found");
+        assert_eq!(ready.reason, "StillRegistered");
+        assert_eq!(
+            ready.last_transition_time,
+            Time(Utc.timestamp_opt(2, 0).unwrap())
+        );
+
+        assert!(status.remove_condition(READY_CONDITION).is_some());
+        assert!(status.condition(READY_CONDITION).is_none());
+    }
+
+    fn assignment(pod: &str, container: &str, cpus: Vec<u32>) -> Assignment {
+        Assignment {
+            pod_uid: format!("{pod}-uid"),
+            namespace: "default".to_owned(),
+            name: pod.to_owned(),
+            container: container.to_owned(),
+            cpus,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_assignments() {
+        let mut an = ActiNode::new("validate-assignments", Default::default());
+        an.spec.assignments = vec![assignment("a", "main", vec![0, 1])];
+        assert!(an.spec.validate().is_ok());
+
+        an.spec.assignments.push(assignment("b", "main", vec![]));
+        assert!(an.spec.validate().is_err());
+
+        an.spec.assignments[1] = assignment("b", "", vec![2]);
+        assert!(an.spec.validate().is_err());
+    }
+
+    #[test]
+    fn assignment_mems() -> Result<()> {
+        let unbound = assignment("a", "main", vec![0, 1]);
+        let json = serde_json::to_string(&unbound)?;
+        assert!(!json.contains("mems"));
+        assert!(json.contains(r#""class":"Shared""#));
+
+        let bound = Assignment {
+            mems: vec![1],
+            ..assignment("b", "main", vec![2, 3])
+        };
+        let de: Assignment = serde_json::from_str(&serde_json::to_string(&bound)?)?;
+        assert_eq!(de, bound);
+        Ok(())
+    }
+
+    #[test]
+    fn exclusive_overlaps() {
+        let exclusive = |pod, cpus| Assignment {
+            class: AssignmentClass::Exclusive,
+            ..assignment(pod, "main", cpus)
+        };
+
+        let mut an = ActiNode::new("exclusive-overlaps", Default::default());
+        an.spec.assignments = vec![
+            exclusive("a", vec![0, 1]),
+            assignment("b", "main", vec![1, 2]),
+            Assignment {
+                class: AssignmentClass::BestEffort,
+                ..assignment("e", "main", vec![0, 2])
+            },
+            exclusive("c", vec![2, 3]),
+        ];
+        assert_eq!(check_overlaps(&an.spec.assignments), Ok(()));
+        assert!(an.spec.validate().is_ok());
+
+        an.spec.assignments.push(exclusive("d", vec![4, 3]));
+        assert_eq!(
+            check_overlaps(&an.spec.assignments),
+            Err(OverlapError {
+                cpu: 3,
+                first: 3,
+                second: 4
+            })
+        );
+        assert!(an.spec.validate().is_err());
+    }
+
+    /// Returns the field selections of the given CEL rule (e.g., `self.spec.assignments`), along
+    /// with whether each one of them is the receiver of a macro (e.g., `all`), panicking if its
+    /// parentheses or quotes are unbalanced.
+    fn cel_selections(rule: &str) -> Vec<(String, bool)> {
+        let (mut selections, mut depth, mut quoted, mut token) =
+            (vec![], 0i32, false, String::new());
+        for ch in rule.chars().chain(std::iter::once(' ')) {
+            match ch {
+                '\'' => quoted = !quoted,
+                _ if quoted => continue,
+                ch if ch.is_ascii_alphanumeric() || "_.[]".contains(ch) => {
+                    token.push(ch);
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => (),
+            }
+            assert!(depth >= 0, "unbalanced parentheses in {rule:?}");
+            let token = std::mem::take(&mut token);
+            let (path, is_receiver) = match (ch, token.rsplit_once('.')) {
+                ('(', Some((path, _))) => (path.to_owned(), true),
+                ('(', None) => continue,
+                _ => (token, false),
+            };
+            if path.contains('.') || path.contains('[') {
+                selections.push((path, is_receiver));
+            }
+        }
+        assert!(depth == 0 && !quoted, "unbalanced {rule:?}");
+        selections
+    }
+
+    /// Resolves the schema of the given CEL field selection, through the schemas of its variables.
+    fn cel_select<'a>(
+        vars: &[(&str, &'a serde_json::Value)],
+        path: &str,
+    ) -> Option<&'a serde_json::Value> {
+        let path = path.replace('[', ".[");
+        let mut parts = path.split('.');
+        let root = parts.next()?;
+        let mut schema = vars.iter().find(|(var, _)| *var == root)?.1;
+        for part in parts {
+            schema = match part.starts_with('[') {
+                true => schema.get("additionalProperties")?,
+                false => schema.get("properties")?.get(part)?,
+            };
+        }
+        Some(schema)
+    }
+
+    #[test]
+    fn cel_rules() -> Result<()> {
+        use super::ASSIGNMENTS_RULES;
+
+        let crd = serde_json::to_value(ActiNode::crd())?;
+        let schema = &crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"];
+        let assignments = &schema["properties"]["spec"]["properties"]["assignments"];
+        let rules = assignments["x-kubernetes-validations"]
+            .as_array()
+            .expect("no CEL rules for assignments");
+        assert_eq!(rules.len(), ASSIGNMENTS_RULES.len());
+
+        // Every selected field must exist, and macros must range over lists or maps
+        let vars = [("self", assignments), ("a", &assignments["items"])];
+        for rule in rules {
+            let rule = rule["rule"].as_str().expect("no CEL rule");
+            for (path, is_receiver) in cel_selections(rule) {
+                let field = cel_select(&vars, &path)
+                    .unwrap_or_else(|| panic!("unknown field {path:?} in {rule:?}"));
+                if is_receiver {
+                    assert!(
+                        matches!(field["type"].as_str(), Some("array" | "object")),
+                        "{path:?} is not a list or a map in {rule:?}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pinnings_within_assignments() {
+        let pinning = |cpus| Pinning { cpus, mems: vec![] };
+        let mut an = ActiNode::new("pinnings-within-assignments", Default::default());
+        an.spec.assignments = vec![
+            assignment("a", "main", vec![0, 1]),
+            assignment("a", "sidecar", vec![2]),
+            assignment("b", "main", vec![1, 2]),
+        ];
+        let status = an.status.get_or_insert_with(Default::default);
+        // The containers of Pod "a" are assigned on different cores
+        status.pinnings = [
+            ("default/a".to_owned(), pinning(vec![0, 1, 2])),
+            ("default/b".to_owned(), pinning(vec![2])),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            check_pinnings(&an.spec.assignments, &status.pinnings),
+            Ok(())
+        );
+
+        status
+            .pinnings
+            .insert("default/b".to_owned(), pinning(vec![1, 3]));
+        assert_eq!(
+            check_pinnings(&an.spec.assignments, &status.pinnings),
+            Err(PinningError {
+                pod: "default/b".to_owned(),
+                cpu: 3
+            })
+        );
+        status
+            .pinnings
+            .insert("default/b".to_owned(), pinning(vec![1]));
+        status
+            .pinnings
+            .insert("other/a".to_owned(), pinning(vec![0]));
+        assert!(check_pinnings(&an.spec.assignments, &status.pinnings).is_err());
+        status.pinnings.remove("other/a");
+
+        // Removing the assignments of a pinned Pod is allowed; its pinning lingers until pruned
+        an.spec.assignments.retain(|a| a.name != "a");
+        assert!(an.spec.validate().is_ok());
+        let status = an.status.as_ref().expect("status not initialized");
+        assert_eq!(
+            check_pinnings(&an.spec.assignments, &status.pinnings),
+            Err(PinningError {
+                pod: "default/a".to_owned(),
+                cpu: 0
+            })
+        );
+        assert_eq!(an.prune_pinnings(), vec!["default/a".to_owned()]);
+        assert!(an.prune_pinnings().is_empty());
+        let status = an.status.as_ref().expect("status not initialized");
+        assert_eq!(
+            check_pinnings(&an.spec.assignments, &status.pinnings),
+            Ok(())
+        );
+        assert!(status.pinnings.contains_key("default/b"));
+    }
+
+    #[test]
+    fn policy_defaults() -> Result<()> {
+        use super::{ActiPolicy, AllocationStrategy, DetectionMode, SmtPolicy};
+
+        let mut ap: ActiPolicy = serde_yaml::from_str(
+            r#"
+apiVersion: acti.cslab.ece.ntua.gr/v1alpha1
+kind: ActiPolicy
+metadata:
+  name: policy-defaults
+spec:
+  reservedCpus: [0, 1]
+"#,
+        )?;
+        assert_eq!(ap.spec.reserved_cpus, vec![0, 1]);
+        assert_eq!(ap.spec.smt_policy, SmtPolicy::Allow);
+        assert_eq!(ap.spec.allocation_strategy, AllocationStrategy::Pack);
+        assert!(ap.spec.applies_to("default"));
+        assert!(ap.spec.allows_detection_mode(DetectionMode::Full));
+
+        ap.spec.namespaces = vec!["acti".to_owned()];
+        ap.spec.allowed_detection_modes = vec![DetectionMode::IsolationBoundariesOnly];
+        assert!(!ap.spec.applies_to("default"));
+        assert!(ap.spec.applies_to("acti"));
+        assert!(!ap.spec.allows_detection_mode(DetectionMode::Full));
+        Ok(())
+    }
+
+    #[test]
+    fn reserved_cpus() -> Result<()> {
+        use super::{ActiReservation, ReservationPurpose};
+
+        let mut ar: ActiReservation = serde_yaml::from_str(
+            r#"
+apiVersion: acti.cslab.ece.ntua.gr/v1alpha1
+kind: ActiReservation
+metadata:
+  name: reserved-cpus
+  namespace: acti
+spec:
+  nodeName: node-0
+  cpuSets:
+    - purpose: Kubelet
+      cpus: [0, 1]
+    - purpose: Dpdk
+      cpus: [1, 8, 9]
+      description: poll-mode threads
+"#,
+        )?;
+        assert!(ar.spec.validate().is_ok());
+        assert_eq!(ar.spec.cpu_sets[0].purpose, ReservationPurpose::Kubelet);
+        assert_eq!(
+            ar.spec.reserved_cpus().into_iter().collect::<Vec<_>>(),
+            vec![0, 1, 8, 9]
+        );
+        assert!(ar.spec.is_reserved(8));
+        assert!(!ar.spec.is_reserved(2));
+
+        ar.spec.cpu_sets[1].cpus.clear();
+        assert!(ar.spec.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn placement_elements() -> Result<()> {
+        use actitopo::{ProcessingElement, TopologyBuilder};
+
+        use super::{ActiPlacement, ElementRef};
+
+        // Both packages have a physical core with OS index 0
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0);
+                });
+            })
+            .package(1, |p| {
+                p.core(0, |c| {
+                    c.thread(1);
+                });
+            })
+            .build();
+        let mut ap = ActiPlacement::new("placement-elements", Default::default());
+        ap.spec.pod_name = "pod".to_owned();
+        ap.spec.pod_uid = "pod-uid".to_owned();
+        ap.spec.node_name = "node-0".to_owned();
+        assert!(ap.spec.validate().is_err());
+
+        ap.spec.elements = vec![ElementRef {
+            path: "package:1/core:0".to_owned(),
+        }];
+        assert!(ap.spec.validate().is_ok());
+        let core = ap.spec.elements[0].resolve(&topo).expect("unresolved");
+        assert_eq!(core.processing(), Some(&ProcessingElement::Core(0)));
+        assert_eq!(core.cpuset().to_string(), "1");
+
+        let thread = ElementRef {
+            path: "package:0/core:0/thread:0".to_owned(),
+        };
+        assert!(thread.resolve(&topo).is_some());
+        assert!(ElementRef {
+            path: "package:0/core:1".to_owned()
+        }
+        .resolve(&topo)
+        .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn refresh_summary() {
+        let mut an = ActiNode::new("refresh-summary", Default::default());
+        an.spec.assignments = vec![
+            assignment("a", "main", vec![0, 1]),
+            assignment("a", "sidecar", vec![2]),
+            assignment("b", "main", vec![1, 2]),
+        ];
+        an.refresh_summary();
+        let status = an.status.as_mut().expect("status not initialized");
+        assert_eq!(status.assigned_pods, Some(2));
+        assert_eq!(status.pinned_cpus, Some(0));
+        assert_eq!(status.numa_nodes, None);
+
+        let pinning = |cpus| Pinning {
+            cpus,
+            mems: vec![0],
+        };
+        status.pinnings = [
+            ("default/a".to_owned(), pinning(vec![0, 1, 2])),
+            ("default/b".to_owned(), pinning(vec![1, 2])),
+        ]
+        .into_iter()
+        .collect();
+        an.refresh_summary();
+        assert_eq!(an.status.as_ref().and_then(|s| s.pinned_cpus), Some(3));
+    }
+
+    #[test]
+    fn topology_roundtrip() -> Result<()> {
+        use actitopo::Topology;
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../../actitopo/test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let mut an = ActiNode::new("topology-roundtrip", Default::default());
+        an.spec.topology = Some(topo.clone());
+
+        let yaml = serde_yaml::to_string(&an)?;
+        assert!(!yaml.contains("partialTopology"));
+        let de: ActiNode = serde_yaml::from_str(&yaml)?;
+        assert_eq!(de.spec.topology, Some(topo));
+        assert_eq!(de.spec.partial_topology, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn topology_schema() -> Result<()> {
+        let crd = serde_json::to_value(ActiNode::crd())?;
+        let spec = &crd["spec"]["versions"][0]["schema"]["openAPIV3Schema"]["properties"]["spec"];
+        for field in ["topology", "partialTopology"] {
+            let element =
+                &spec["properties"][field]["properties"]["nodes"]["items"]["properties"]["data"];
+            assert_eq!(element["x-kubernetes-preserve-unknown-fields"], true);
+            assert_eq!(
+                element["properties"]["processing"]["properties"]["kind"]["enum"],
+                serde_json::json!(["package", "die", "numanode", "core", "thread"])
+            );
+            // Known kinds, plus one for the unknown ones
+            assert_eq!(element["oneOf"].as_array().map(Vec::len), Some(5));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,81 @@
+use actitopo::{Element, Topology};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// ActiPlacementSpec binds a Pod to specific hardware topology elements of a Node.
+///
+/// ActiPlacements are created by ActiK8s' scheduler extender and consumed by ActiK8s' `internal`
+/// controller, which enforces them.
+#[derive(
+    CustomResource, Serialize, Deserialize, Debug, Default, PartialEq, Clone, JsonSchema, Validate,
+)]
+#[kube(
+    group = "acti.cslab.ece.ntua.gr",
+    version = "v1alpha1",
+    kind = "ActiPlacement",
+    namespaced,
+    status = "ActiPlacementStatus",
+    derive = "PartialEq",
+    derive = "Default",
+    shortname = "apl",
+    shortname = "aplace",
+    printcolumn = r#"{"name":"Pod", "type":"string", "jsonPath":".spec.podName"}"#,
+    printcolumn = r#"{"name":"Node", "type":"string", "jsonPath":".spec.nodeName"}"#,
+    printcolumn = r#"{"name":"Enforced", "type":"string", "jsonPath":".status.conditions[?(@.type==\"Enforced\")].status"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiPlacementSpec {
+    /// PodName is the name of the placed Pod, which resides in the ActiPlacement's namespace.
+    #[validate(length(min = 1, max = 253))]
+    pub pod_name: String,
+
+    /// PodUid is the UID of the placed Pod.
+    #[validate(length(min = 1))]
+    pub pod_uid: String,
+
+    /// NodeName is the name of the Node where the Pod is placed.
+    #[validate(length(min = 1, max = 253))]
+    pub node_name: String,
+
+    /// Elements are the hardware topology elements of the Node that the Pod is bound to.
+    #[validate(length(min = 1))]
+    pub elements: Vec<ElementRef>,
+}
+
+/// ActiPlacementStatus describes the observed state of an ActiPlacement.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiPlacementStatus {
+    /// Conditions describe the latest observations of the ActiPlacement's state (e.g., an
+    /// `Enforced` condition).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+}
+
+/// The type of the condition that signifies that an ActiPlacement has been enforced.
+pub const ENFORCED_CONDITION: &str = "Enforced";
+
+/// ElementRef refers to a hardware topology element of a Node by its path in the Node's topology.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementRef {
+    /// Path is the path of the element from the root of the Node's topology, as produced by
+    /// `actitopo::Topology::path_of` (e.g., `package:1/numa:2/l3:5/core:3`).
+    ///
+    /// Unlike the OS indices of some elements (e.g., those of physical cores, which are not
+    /// necessarily unique across packages), it refers to exactly one element.
+    pub path: String,
+}
+
+impl ElementRef {
+    /// Returns the `actitopo::Element` of the given `actitopo::Topology` that is referred to, if
+    /// any.
+    pub fn resolve<'topo>(&self, topology: &'topo Topology) -> Option<&'topo Element> {
+        let id = topology.resolve_path(&self.path)?;
+        topology.tree().get_by_id(&id)
+    }
+}
@@ -0,0 +1,114 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// ActiPolicySpec defines the pinning policy that ActiK8s' components should follow, either
+/// cluster-wide or for specific namespaces.
+#[derive(
+    CustomResource, Serialize, Deserialize, Debug, Default, PartialEq, Clone, JsonSchema, Validate,
+)]
+#[kube(
+    group = "acti.cslab.ece.ntua.gr",
+    version = "v1alpha1",
+    kind = "ActiPolicy",
+    derive = "PartialEq",
+    derive = "Default",
+    shortname = "ap",
+    shortname = "apol",
+    printcolumn = r#"{"name":"SMT", "type":"string", "jsonPath":".spec.smtPolicy"}"#,
+    printcolumn = r#"{"name":"Strategy", "type":"string", "jsonPath":".spec.allocationStrategy"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiPolicySpec {
+    /// Namespaces are the namespaces that the ActiPolicy applies to; if empty, it applies to the
+    /// whole cluster.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<String>,
+
+    /// ReservedCpus are the OS indices of the cores that are reserved for system daemons on every
+    /// Node, and therefore must never be assigned to Pods.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reserved_cpus: Vec<u32>,
+
+    /// SmtPolicy regulates how the hardware threads of the same physical core may be assigned.
+    #[serde(default)]
+    pub smt_policy: SmtPolicy,
+
+    /// AllowedDetectionModes are the hardware topology detection modes that ActiK8s' registrant
+    /// may use; if empty, any of them may be used.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_detection_modes: Vec<DetectionMode>,
+
+    /// AllocationStrategy is the strategy used by default when allocating cores to Pods.
+    #[serde(default)]
+    pub allocation_strategy: AllocationStrategy,
+}
+
+impl ActiPolicySpec {
+    /// Returns `true` if the ActiPolicy applies to the given namespace.
+    pub fn applies_to(&self, namespace: &str) -> bool {
+        self.namespaces.is_empty() || self.namespaces.iter().any(|ns| ns == namespace)
+    }
+
+    /// Returns `true` if the given hardware topology detection mode is allowed.
+    pub fn allows_detection_mode(&self, mode: DetectionMode) -> bool {
+        self.allowed_detection_modes.is_empty() || self.allowed_detection_modes.contains(&mode)
+    }
+}
+
+/// SmtPolicy regulates how the hardware threads (i.e., SMT siblings) of the same physical core may
+/// be assigned to Pods.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash, Clone, Copy, JsonSchema)]
+pub enum SmtPolicy {
+    /// The hardware threads of the same physical core may be assigned to different Pods.
+    #[default]
+    Allow,
+
+    /// Only whole physical cores (i.e., all of their hardware threads) may be assigned to a Pod.
+    FullCores,
+
+    /// Only a single hardware thread of each physical core may be assigned, while its siblings are
+    /// left idle.
+    SingleThread,
+}
+
+/// DetectionMode mirrors `actitopo::DetectionMode`, so that it can be part of an ActiPolicy.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, JsonSchema)]
+pub enum DetectionMode {
+    /// See `actitopo::DetectionMode::Full`.
+    Full,
+
+    /// See `actitopo::DetectionMode::IsolationBoundariesOnly`.
+    IsolationBoundariesOnly,
+
+    /// See `actitopo::DetectionMode::CoresOnly`.
+    CoresOnly,
+
+    /// See `actitopo::DetectionMode::NumaOnly`.
+    NumaOnly,
+}
+
+#[cfg(feature = "detect")]
+impl From<DetectionMode> for actitopo::DetectionMode {
+    fn from(mode: DetectionMode) -> Self {
+        match mode {
+            DetectionMode::Full => Self::Full,
+            DetectionMode::IsolationBoundariesOnly => Self::IsolationBoundariesOnly,
+            DetectionMode::CoresOnly => Self::CoresOnly,
+            DetectionMode::NumaOnly => Self::NumaOnly,
+        }
+    }
+}
+
+/// AllocationStrategy regulates how cores are picked when allocating them to Pods.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash, Clone, Copy, JsonSchema)]
+pub enum AllocationStrategy {
+    /// Pack each Pod into as few isolation boundaries (e.g., caches, NUMA nodes) as possible.
+    #[default]
+    Pack,
+
+    /// Spread each Pod across as many isolation boundaries (e.g., caches, NUMA nodes) as possible.
+    Spread,
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeSet;
+
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// ActiReservationSpec declares the cores of a Node that are statically reserved for purposes other
+/// than running Pods, and therefore must be subtracted from the cores available for allocation.
+#[derive(
+    CustomResource, Serialize, Deserialize, Debug, Default, PartialEq, Clone, JsonSchema, Validate,
+)]
+#[kube(
+    group = "acti.cslab.ece.ntua.gr",
+    version = "v1alpha1",
+    kind = "ActiReservation",
+    namespaced,
+    derive = "PartialEq",
+    derive = "Default",
+    shortname = "ar",
+    shortname = "ares",
+    printcolumn = r#"{"name":"Node", "type":"string", "jsonPath":".spec.nodeName"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiReservationSpec {
+    /// NodeName is the name of the Node where the cores are reserved.
+    #[validate(length(min = 1, max = 253))]
+    pub node_name: String,
+
+    /// CpuSets are the sets of reserved cores, along with the purpose of each of them.
+    #[validate]
+    pub cpu_sets: Vec<ReservedCpuSet>,
+}
+
+impl ActiReservationSpec {
+    /// Returns the OS indices of all reserved cores, regardless of their purpose.
+    pub fn reserved_cpus(&self) -> BTreeSet<u32> {
+        self.cpu_sets
+            .iter()
+            .flat_map(|set| set.cpus.iter().copied())
+            .collect()
+    }
+
+    /// Returns `true` if the core with the given OS index is reserved.
+    pub fn is_reserved(&self, cpu: u32) -> bool {
+        self.cpu_sets.iter().any(|set| set.cpus.contains(&cpu))
+    }
+}
+
+/// ReservedCpuSet is a set of cores that are reserved for a specific purpose.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, JsonSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedCpuSet {
+    /// Purpose is what the cores are reserved for.
+    #[serde(default)]
+    pub purpose: ReservationPurpose,
+
+    /// Cpus are the OS indices of the reserved cores.
+    #[validate(length(min = 1))]
+    pub cpus: Vec<u32>,
+
+    /// Description is an optional, human-readable note about the reservation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// ReservationPurpose is what a [`ReservedCpuSet`] is reserved for.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash, Clone, Copy, JsonSchema)]
+pub enum ReservationPurpose {
+    /// The cores are reserved for the kubelet and other system daemons.
+    Kubelet,
+
+    /// The cores are reserved for handling interrupts.
+    Irq,
+
+    /// The cores are reserved for DPDK (or similar) poll-mode threads.
+    Dpdk,
+
+    /// The cores are reserved for any other purpose.
+    #[default]
+    Other,
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use immutree::NodeId;
+
+use crate::{CpuSet, Error, ProcessingElement, Topology};
+
+/// The policy that [`Topology::allocate`] places the allocated physical cores according to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AllocationPolicy {
+    /// Pack the physical cores within the narrowest isolation boundary that fits them all; i.e.,
+    /// an L3 cache, or else a NUMA node, or else a package (or, as a last resort, the machine).
+    #[default]
+    Pack,
+    /// Spread the physical cores as evenly as possible across the NUMA nodes (or, in their
+    /// absence, across the packages) of the machine.
+    Spread,
+}
+
+/// A physical core that is available for allocation.
+struct FreeCore {
+    /// The [`NodeId`] of the element that represents the physical core.
+    id: NodeId,
+    /// The OS indices of its hardware threads, in ascending order.
+    cpus: Vec<u32>,
+}
+
+impl Topology {
+    /// Allocates `n_cores` physical cores, none of whose hardware threads has already been
+    /// assigned (or is offline), placing them according to the given [`AllocationPolicy`], and
+    /// returns the OS indices of their hardware threads, in ascending order.
+    ///
+    /// Physical cores are identified as in [`Topology::threads_per_core`]; the same physical
+    /// cores are always picked for the same input.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::InsufficientCores`] is returned if fewer than `n_cores` physical cores are
+    /// free.
+    pub fn allocate(
+        &self,
+        n_cores: usize,
+        already_assigned: &CpuSet,
+        policy: AllocationPolicy,
+    ) -> Result<Vec<u32>, Error> {
+        let free_cores = self.free_cores(already_assigned);
+        if free_cores.len() < n_cores {
+            return Err(Error::InsufficientCores {
+                requested: n_cores,
+                available: free_cores.len(),
+            });
+        }
+
+        let allocated = match policy {
+            AllocationPolicy::Pack => self.pack(free_cores, n_cores),
+            AllocationPolicy::Spread => self.spread(free_cores, n_cores),
+        };
+        let mut cpus: Vec<_> = allocated.into_iter().flat_map(|core| core.cpus).collect();
+        cpus.sort_unstable();
+        Ok(cpus)
+    }
+
+    /// Returns the physical cores none of whose hardware threads has already been assigned (or is
+    /// offline), ordered by the OS index of their first hardware thread.
+    fn free_cores(&self, already_assigned: &CpuSet) -> Vec<FreeCore> {
+        let mut unavailable = already_assigned.clone();
+        let mut cores: BTreeMap<NodeId, Vec<u32>> = BTreeMap::new();
+        for (thread_id, thread) in self.threads() {
+            if let ProcessingElement::Thread(os_index) = thread {
+                let core_id = self.physical_core_of(&thread_id).unwrap_or(thread_id);
+                cores.entry(core_id).or_default().push(*os_index);
+                if matches!(self.tree.get_by_id(&thread_id), Some(elem) if elem.is_offline()) {
+                    unavailable.insert(*os_index);
+                }
+            }
+        }
+        let mut free_cores: Vec<_> = cores
+            .into_iter()
+            .filter(|(_, cpus)| cpus.iter().all(|cpu| !unavailable.contains(*cpu)))
+            .map(|(id, mut cpus)| {
+                cpus.sort_unstable();
+                FreeCore { id, cpus }
+            })
+            .collect();
+        free_cores.sort_by_key(|core| core.cpus[0]);
+        free_cores
+    }
+
+    /// Picks `n_cores` of the given free physical cores, all within the narrowest isolation
+    /// boundary that fits them; among boundaries of the same kind, the one with the fewest free
+    /// physical cores is preferred, to limit fragmentation.
+    fn pack(&self, free_cores: Vec<FreeCore>, n_cores: usize) -> Vec<FreeCore> {
+        let boundaries: [Vec<NodeId>; 4] = [
+            self.l3_cache_ids().collect(),
+            self.numa_node_ids().collect(),
+            self.package_ids().collect(),
+            self.tree.root_id().into_iter().collect(),
+        ];
+        let is_under = |boundary_id: &NodeId, core: &FreeCore| {
+            *boundary_id == core.id
+                || matches!(self.tree.is_ancestor_of(boundary_id, &core.id), Ok(true))
+        };
+        let best_fit = boundaries.iter().find_map(|ids| {
+            ids.iter()
+                .map(|id| {
+                    (
+                        id,
+                        free_cores.iter().filter(|core| is_under(id, core)).count(),
+                    )
+                })
+                .filter(|&(_, count)| count >= n_cores)
+                .min_by_key(|&(_, count)| count)
+                .map(|(id, _)| *id)
+        });
+        match best_fit {
+            Some(boundary_id) => free_cores
+                .into_iter()
+                .filter(|core| is_under(&boundary_id, core))
+                .take(n_cores)
+                .collect(),
+            None => free_cores.into_iter().take(n_cores).collect(),
+        }
+    }
+
+    /// Picks `n_cores` of the given free physical cores, one at a time from the NUMA node (or
+    /// package) with the most free physical cores left.
+    fn spread(&self, free_cores: Vec<FreeCore>, n_cores: usize) -> Vec<FreeCore> {
+        let has_numa_nodes = self.numa_node_ids().next().is_some();
+        let mut groups: BTreeMap<Option<NodeId>, Vec<FreeCore>> = BTreeMap::new();
+        for core in free_cores.into_iter().rev() {
+            let group_id = if has_numa_nodes {
+                self.numa_node_of(core.id)
+            } else {
+                self.package_of(core.id)
+            };
+            groups.entry(group_id).or_default().push(core);
+        }
+
+        // Each group's cores are in descending order, so that the next one is popped each time
+        let mut allocated = Vec::with_capacity(n_cores);
+        while allocated.len() < n_cores {
+            let next_group = groups
+                .values_mut()
+                .rev()
+                .max_by_key(|cores| cores.len())
+                .expect("fewer free cores than requested");
+            allocated.extend(next_group.pop());
+        }
+        allocated
+    }
+}
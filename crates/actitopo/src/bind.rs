@@ -0,0 +1,48 @@
+use std::{io, mem};
+
+use immutree::NodeId;
+
+use crate::{Error, Topology};
+
+impl Topology {
+    /// Binds the current thread to the hardware threads of the element stored under the given
+    /// [`NodeId`] (e.g., to those of a housekeeping isolation domain), through
+    /// `sched_setaffinity(2)`.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::ImmuTree`] is returned if the given [`NodeId`] does not correspond to an
+    /// element of the topology, while an [`Error::Binding`] is returned if the element has no
+    /// hardware threads, or if `sched_setaffinity(2)` fails (e.g., because none of them is
+    /// allowed by the cgroup of the current process).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub fn bind_current_thread(&self, node: NodeId) -> Result<(), Error> {
+        let cpuset = self
+            .tree
+            .get_by_id(&node)
+            .ok_or(immutree::Error::InvalidNodeId(node))?
+            .cpuset();
+        if cpuset.is_empty() {
+            return Err(Error::Binding(format!(
+                "element {node} has no hardware threads"
+            )));
+        }
+
+        // SAFETY: cpu_set_t is a plain bitmask, for which all-zeroes is the empty set
+        let mut mask: libc::cpu_set_t = unsafe { mem::zeroed() };
+        for cpu in cpuset {
+            if cpu as usize >= libc::CPU_SETSIZE as usize {
+                return Err(Error::Binding(format!("CPU {cpu} exceeds CPU_SETSIZE")));
+            }
+            // SAFETY: cpu has just been checked to be within the bounds of the mask
+            unsafe { libc::CPU_SET(cpu as usize, &mut mask) };
+        }
+        // SAFETY: mask is a valid cpu_set_t of the given size; pid 0 is the calling thread
+        let ret = unsafe { libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mask) };
+        if ret != 0 {
+            return Err(Error::Binding(io::Error::last_os_error().to_string()));
+        }
+        Ok(())
+    }
+}
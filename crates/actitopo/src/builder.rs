@@ -0,0 +1,229 @@
+use immutree::{InsertMode, NodeId, Tree};
+
+use crate::{
+    CacheAttributes, CacheKind, CacheLevel, CpuSet, Element, IoDeviceKind, ProcessingElement,
+    Topology,
+};
+
+/// A builder for synthetic [`Topology`]s, which are constructed programmatically rather than
+/// detected through `libhwloc2-rs` (e.g., for testing components that consume them).
+///
+/// Elements are added under the root [`Machine`] (or under each other) through closures, which
+/// are given an [`ElementBuilder`] to add the children of the new element with. The [`CpuSet`]s
+/// of all elements are computed out of the [`Thread`]s under them when the [`Topology`] is built,
+/// and caches are assigned logical indices per level, in the order they are added.
+///
+/// # Example
+///
+/// ```
+/// use actitopo::{CacheLevel, TopologyBuilder};
+///
+/// let topo = TopologyBuilder::machine()
+///     .package(0, |p| {
+///         p.numa(0, |n| {
+///             n.cache(CacheLevel::L2, |c| {
+///                 c.core(0, |c| {
+///                     c.thread(0).thread(2);
+///                 });
+///             })
+///             .cache(CacheLevel::L2, |c| {
+///                 c.core(1, |c| {
+///                     c.thread(1).thread(3);
+///                 });
+///             });
+///         });
+///     })
+///     .build();
+/// assert_eq!(topo.thread_ids().count(), 4);
+/// assert_eq!(topo.cpu_os_indices_under(0), [0, 1, 2, 3]);
+/// ```
+///
+/// [`Machine`]: crate::Element::Machine
+/// [`Thread`]: crate::ProcessingElement::Thread
+#[derive(Debug)]
+pub struct TopologyBuilder {
+    state: State,
+    root_id: NodeId,
+}
+
+/// Adds children to an element of a [`Topology`] under construction by a [`TopologyBuilder`].
+#[derive(Debug)]
+pub struct ElementBuilder<'b> {
+    state: &'b mut State,
+    id: NodeId,
+}
+
+#[derive(Debug)]
+struct State {
+    tree: Tree<Element>,
+    /// The next logical index of each cache level.
+    cache_indices: [u32; 5],
+}
+
+impl TopologyBuilder {
+    /// Creates a new [`TopologyBuilder`], whose root element is the [`Machine`].
+    ///
+    /// [`Machine`]: crate::Element::Machine
+    pub fn machine() -> Self {
+        let (tree, root_id) = Tree::with_root(Element::Machine {
+            cpuset: CpuSet::new(),
+            info: None,
+        });
+        Self {
+            state: State {
+                tree,
+                cache_indices: [0; 5],
+            },
+            root_id,
+        }
+    }
+
+    /// Adds a [`Package`] with the given OS index under the [`Machine`].
+    ///
+    /// [`Package`]: crate::ProcessingElement::Package
+    /// [`Machine`]: crate::Element::Machine
+    pub fn package<F: FnOnce(&mut ElementBuilder<'_>)>(mut self, os_index: u32, f: F) -> Self {
+        self.root().package(os_index, f);
+        self
+    }
+
+    /// Adds a [`NumaNode`] with the given OS index under the [`Machine`].
+    ///
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`Machine`]: crate::Element::Machine
+    pub fn numa<F: FnOnce(&mut ElementBuilder<'_>)>(mut self, os_index: u32, f: F) -> Self {
+        self.root().numa(os_index, f);
+        self
+    }
+
+    /// Adds a [`Cache`] of the given level under the [`Machine`].
+    ///
+    /// [`Cache`]: crate::Element::Cache
+    /// [`Machine`]: crate::Element::Machine
+    pub fn cache<F: FnOnce(&mut ElementBuilder<'_>)>(mut self, level: CacheLevel, f: F) -> Self {
+        self.root().cache(level, f);
+        self
+    }
+
+    /// Adds an [`IoDevice`] of the given kind and name under the [`Machine`].
+    ///
+    /// [`IoDevice`]: crate::Element::IoDevice
+    /// [`Machine`]: crate::Element::Machine
+    pub fn io_device(mut self, kind: IoDeviceKind, name: &str) -> Self {
+        self.root().io_device(kind, name);
+        self
+    }
+
+    /// Builds the [`Topology`], computing the [`CpuSet`]s of all of its elements.
+    pub fn build(self) -> Topology {
+        let mut tree = self.state.tree;
+        Topology::fill_cpusets(&mut tree, &self.root_id, &CpuSet::new());
+        Topology::new(tree)
+    }
+
+    fn root(&mut self) -> ElementBuilder<'_> {
+        ElementBuilder {
+            state: &mut self.state,
+            id: self.root_id,
+        }
+    }
+}
+
+impl ElementBuilder<'_> {
+    /// Adds a [`Package`] with the given OS index under the current element.
+    ///
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub fn package<F: FnOnce(&mut ElementBuilder<'_>)>(
+        &mut self,
+        os_index: u32,
+        f: F,
+    ) -> &mut Self {
+        self.child(ProcessingElement::Package(os_index).into(), f)
+    }
+
+    /// Adds a [`Die`] with the given OS index under the current element.
+    ///
+    /// [`Die`]: crate::ProcessingElement::Die
+    pub fn die<F: FnOnce(&mut ElementBuilder<'_>)>(&mut self, os_index: u32, f: F) -> &mut Self {
+        self.child(ProcessingElement::Die(os_index).into(), f)
+    }
+
+    /// Adds a [`NumaNode`] with the given OS index under the current element.
+    ///
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    pub fn numa<F: FnOnce(&mut ElementBuilder<'_>)>(&mut self, os_index: u32, f: F) -> &mut Self {
+        self.child(ProcessingElement::NumaNode(os_index).into(), f)
+    }
+
+    /// Adds a [`Cache`] of the given level (with default attributes) under the current element.
+    ///
+    /// [`Cache`]: crate::Element::Cache
+    pub fn cache<F: FnOnce(&mut ElementBuilder<'_>)>(
+        &mut self,
+        level: CacheLevel,
+        f: F,
+    ) -> &mut Self {
+        self.cache_with_attributes(level, CacheAttributes::default(), f)
+    }
+
+    /// Adds a [`Cache`] of the given level and attributes under the current element.
+    ///
+    /// [`Cache`]: crate::Element::Cache
+    pub fn cache_with_attributes<F: FnOnce(&mut ElementBuilder<'_>)>(
+        &mut self,
+        level: CacheLevel,
+        attributes: CacheAttributes,
+        f: F,
+    ) -> &mut Self {
+        let next_index = &mut self.state.cache_indices[level as usize];
+        let logical_index = *next_index;
+        *next_index += 1;
+        let cache = Element::Cache {
+            level,
+            kind: CacheKind::Unified,
+            logical_index,
+            attributes,
+            cpuset: CpuSet::new(),
+        };
+        self.child(cache, f)
+    }
+
+    /// Adds a [`Core`] with the given OS index under the current element.
+    ///
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub fn core<F: FnOnce(&mut ElementBuilder<'_>)>(&mut self, os_index: u32, f: F) -> &mut Self {
+        self.child(ProcessingElement::Core(os_index).into(), f)
+    }
+
+    /// Adds a [`Thread`] with the given OS index under the current element.
+    ///
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn thread(&mut self, os_index: u32) -> &mut Self {
+        self.child(ProcessingElement::Thread(os_index).into(), |_| {})
+    }
+
+    /// Adds an [`IoDevice`] of the given kind and name under the current element.
+    ///
+    /// [`IoDevice`]: crate::Element::IoDevice
+    pub fn io_device(&mut self, kind: IoDeviceKind, name: &str) -> &mut Self {
+        let io_device = Element::IoDevice {
+            kind,
+            name: name.to_owned(),
+            cpuset: CpuSet::new(),
+        };
+        self.child(io_device, |_| {})
+    }
+
+    fn child<F: FnOnce(&mut ElementBuilder<'_>)>(&mut self, elem: Element, f: F) -> &mut Self {
+        let id = self
+            .state
+            .tree
+            .insert(elem, InsertMode::Under(&self.id))
+            .expect("NodeId out of bounds");
+        f(&mut ElementBuilder {
+            state: self.state,
+            id,
+        });
+        self
+    }
+}
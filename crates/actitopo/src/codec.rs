@@ -0,0 +1,174 @@
+use std::io::{Read, Write};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use immutree::NodeId;
+use serde::{ser, Serialize, Serializer};
+
+use crate::{versioned::VersionedTopologyRef, Error, Topology, VersionedTopology};
+
+/// The maximum size (in bytes) that an annotation's value may be decompressed into, so that a
+/// small (e.g., maliciously crafted) value cannot be inflated without bound.
+///
+/// Since the annotations of a Kubernetes object are limited to 256 KiB in total, this allows for
+/// the JSON representations of topologies of thousands of hardware threads, while capping the
+/// memory that decoding a value may take.
+pub const MAX_DECOMPRESSED_LEN: u64 = 16 << 20;
+
+impl Topology {
+    /// Serializes the topology into its (compact) JSON representation.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Encoding`] is returned if serialization fails.
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|err| Error::Encoding(err.to_string()))
+    }
+
+    /// Serializes the topology into its pretty-printed JSON representation.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Encoding`] is returned if serialization fails.
+    pub fn to_json_pretty(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(|err| Error::Encoding(err.to_string()))
+    }
+
+    /// Deserializes (and validates) a topology out of its JSON representation, as produced by
+    /// [`Topology::to_json`] or [`Topology::to_json_pretty`].
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Decoding`] is returned if deserialization or validation fails.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|err| Error::Decoding(err.to_string()))
+    }
+
+    /// Serializes only the subtrees rooted at the elements stored under the given [`NodeId`]s,
+    /// along with the root [`Machine`], through the given `Serializer`; the result is a valid
+    /// (albeit partial) serialized topology (see [`Topology::subtrees`]).
+    ///
+    /// For example, only the NUMA nodes that are allocated to ActiK8s can be published this way,
+    /// rather than the whole topology of the machine.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any of the given [`NodeId`]s does not correspond to an element of
+    /// the topology, or if serialization fails.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Machine`]: crate::Element::Machine
+    pub fn serialize_subtrees<S: Serializer>(
+        &self,
+        ids: &[NodeId],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.subtrees(ids)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    /// Serializes the topology into its YAML representation.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Encoding`] is returned if serialization fails.
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> Result<String, Error> {
+        serde_yaml::to_string(self).map_err(|err| Error::Encoding(err.to_string()))
+    }
+
+    /// Deserializes (and validates) a topology out of its YAML representation, as produced by
+    /// [`Topology::to_yaml`].
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Decoding`] is returned if deserialization or validation fails.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self, Error> {
+        serde_yaml::from_str(yaml).map_err(|err| Error::Decoding(err.to_string()))
+    }
+
+    /// Serializes the topology into a compact binary representation (i.e., CBOR), wrapped in a
+    /// [`VersionedTopology`] envelope.
+    ///
+    /// It is considerably denser than JSON, hence suitable for transferring large topologies
+    /// (e.g., over gRPC).
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Encoding`] is returned if serialization fails.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&VersionedTopologyRef(self), &mut bytes)
+            .map_err(|err| Error::Encoding(err.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Deserializes a topology out of its compact binary representation, as produced by
+    /// [`Topology::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Decoding`] is returned if deserialization fails (e.g., because the given bytes
+    /// are malformed, or because of an unsupported schema version).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        ciborium::de::from_reader(bytes)
+            .map(VersionedTopology::into_inner)
+            .map_err(|err: ciborium::de::Error<_>| Error::Decoding(err.to_string()))
+    }
+
+    /// Serializes the topology into a string that is suitable for a Kubernetes annotation; i.e.,
+    /// its JSON representation, wrapped in a [`VersionedTopology`] envelope, gzip-compressed and
+    /// then base64-encoded.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Encoding`] is returned if serialization or compression fails.
+    pub fn to_annotation_string(&self) -> Result<String, Error> {
+        let encoding_err = |err: std::io::Error| Error::Encoding(err.to_string());
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        serde_json::to_writer(&mut encoder, &VersionedTopologyRef(self))
+            .map_err(|err| Error::Encoding(err.to_string()))?;
+        encoder.flush().map_err(encoding_err)?;
+        Ok(BASE64.encode(encoder.finish().map_err(encoding_err)?))
+    }
+
+    /// Deserializes a topology out of a Kubernetes annotation's value, as produced by
+    /// [`Topology::to_annotation_string`].
+    ///
+    /// Legacy (i.e., uncompressed) values, which consist of the raw JSON representation of the
+    /// topology, are transparently detected and deserialized as well; including the ones written
+    /// before elements carried their [`CpuSet`]s (see the [`Deserialize`] implementation of
+    /// [`Topology`]).
+    ///
+    /// [`CpuSet`]: crate::CpuSet
+    /// [`Deserialize`]: serde::Deserialize
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Decoding`] is returned if decoding, decompression or deserialization fails, or
+    /// if the value decompresses into more than [`MAX_DECOMPRESSED_LEN`] bytes.
+    pub fn from_annotation_string(value: &str) -> Result<Self, Error> {
+        let decoding_err = |err: &dyn std::error::Error| Error::Decoding(err.to_string());
+        let value = value.trim();
+        if value.starts_with('{') {
+            return serde_json::from_str(value).map_err(|err| decoding_err(&err));
+        }
+
+        let compressed = BASE64.decode(value).map_err(|err| decoding_err(&err))?;
+        let mut json = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .take(MAX_DECOMPRESSED_LEN + 1)
+            .read_to_end(&mut json)
+            .map_err(|err| decoding_err(&err))?;
+        if json.len() as u64 > MAX_DECOMPRESSED_LEN {
+            return Err(Error::Decoding(format!(
+                "annotation value decompresses into more than {MAX_DECOMPRESSED_LEN} bytes"
+            )));
+        }
+        serde_json::from_slice(&json)
+            .map(VersionedTopology::into_inner)
+            .map_err(|err| decoding_err(&err))
+    }
+}
@@ -0,0 +1,275 @@
+use std::{fmt, iter::FusedIterator, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Error;
+
+const WORD_BITS: u32 = u64::BITS;
+
+/// A set of hardware threads (i.e., logical cores), identified by their OS indices.
+///
+/// It is stored as a bitmask, and it is (de)serialized as a "cpulist" string (e.g., `0-5,12-17`),
+/// in the format used by the Linux kernel (e.g., in sysfs, or in cgroups' `cpuset.cpus`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CpuSet {
+    /// The bitmask; it never ends with a zero word, so that equal sets are represented equally.
+    words: Vec<u64>,
+}
+
+impl CpuSet {
+    /// The maximum number of hardware threads that a parsed [`CpuSet`] may refer to (i.e., the
+    /// maximum `NR_CPUS` of the Linux kernel); cpulists with greater OS indices are rejected, since
+    /// the size of the bitmask grows with the greatest OS index in the set.
+    pub const MAX_CPUS: u32 = 8192;
+
+    /// Creates a new, empty [`CpuSet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given OS index to the set, returning `true` if it was not already present.
+    ///
+    /// # Note
+    ///
+    /// The memory that the set occupies grows with the greatest OS index in it; unlike parsing, no
+    /// bound is enforced here, hence OS indices that originate from untrusted sources should be
+    /// checked against [`CpuSet::MAX_CPUS`] beforehand.
+    pub fn insert(&mut self, cpu: u32) -> bool {
+        let (word, bit) = Self::position(cpu);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let inserted = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        inserted
+    }
+
+    /// Removes the given OS index from the set, returning `true` if it was present.
+    pub fn remove(&mut self, cpu: u32) -> bool {
+        let (word, bit) = Self::position(cpu);
+        match self.words.get_mut(word) {
+            Some(w) if *w & bit != 0 => {
+                *w &= !bit;
+                self.trim();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the given OS index is in the set.
+    pub fn contains(&self, cpu: u32) -> bool {
+        let (word, bit) = Self::position(cpu);
+        matches!(self.words.get(word), Some(w) if w & bit != 0)
+    }
+
+    /// Returns the number of OS indices in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Adds all OS indices of the `other` set to this one.
+    pub fn union_with(&mut self, other: &Self) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (w, o) in self.words.iter_mut().zip(&other.words) {
+            *w |= o;
+        }
+    }
+
+    /// Retains only the OS indices of this set that are also in the `other` one.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.words.truncate(other.words.len());
+        for (w, o) in self.words.iter_mut().zip(&other.words) {
+            *w &= o;
+        }
+        self.trim();
+    }
+
+    /// Returns `true` if all OS indices of this set are also in the `other` one.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.len() <= other.words.len()
+            && self
+                .words
+                .iter()
+                .zip(&other.words)
+                .all(|(w, o)| w & !o == 0)
+    }
+
+    /// Returns an iterator over the OS indices in the set, in ascending order.
+    pub fn iter(&self) -> CpuSetIter<'_> {
+        CpuSetIter {
+            words: &self.words,
+            word: 0,
+            curr: self.words.first().copied().unwrap_or(0),
+        }
+    }
+
+    /// Adds all OS indices in the range `start..=end` to the set, a word at a time.
+    fn insert_range(&mut self, start: u32, end: u32) {
+        let (first, last) = ((start / WORD_BITS) as usize, (end / WORD_BITS) as usize);
+        if last >= self.words.len() {
+            self.words.resize(last + 1, 0);
+        }
+        for (word, w) in self.words[first..=last].iter_mut().enumerate() {
+            let lo = if word == 0 { start % WORD_BITS } else { 0 };
+            let hi = if first + word == last {
+                end % WORD_BITS
+            } else {
+                WORD_BITS - 1
+            };
+            *w |= (u64::MAX >> (WORD_BITS - 1 - hi)) & (u64::MAX << lo);
+        }
+    }
+
+    fn position(cpu: u32) -> (usize, u64) {
+        ((cpu / WORD_BITS) as usize, 1 << (cpu % WORD_BITS))
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+}
+
+impl FromIterator<u32> for CpuSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl From<&[u32]> for CpuSet {
+    fn from(cpus: &[u32]) -> Self {
+        cpus.iter().copied().collect()
+    }
+}
+
+impl From<&CpuSet> for Vec<u32> {
+    /// Returns the OS indices in the set, in ascending order.
+    fn from(set: &CpuSet) -> Self {
+        set.iter().collect()
+    }
+}
+
+impl Extend<u32> for CpuSet {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        for cpu in iter {
+            self.insert(cpu);
+        }
+    }
+}
+
+impl<'set> IntoIterator for &'set CpuSet {
+    type Item = u32;
+    type IntoIter = CpuSetIter<'set>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for CpuSet {
+    /// Formats the set as a cpulist string (e.g., `0-5,12-17`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iter = self.iter().peekable();
+        let mut first = true;
+        while let Some(start) = iter.next() {
+            let mut end = start;
+            while iter.peek() == Some(&(end + 1)) {
+                end = iter.next().expect("peeked element vanished");
+            }
+            if !first {
+                write!(f, ",")?;
+            }
+            first = false;
+            match end - start {
+                0 => write!(f, "{start}")?,
+                _ => write!(f, "{start}-{end}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CpuSet {
+    type Err = Error;
+
+    /// Parses a cpulist string (e.g., `0-5,12-17`); whitespace around each entry is ignored.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::InvalidCpuList`] is returned if the string is malformed, or if it refers to OS
+    /// indices that are not lower than [`CpuSet::MAX_CPUS`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidCpuList(s.to_owned());
+        let cpu = |cpu: &str| match cpu.trim().parse::<u32>() {
+            Ok(cpu) if cpu < Self::MAX_CPUS => Ok(cpu),
+            _ => Err(invalid()),
+        };
+        let mut set = Self::new();
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            match entry.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (cpu(start)?, cpu(end)?);
+                    if start > end {
+                        return Err(invalid());
+                    }
+                    set.insert_range(start, end);
+                }
+                None => {
+                    set.insert(cpu(entry)?);
+                }
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl Serialize for CpuSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CpuSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cpulist = String::deserialize(deserializer)?;
+        cpulist.parse().map_err(de::Error::custom)
+    }
+}
+
+/// An iterator over the OS indices in a [`CpuSet`], in ascending order.
+pub struct CpuSetIter<'set> {
+    words: &'set [u64],
+    word: usize,
+    curr: u64,
+}
+
+impl<'set> Iterator for CpuSetIter<'set> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.curr == 0 {
+            self.word += 1;
+            self.curr = *self.words.get(self.word)?;
+        }
+        let bit = self.curr.trailing_zeros();
+        self.curr &= self.curr - 1;
+        Some(self.word as u32 * WORD_BITS + bit)
+    }
+}
+
+impl<'set> FusedIterator for CpuSetIter<'set> {}
@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+
+use immutree::{NodeId, Tree};
+use serde::{Deserialize, Serialize};
+
+use crate::{Element, Error, Topology};
+
+/// A compact representation of the changes between two [`Topology`]s (e.g., after CPU hotplug),
+/// as produced by [`Topology::delta`] and applied by [`Topology::apply_delta`].
+///
+/// It consists of the elements of the new [`Topology`] that differ from the ones stored under the
+/// same [`NodeId`]s (or that are attached under different parents) in the baseline, along with
+/// the fingerprints of both (see [`Topology::fingerprint`]), so that it is only applied to the
+/// baseline it was produced against.
+///
+/// [`NodeId`]: immutree::NodeId
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopologyDelta {
+    /// The fingerprint of the baseline [`Topology`].
+    #[serde(rename = "base")]
+    baseline: String,
+
+    /// The fingerprint of the new [`Topology`].
+    #[serde(rename = "fp")]
+    fingerprint: String,
+
+    /// The number of elements of the new [`Topology`].
+    len: usize,
+
+    /// The elements of the new [`Topology`] that differ from the baseline, in ascending order of
+    /// [`NodeId`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    nodes: Vec<DeltaNode>,
+}
+
+/// An element of a [`TopologyDelta`], along with its [`NodeId`] and the [`NodeId`] of its parent.
+///
+/// [`NodeId`]: immutree::NodeId
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DeltaNode {
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<NodeId>,
+    data: Element,
+}
+
+impl TopologyDelta {
+    /// Returns the fingerprint of the baseline [`Topology`] that the delta can be applied to.
+    pub fn baseline_fingerprint(&self) -> &str {
+        &self.baseline
+    }
+
+    /// Returns `true` if the delta consists of no changes at all.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.baseline == self.fingerprint
+    }
+}
+
+impl Topology {
+    /// Returns the [`TopologyDelta`] that turns the given `baseline` into this topology, when
+    /// applied to it through [`Topology::apply_delta`].
+    ///
+    /// # Note
+    ///
+    /// As in [`Tree::from_parent_array`], the order of siblings is not part of the delta; i.e., the
+    /// children of each element are restored in ascending [`NodeId`] order (which is always the
+    /// case for detected topologies), hence the resulting topology may only be [semantically
+    /// equal] to this one.
+    ///
+    /// [`Tree::from_parent_array`]: immutree::Tree::from_parent_array
+    /// [`NodeId`]: immutree::NodeId
+    /// [semantically equal]: Topology::semantically_equal
+    pub fn delta(&self, baseline: &Self) -> TopologyDelta {
+        let nodes = self
+            .tree
+            .iter()
+            .filter(|(id, elem)| {
+                let parent = self.tree.parent_id(id);
+                baseline.tree.get_by_id(id) != Some(*elem) || baseline.tree.parent_id(id) != parent
+            })
+            .map(|(id, elem)| DeltaNode {
+                id,
+                parent: self.tree.parent_id(&id),
+                data: elem.clone(),
+            })
+            .collect();
+        TopologyDelta {
+            baseline: baseline.fingerprint(),
+            fingerprint: self.fingerprint(),
+            len: self.tree.len(),
+            nodes,
+        }
+    }
+
+    /// Applies the given [`TopologyDelta`] to this (baseline) topology, as produced by
+    /// [`Topology::delta`], returning the new topology.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Delta`] is returned if the delta was produced against a different baseline, if
+    /// it refers to elements out of the bounds of the new topology, or if the new topology does
+    /// not match the fingerprint recorded in the delta; an [`Error::ImmuTree`] or an
+    /// [`Error::InvalidRoot`] is returned if the resulting hierarchy is not a valid topology.
+    pub fn apply_delta(&self, delta: &TopologyDelta) -> Result<Self, Error> {
+        let baseline = self.fingerprint();
+        if baseline != delta.baseline {
+            return Err(Error::Delta(format!(
+                "baseline fingerprint mismatch: expected {}, found {baseline}",
+                delta.baseline
+            )));
+        }
+
+        let mut items = self.tree.to_parent_array();
+        items.truncate(delta.len);
+        let mut missing = delta.len - items.len();
+        for node in &delta.nodes {
+            let index = node.id as usize;
+            let item = (node.parent, node.data.clone());
+            match index.cmp(&items.len()) {
+                Ordering::Less => items[index] = item,
+                Ordering::Equal if missing > 0 => {
+                    items.push(item);
+                    missing -= 1;
+                }
+                _ => {
+                    return Err(Error::Delta(format!(
+                        "element {} is out of bounds or out of order",
+                        node.id
+                    )))
+                }
+            }
+        }
+        if missing > 0 {
+            return Err(Error::Delta(format!("{missing} elements are missing")));
+        }
+
+        let topo = Self::validated(Tree::from_parent_array(items)?)?;
+        let fingerprint = topo.fingerprint();
+        if fingerprint != delta.fingerprint {
+            return Err(Error::Delta(format!(
+                "fingerprint mismatch: expected {}, found {fingerprint}",
+                delta.fingerprint
+            )));
+        }
+        Ok(topo)
+    }
+}
@@ -0,0 +1,892 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use hwloc2::{topology::Filter, ObjectType};
+use immutree::{InsertMode, NodeId, Tree};
+
+use crate::{
+    sysfs::{cache_indices, IO_DEVICE_CLASSES},
+    types::pu_cpuset,
+    CacheKind, CacheLevel, CoreKind, CpuFrequency, CpuSet, Element, Error, IoDeviceKind,
+    MachineInfo, NumaAttributes, PageType, ProcessingElement, Topology,
+};
+
+/// Although hardware topology detection always happens the same way, the produced [`Topology`] may
+/// vary based on the selected [`DetectionMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionMode {
+    /// `Full` detection includes all hardware topology nodes that may be examined for the purposes
+    /// of the ActiK8s project.
+    Full,
+    /// `IsolationBoundariesOnly` detection excludes any intermediate nodes in the hardware
+    /// topology hierarchy; i.e., nodes that are the only child of their parent are excluded from
+    /// the [`Topology`].
+    ///
+    /// # Note
+    ///
+    /// The only exception to this node exclusion rule on Intel is [`NumaNode`] as a child of
+    /// [`Package`].
+    ///
+    /// [`Package`]: crate::ProcessingElement::Package
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    IsolationBoundariesOnly,
+    /// `CoresOnly` detection excludes all caches and [`Die`]s; i.e., the [`Topology`] consists of
+    /// the machine, its [`Package`]s, [`NumaNode`]s, [`Core`]s and [`Thread`]s only.
+    ///
+    /// [`Die`]: crate::ProcessingElement::Die
+    /// [`Package`]: crate::ProcessingElement::Package
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`Core`]: crate::ProcessingElement::Core
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    CoresOnly,
+    /// `NumaOnly` detection excludes everything but the [`NumaNode`]s and the [`Thread`]s that are
+    /// local to each of them; i.e., the [`Topology`] consists of the machine, its [`NumaNode`]s and
+    /// their [`Thread`]s only.
+    ///
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    NumaOnly,
+}
+
+/// Options that further configure the hardware topology detection, on top of its
+/// [`DetectionMode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectionOptions {
+    mode: DetectionMode,
+    restrict_to_allowed: bool,
+    io_devices: bool,
+    include_offline: bool,
+    numa_distances: bool,
+    excluded_cpus: CpuSet,
+}
+
+impl DetectionOptions {
+    /// Creates new [`DetectionOptions`] for the given [`DetectionMode`], with all other options
+    /// disabled.
+    pub fn new(mode: DetectionMode) -> Self {
+        Self {
+            mode,
+            restrict_to_allowed: false,
+            io_devices: false,
+            include_offline: false,
+            numa_distances: false,
+            excluded_cpus: CpuSet::new(),
+        }
+    }
+
+    /// Restricts the produced [`Topology`] to the hardware threads and NUMA nodes that the current
+    /// process is actually allowed to use (e.g., due to its cgroup's cpuset), as reported by the
+    /// Linux kernel in `/proc/self/status`.
+    ///
+    /// # Note
+    ///
+    /// Elements left without any allowed hardware thread are excluded, and the cpusets of the
+    /// rest are narrowed down to the allowed hardware threads. Elements that were excluded by
+    /// [`DetectionMode::IsolationBoundariesOnly`] are not reconsidered, even if they become
+    /// isolation boundaries after the restriction.
+    pub fn restrict_to_allowed(mut self) -> Self {
+        self.restrict_to_allowed = true;
+        self
+    }
+
+    /// Includes in the produced [`Topology`] the I/O devices (e.g., network interfaces, NVMe
+    /// drives, GPUs, etc) that the Linux kernel reports under `/sys/class`, each of them attached
+    /// under the NUMA node it is local to (or under the root, if there is no such NUMA node).
+    ///
+    /// # Note
+    ///
+    /// Only devices of the classes that correspond to an [`IoDeviceKind`] are included, as long
+    /// as the locality of their underlying (e.g., PCI) device is reported; hence, virtual devices
+    /// (e.g., the loopback interface, or partitions of block devices) are left out.
+    ///
+    /// See [`Element::IoDevice`].
+    ///
+    /// [`IoDeviceKind`]: crate::IoDeviceKind
+    pub fn with_io_devices(mut self) -> Self {
+        self.io_devices = true;
+        self
+    }
+
+    /// Includes in the produced [`Topology`] the hardware threads that are present but offline
+    /// (e.g., offlined by operators for maintenance), as reported by the Linux kernel in
+    /// `/sys/devices/system/cpu/`, marked as such (see [`Element::is_offline`]).
+    ///
+    /// # Note
+    ///
+    /// Since `libhwloc2-rs` does not report offline hardware threads, their position in the
+    /// hierarchy is unknown; hence, they are attached directly under the root, and they are not
+    /// part of the cpuset of any other element. They are added after the restriction to the
+    /// allowed resources (if requested).
+    pub fn include_offline(mut self) -> Self {
+        self.include_offline = true;
+        self
+    }
+
+    /// Includes in the produced [`Topology`] the distances between its NUMA nodes, as reported by
+    /// the Linux kernel in `/sys/devices/system/node/` (see [`NumaAttributes::distances`]).
+    ///
+    /// [`NumaAttributes::distances`]: crate::NumaAttributes::distances
+    pub fn numa_distances(mut self) -> Self {
+        self.numa_distances = true;
+        self
+    }
+
+    /// Excludes from the produced [`Topology`] the hardware threads with the given OS indices
+    /// (e.g., the ones reserved for the kubelet and system daemons through `--reserved-cpus`), so
+    /// that it only consists of the ones that are available to workloads.
+    ///
+    /// # Note
+    ///
+    /// Elements left without any hardware thread are excluded, and the cpusets of the rest are
+    /// narrowed down to the remaining hardware threads, as in
+    /// [`DetectionOptions::restrict_to_allowed`]. The exclusion takes place after all other
+    /// options have been applied; hence, it applies to offline hardware threads as well.
+    pub fn exclude_cpus(mut self, cpus: CpuSet) -> Self {
+        self.excluded_cpus.union_with(&cpus);
+        self
+    }
+
+    /// Returns the [`DetectionMode`] of the detection.
+    pub fn mode(&self) -> DetectionMode {
+        self.mode
+    }
+}
+
+impl From<DetectionMode> for DetectionOptions {
+    fn from(mode: DetectionMode) -> Self {
+        Self::new(mode)
+    }
+}
+
+impl Topology {
+    /// Detect the underlying hardware topology employing `libhwloc2-rs`, process it, and return a
+    /// new immutable Acti-[`Topology`].
+    ///
+    /// # Errors
+    ///
+    /// An [`Error`] is returned when any operation in `libhwloc2-rs` or [`immutree`] fails.
+    ///
+    /// # Panics
+    ///
+    /// Only in cases of unexpected results (certainly bugs) from the underlying `libhwloc2-rs`.
+    pub fn detect(mode: DetectionMode) -> Result<Self, Error> {
+        Self::detect_with(DetectionOptions::new(mode))
+    }
+
+    /// Detect the underlying hardware topology employing `libhwloc2-rs`, process it according to
+    /// the given [`DetectionOptions`], and return a new immutable Acti-[`Topology`].
+    ///
+    /// # Errors
+    ///
+    /// An [`Error`] is returned when any operation in `libhwloc2-rs` or [`immutree`] fails, or
+    /// when the allowed resources or the offline hardware threads cannot be retrieved (if
+    /// requested).
+    ///
+    /// # Panics
+    ///
+    /// Only in cases of unexpected results (certainly bugs) from the underlying `libhwloc2-rs`.
+    pub fn detect_with(options: DetectionOptions) -> Result<Self, Error> {
+        let keep = |kept: bool| {
+            if kept {
+                Filter::KeepAll
+            } else {
+                Filter::KeepNone
+            }
+        };
+        let (caches, cores) = match options.mode {
+            DetectionMode::Full | DetectionMode::IsolationBoundariesOnly => (true, true),
+            DetectionMode::CoresOnly => (false, true),
+            DetectionMode::NumaOnly => (false, false),
+        };
+        // NOTE: Packages are kept by libhwloc2-rs even in `DetectionMode::NumaOnly`, so that the
+        // NUMA nodes can be attached to them; they are excluded from the Topology afterwards.
+        let builder = hwloc2::Topology::builder()?
+            .all_types_filter(Filter::KeepNone)?
+            .type_filter(ObjectType::Machine, Filter::KeepAll)?
+            //.type_filter(ObjectType::Group, Filter::KeepAll)?
+            .type_filter(ObjectType::Package, Filter::KeepAll)?
+            .type_filter(ObjectType::Die, keep(caches))?
+            .type_filter(ObjectType::NumaNode, Filter::KeepAll)?
+            .type_filter(ObjectType::L1Cache, keep(caches))?
+            .type_filter(ObjectType::L2Cache, keep(caches))?
+            .type_filter(ObjectType::L3Cache, keep(caches))?
+            .type_filter(ObjectType::L4Cache, keep(caches))?
+            .type_filter(ObjectType::L5Cache, keep(caches))?
+            .type_filter(ObjectType::Core, keep(cores))?
+            .type_filter(ObjectType::PU, Filter::KeepAll)?;
+        let topo = builder.build()?;
+
+        let root_obj = topo.root_object().ok_or(Error::EmptyTopology)?;
+        let (mut tree, root_id) = Tree::with_root(Element::try_from(&root_obj)?);
+        tree.reserve(Self::count_objects(&root_obj) - 1);
+
+        let add_descendants_fn = match options.mode {
+            DetectionMode::Full | DetectionMode::CoresOnly | DetectionMode::NumaOnly => {
+                Self::add_all_descendants
+            }
+            DetectionMode::IsolationBoundariesOnly => Self::add_isol_bound_descendants,
+        };
+        add_descendants_fn(&mut tree, &root_id, &root_obj)?;
+        if options.mode == DetectionMode::NumaOnly {
+            tree = Self::exclude_packages(&tree)?;
+        }
+
+        Self::add_core_kinds(
+            &mut tree,
+            &Self::core_kinds_by_pu(cpu_kinds_in(Path::new("/sys/devices"))),
+        );
+
+        Self::add_cache_kinds(&mut tree, |os_index, level| {
+            cache_kind_in(
+                Path::new(&format!("/sys/devices/system/cpu/cpu{os_index}/cache")),
+                level,
+            )
+        });
+
+        Self::add_frequencies(&mut tree, |os_index| {
+            let khz = |name: &str| -> Option<u32> {
+                fs::read_to_string(format!(
+                    "/sys/devices/system/cpu/cpu{os_index}/cpufreq/{name}"
+                ))
+                .ok()?
+                .trim()
+                .parse::<u32>()
+                .ok()
+            };
+            Some(CpuFrequency {
+                base: khz("base_frequency").map(|khz| khz / 1000),
+                max: khz("cpuinfo_max_freq").map(|khz| khz / 1000),
+            })
+        });
+
+        if options.io_devices {
+            let io_devices = io_devices_in(Path::new("/sys/class"));
+            tree.reserve(io_devices.len());
+            for io_device in io_devices {
+                // Attach it under the smallest NUMA node that it is local to, if any
+                let parent_id = tree
+                    .iter()
+                    .filter(|(_, e)| matches!(e.processing(), Some(ProcessingElement::NumaNode(_))))
+                    .filter(|(_, e)| {
+                        !io_device.cpuset().is_empty() && io_device.cpuset().is_subset(e.cpuset())
+                    })
+                    .min_by_key(|(_, e)| e.cpuset().len())
+                    .map_or(root_id, |(id, _)| id);
+                tree.insert(io_device, InsertMode::Under(&parent_id))?;
+            }
+        }
+
+        Self::add_numa_memory(&mut tree, |os_index| {
+            numa_memory_in(Path::new(&format!(
+                "/sys/devices/system/node/node{os_index}"
+            )))
+        });
+
+        Self::add_machine_info(&mut tree, |name| {
+            fs::read_to_string(format!("/proc/sys/kernel/{name}"))
+                .ok()
+                .map(|value| value.trim().to_owned())
+        });
+
+        if options.numa_distances {
+            let read = |path: String| {
+                fs::read_to_string(path).map_err(|err| Error::NumaDistances(err.to_string()))
+            };
+            let online = read("/sys/devices/system/node/online".to_owned())?.parse()?;
+            Self::add_numa_distances(&mut tree, &online, |os_index| {
+                read(format!("/sys/devices/system/node/node{os_index}/distance"))
+            })?;
+        }
+
+        if options.restrict_to_allowed {
+            let status = fs::read_to_string("/proc/self/status")
+                .map_err(|err| Error::AllowedResources(err.to_string()))?;
+            let (allowed_cpus, allowed_mems) = Self::parse_allowed_resources(&status)?;
+            tree = Self::restrict_tree(&tree, &allowed_cpus, &allowed_mems)?;
+        }
+
+        if options.include_offline {
+            let cpus = |name: &str| -> Result<CpuSet, Error> {
+                fs::read_to_string(format!("/sys/devices/system/cpu/{name}"))
+                    .map_err(|err| Error::OfflineCpus(err.to_string()))?
+                    .parse()
+            };
+            Self::add_offline_threads(&mut tree, &cpus("online")?, &cpus("present")?)?;
+        }
+
+        if !options.excluded_cpus.is_empty() {
+            tree = Self::exclude_cpus(&tree, &options.excluded_cpus)?;
+        }
+
+        Ok(Self::new(tree))
+    }
+
+    /// Returns a copy of the given `Tree<Element>`, out of which the hardware threads with the
+    /// given OS indices have been excluded (see [`DetectionOptions::exclude_cpus`]).
+    pub(crate) fn exclude_cpus(
+        tree: &Tree<Element>,
+        excluded_cpus: &CpuSet,
+    ) -> Result<Tree<Element>, Error> {
+        let (mut remaining_cpus, mut all_mems) = (CpuSet::new(), CpuSet::new());
+        for (_, elem) in tree.iter() {
+            match elem.processing() {
+                Some(ProcessingElement::Thread(os_index)) if !excluded_cpus.contains(*os_index) => {
+                    remaining_cpus.insert(*os_index);
+                }
+                Some(ProcessingElement::NumaNode(os_index)) => {
+                    all_mems.insert(*os_index);
+                }
+                _ => (),
+            }
+        }
+        Self::restrict_tree(tree, &remaining_cpus, &all_mems)
+    }
+
+    /// Sets the distances of the NUMA nodes of the given `Tree<Element>`, out of the given online
+    /// NUMA nodes and the rows of the distance matrix that `distances_of` returns for each of them
+    /// (i.e., the whitespace-separated distances to all online NUMA nodes, in ascending order).
+    pub(crate) fn add_numa_distances<F>(
+        tree: &mut Tree<Element>,
+        online: &CpuSet,
+        mut distances_of: F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(u32) -> Result<String, Error>,
+    {
+        for id in 0..tree.len() as NodeId {
+            if let Some(Element::Processing {
+                element: ProcessingElement::NumaNode(os_index),
+                memory: Some(memory),
+                ..
+            }) = tree.get_mut_by_id(&id)
+            {
+                let row = distances_of(*os_index)?;
+                let distances = row
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<Vec<u32>, _>>()
+                    .ok()
+                    .filter(|distances| distances.len() == online.len())
+                    .ok_or_else(|| {
+                        Error::NumaDistances(format!(
+                            "malformed distances of NUMA node {os_index}: '{}'",
+                            row.trim()
+                        ))
+                    })?;
+                let distances: BTreeMap<_, _> = online.iter().zip(distances).collect();
+                *memory = std::mem::take(memory).with_distances(distances);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the memory attributes of the NUMA nodes of the given `Tree<Element>`, out of the ones
+    /// that `memory_of` returns for the OS index of each of them.
+    ///
+    /// NUMA nodes whose memory is not reported at all are left without memory attributes.
+    pub(crate) fn add_numa_memory<F>(tree: &mut Tree<Element>, mut memory_of: F)
+    where
+        F: FnMut(u32) -> Option<NumaAttributes>,
+    {
+        for id in 0..tree.len() as NodeId {
+            if let Some(Element::Processing {
+                element: ProcessingElement::NumaNode(os_index),
+                memory,
+                ..
+            }) = tree.get_mut_by_id(&id)
+            {
+                *memory = memory_of(*os_index);
+            }
+        }
+    }
+
+    /// Sets the [`MachineInfo`] of the root of the given `Tree<Element>`, out of the kernel
+    /// parameters that `kernel_param` returns for their names under `/proc/sys/kernel/` (e.g.,
+    /// `hostname`), the architecture that the crate has been built for, and the total memory of
+    /// the NUMA nodes of the `Tree<Element>`.
+    pub(crate) fn add_machine_info<F>(tree: &mut Tree<Element>, mut kernel_param: F)
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let total_memory = tree
+            .iter()
+            .filter_map(|(_, elem)| elem.memory())
+            .map(NumaAttributes::local_memory)
+            .sum();
+        let info = MachineInfo {
+            hostname: kernel_param("hostname"),
+            architecture: Some(std::env::consts::ARCH.to_owned()),
+            total_memory,
+            os_name: kernel_param("ostype"),
+            os_release: kernel_param("osrelease"),
+            hwloc_version: None,
+        };
+        if let Some(root) = tree.root_mut() {
+            root.set_machine_info(Some(info));
+        }
+    }
+
+    /// Sets the [`CacheKind`]s of the caches of the given `Tree<Element>`, out of the ones that
+    /// `kind_of` returns for the OS index of the first hardware thread of each cache and its
+    /// level.
+    ///
+    /// Caches whose kind is not reported at all are left unified.
+    pub(crate) fn add_cache_kinds<F>(tree: &mut Tree<Element>, mut kind_of: F)
+    where
+        F: FnMut(u32, CacheLevel) -> Option<CacheKind>,
+    {
+        for id in 0..tree.len() as NodeId {
+            if let Some(Element::Cache {
+                level,
+                kind,
+                cpuset,
+                ..
+            }) = tree.get_mut_by_id(&id)
+            {
+                *kind = cpuset
+                    .iter()
+                    .next()
+                    .and_then(|os_index| kind_of(os_index, *level))
+                    .unwrap_or_default();
+            }
+        }
+    }
+
+    /// Sets the nominal frequencies of the physical cores and hardware threads of the given
+    /// `Tree<Element>`, out of the ones that `frequency_of` returns for the OS index of each
+    /// hardware thread; physical cores get the ones of their first hardware thread.
+    ///
+    /// Frequencies that are not reported at all (e.g., in VMs without `cpufreq`) are left unset.
+    pub(crate) fn add_frequencies<F>(tree: &mut Tree<Element>, mut frequency_of: F)
+    where
+        F: FnMut(u32) -> Option<CpuFrequency>,
+    {
+        for id in 0..tree.len() as NodeId {
+            let elem = tree.get_mut_by_id(&id).expect("NodeId out of bounds");
+            if let Some(ProcessingElement::Core(_) | ProcessingElement::Thread(_)) =
+                elem.processing()
+            {
+                let frequency = elem
+                    .cpuset()
+                    .iter()
+                    .next()
+                    .and_then(&mut frequency_of)
+                    .filter(|frequency| *frequency != CpuFrequency::default());
+                elem.set_frequency(frequency);
+            }
+        }
+    }
+
+    /// Marks the hardware threads of the given `Tree<Element>` that are not among the given online
+    /// ones as offline, and attaches the present but offline hardware threads that are missing
+    /// from it directly under its root, marked as offline as well.
+    pub(crate) fn add_offline_threads(
+        tree: &mut Tree<Element>,
+        online: &CpuSet,
+        present: &CpuSet,
+    ) -> Result<(), Error> {
+        let mut missing = present.clone();
+        for id in 0..tree.len() as NodeId {
+            let elem = tree.get_mut_by_id(&id).expect("NodeId out of bounds");
+            if let Some(ProcessingElement::Thread(os_index)) = elem.processing() {
+                let os_index = *os_index;
+                missing.remove(os_index);
+                elem.set_offline(!online.contains(os_index));
+            }
+        }
+
+        let root_id = tree.root_id().ok_or(Error::EmptyTopology)?;
+        for os_index in missing
+            .iter()
+            .filter(|&os_index| !online.contains(os_index))
+        {
+            let mut thread = Element::from(ProcessingElement::Thread(os_index));
+            thread.set_cpuset(std::iter::once(os_index).collect());
+            thread.set_offline(true);
+            tree.insert(thread, InsertMode::Under(&root_id))?;
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of the given `Tree<Element>`, excluding all [`Package`]s; their descendants
+    /// are attached to their closest ancestor.
+    ///
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub(crate) fn exclude_packages(tree: &Tree<Element>) -> Result<Tree<Element>, Error> {
+        Self::retain_tree(tree, |_, elem| {
+            !matches!(elem.processing(), Some(ProcessingElement::Package(_)))
+        })
+    }
+
+    /// Parses the OS indices of the hardware threads and NUMA nodes that a process is allowed to
+    /// use, out of the contents of its `/proc/<pid>/status`.
+    pub(crate) fn parse_allowed_resources(status: &str) -> Result<(CpuSet, CpuSet), Error> {
+        let field = |name: &str| -> Result<CpuSet, Error> {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+                .ok_or_else(|| Error::AllowedResources(format!("'{name}' not found")))?
+                .parse()
+        };
+        Ok((field("Cpus_allowed_list")?, field("Mems_allowed_list")?))
+    }
+
+    /// Returns a copy of the given `Tree<Element>`, restricted to the given allowed hardware
+    /// threads and NUMA nodes.
+    ///
+    /// Elements without any allowed hardware thread, as well as NUMA nodes that are not allowed,
+    /// are excluded; their allowed descendants (if any) are attached to their closest ancestor.
+    pub(crate) fn restrict_tree(
+        tree: &Tree<Element>,
+        allowed_cpus: &CpuSet,
+        allowed_mems: &CpuSet,
+    ) -> Result<Tree<Element>, Error> {
+        fn restrict_descendants(
+            src: &Tree<Element>,
+            src_id: &NodeId,
+            dst: &mut Tree<Element>,
+            dst_parent_id: &NodeId,
+            allowed_cpus: &CpuSet,
+            allowed_mems: &CpuSet,
+        ) -> Result<(), Error> {
+            for child_id in src.child_ids_slice(src_id)? {
+                let mut elem = src
+                    .get_by_id(child_id)
+                    .expect("NodeId out of bounds")
+                    .clone();
+                let mut cpuset = elem.cpuset().clone();
+                cpuset.intersect_with(allowed_cpus);
+                let allowed = !cpuset.is_empty()
+                    && !matches!(elem.processing(), Some(ProcessingElement::NumaNode(os_index))
+                        if !allowed_mems.contains(*os_index));
+                let dst_id = if allowed {
+                    elem.set_cpuset(cpuset);
+                    dst.insert(elem, InsertMode::Under(dst_parent_id))?
+                } else {
+                    *dst_parent_id
+                };
+                restrict_descendants(src, child_id, dst, &dst_id, allowed_cpus, allowed_mems)?;
+            }
+            Ok(())
+        }
+
+        let src_root_id = tree.root_id().ok_or(Error::EmptyTopology)?;
+        let mut root = tree.root().ok_or(Error::EmptyTopology)?.clone();
+        let mut cpuset = root.cpuset().clone();
+        cpuset.intersect_with(allowed_cpus);
+        root.set_cpuset(cpuset);
+
+        let (mut restricted, root_id) = Tree::with_root(root);
+        restrict_descendants(
+            tree,
+            &src_root_id,
+            &mut restricted,
+            &root_id,
+            allowed_cpus,
+            allowed_mems,
+        )?;
+        restricted.shrink_to_fit();
+        Ok(restricted)
+    }
+
+    /// Tags the physical cores and hardware threads of the given `Tree<Element>` with their
+    /// [`CoreKind`], out of the given one of each hardware thread (see
+    /// [`Topology::core_kinds_by_pu`]); physical cores get the one of their first hardware thread.
+    pub(crate) fn add_core_kinds(tree: &mut Tree<Element>, core_kinds: &HashMap<u32, CoreKind>) {
+        if core_kinds.is_empty() {
+            return;
+        }
+        for id in 0..tree.len() as NodeId {
+            let elem = tree.get_mut_by_id(&id).expect("NodeId out of bounds");
+            if let Some(ProcessingElement::Core(_) | ProcessingElement::Thread(_)) =
+                elem.processing()
+            {
+                let core_kind = elem
+                    .cpuset()
+                    .iter()
+                    .next()
+                    .and_then(|pu| core_kinds.get(&pu).copied());
+                elem.set_core_kind(core_kind);
+            }
+        }
+    }
+
+    /// Maps the OS index of each hardware thread to its [`CoreKind`], given the efficiency rank
+    /// and the hardware threads of each CPU kind (see [`cpu_kinds_in`]); the higher the rank, the
+    /// more powerful the CPU kind.
+    ///
+    /// The returned map is empty on non-hybrid CPUs (i.e., when a single CPU kind is reported), or
+    /// when the CPU kinds cannot be ranked (i.e., when their efficiency is unknown).
+    pub(crate) fn core_kinds_by_pu<I>(cpu_kinds: I) -> HashMap<u32, CoreKind>
+    where
+        I: IntoIterator<Item = (i32, Vec<u32>)>,
+    {
+        let cpu_kinds = cpu_kinds.into_iter().collect::<Vec<_>>();
+        if cpu_kinds.len() < 2 || cpu_kinds.iter().any(|(efficiency, _)| *efficiency < 0) {
+            return HashMap::new();
+        }
+        let max_efficiency = cpu_kinds.iter().map(|(efficiency, _)| *efficiency).max();
+        cpu_kinds
+            .into_iter()
+            .flat_map(|(efficiency, pus)| {
+                let core_kind = if Some(efficiency) == max_efficiency {
+                    CoreKind::Performance
+                } else {
+                    CoreKind::Efficiency
+                };
+                pus.into_iter().map(move |pu| (pu, core_kind))
+            })
+            .collect()
+    }
+
+    /// Recursively count the objects under the given `hwloc2::Object` (including itself and its
+    /// memory children), as an upper bound of the elements that will be inserted into the
+    /// `Tree<Element>`.
+    fn count_objects(obj: &hwloc2::Object) -> usize {
+        let mem_count = obj.memory_arity() as usize;
+        let children_count: usize = obj.children().iter().map(Self::count_objects).sum();
+        1 + mem_count + children_count
+    }
+
+    /// Insert the memory child (i.e., NUMA node) of the given `hwloc2::Object`, if any, under the
+    /// given [`NodeId`], returning its [`NodeId`], which the "normal" children of the
+    /// `hwloc2::Object` should be inserted under.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::MemoryArity`] is returned if there are more than one memory children (e.g., due
+    /// to CXL-attached memory, or SNC/NPS modes), which `libhwloc2-rs` provides no way to
+    /// enumerate.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    fn add_memory_child(
+        tree: &mut Tree<Element>,
+        parent_node_id: &NodeId,
+        parent_obj: &hwloc2::Object,
+    ) -> Result<Option<NodeId>, Error> {
+        let mem_child_obj = match parent_obj.memory_arity() {
+            0 => return Ok(None),
+            1 => parent_obj
+                .memory_first_child()
+                .expect("memory_first_child() is None, despite memory_arity() == 1"),
+            arity => return Err(Error::MemoryArity(arity)),
+        };
+        match mem_child_obj.object_type() {
+            ObjectType::NumaNode => {
+                let mut numa_node = Element::try_from(&mem_child_obj)?;
+                numa_node.set_cpuset(pu_cpuset(parent_obj));
+                Ok(Some(
+                    tree.insert(numa_node, InsertMode::Under(parent_node_id))?,
+                ))
+            }
+            _ => unreachable!("Memory child's type is '{}'", mem_child_obj.object_type()),
+        }
+    }
+
+    /// Recursively add all descendant objects into the given `Tree<Element>`.
+    fn add_all_descendants<'topo, 'tree>(
+        tree: &'tree mut Tree<Element>,
+        parent_node_id: &'tree NodeId,
+        parent_obj: &'topo hwloc2::Object,
+    ) -> Result<(), Error> {
+        // First, insert any memory child (i.e., only a single NUMA node in our case).
+        let parent_mem_node_id = Self::add_memory_child(tree, parent_node_id, parent_obj)?;
+
+        // Then, deal with "normal" descendants.
+        for child_idx in 0..parent_obj.arity() {
+            let child_obj = parent_obj.children()[child_idx as usize];
+
+            match Element::try_from(&child_obj) {
+                Ok(child_elem) => {
+                    let child_node_id = tree.insert(
+                        child_elem,
+                        InsertMode::Under(&parent_mem_node_id.unwrap_or(*parent_node_id)),
+                    )?;
+                    Self::add_all_descendants(tree, &child_node_id, &child_obj)?;
+                }
+                Err(Error::NoEquivalentElement) => {
+                    Self::add_all_descendants(
+                        tree,
+                        &parent_mem_node_id.unwrap_or(*parent_node_id),
+                        &child_obj,
+                    )?;
+                }
+                Err(err) => unreachable!("Element::try_from() returned {err:?}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recursively add into the given `Tree<Element>` only descendant objects at isolation
+    /// boundaries.
+    fn add_isol_bound_descendants<'topo, 'tree>(
+        tree: &'tree mut Tree<Element>,
+        parent_node_id: &'tree NodeId,
+        parent_obj: &'topo hwloc2::Object,
+    ) -> Result<(), Error> {
+        // First, insert any memory child (i.e., only a single NUMA node in our case).
+        let parent_mem_node_id = Self::add_memory_child(tree, parent_node_id, parent_obj)?;
+
+        // Then, deal with "normal" descendants.
+        for child_idx in 0..parent_obj.arity() {
+            let child_obj = parent_obj.children()[child_idx as usize];
+
+            match Element::try_from(&child_obj) {
+                Ok(child_elem) => {
+                    if parent_obj.arity() > 1 {
+                        let child_node_id = tree.insert(
+                            child_elem,
+                            InsertMode::Under(&parent_mem_node_id.unwrap_or(*parent_node_id)),
+                        )?;
+                        Self::add_isol_bound_descendants(tree, &child_node_id, &child_obj)?;
+                    } else {
+                        Self::add_isol_bound_descendants(
+                            tree,
+                            &parent_mem_node_id.unwrap_or(*parent_node_id),
+                            &child_obj,
+                        )?;
+                    }
+                }
+                Err(Error::NoEquivalentElement) => {
+                    Self::add_isol_bound_descendants(
+                        tree,
+                        &parent_mem_node_id.unwrap_or(*parent_node_id),
+                        &child_obj,
+                    )?;
+                }
+                Err(err) => unreachable!("Element::try_from() returned {err:?}"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the [`CacheKind`] of the cache of the given level that `libhwloc2-rs` reports, out of
+/// the `index*` directories under the given `cache` directory of a CPU (see [`cache_indices`]).
+///
+/// Since `libhwloc2-rs` only reports the unified or the data cache of each level, it is unified if
+/// there is a unified one, or else data if there is a data one (e.g., L1d, when L1 is split).
+pub(crate) fn cache_kind_in(cpu_cache_dir: &Path, level: CacheLevel) -> Option<CacheKind> {
+    let level = level as u8 + 1;
+    let types = cache_indices(cpu_cache_dir)
+        .into_iter()
+        .filter(|(_, l, _)| *l == level)
+        .map(|(_, _, type_)| type_)
+        .collect::<Vec<_>>();
+    let has = |type_: &str| types.iter().any(|t| t == type_);
+    if has("Unified") {
+        Some(CacheKind::Unified)
+    } else if has("Data") {
+        Some(CacheKind::Data)
+    } else {
+        None
+    }
+}
+
+/// Returns the CPU kinds of a hybrid CPU, as pairs of their efficiency rank and their hardware
+/// threads, out of the `cpus` of the PMUs that the Linux kernel registers for each of them under
+/// the given sysfs `devices` directory (i.e., `cpu_core` for Intel's P-cores and `cpu_atom` for its
+/// E-cores); no CPU kinds are returned for non-hybrid CPUs, which have a single `cpu` PMU.
+pub(crate) fn cpu_kinds_in(devices_dir: &Path) -> Vec<(i32, Vec<u32>)> {
+    [("cpu_atom", 0), ("cpu_core", 1)]
+        .into_iter()
+        .filter_map(|(pmu, efficiency)| {
+            let cpus = fs::read_to_string(devices_dir.join(pmu).join("cpus"))
+                .ok()?
+                .parse::<CpuSet>()
+                .ok()?;
+            Some((efficiency, cpus.iter().collect()))
+        })
+        .collect()
+}
+
+/// Returns the I/O devices under the given sysfs `class` directory (e.g., `/sys/class`), as
+/// [`Element::IoDevice`]s carrying the hardware threads that are local to them, in ascending order
+/// of their kinds and names (see [`DetectionOptions::with_io_devices`]).
+///
+/// The locality of a device is the `local_cpulist` of the closest one among the (e.g., PCI)
+/// devices that its `device` link resolves to and their ancestors.
+pub(crate) fn io_devices_in(class_dir: &Path) -> Vec<Element> {
+    let mut io_devices = Vec::new();
+    for (kind, class) in IO_DEVICE_CLASSES {
+        for entry in fs::read_dir(class_dir.join(class)).into_iter().flatten() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Skip the display connectors of GPUs (e.g., `card0-HDMI-A-1`)
+            if *kind == IoDeviceKind::Gpu && name.contains('-') {
+                continue;
+            }
+            let cpuset =
+                fs::canonicalize(entry.path().join("device"))
+                    .ok()
+                    .and_then(|device_dir| {
+                        device_dir.ancestors().find_map(|dir| {
+                            fs::read_to_string(dir.join("local_cpulist"))
+                                .ok()?
+                                .parse::<CpuSet>()
+                                .ok()
+                        })
+                    });
+            if let Some(cpuset) = cpuset {
+                io_devices.push((*kind, name, cpuset));
+            }
+        }
+    }
+    io_devices.sort_unstable_by(|(a_kind, a_name, _), (b_kind, b_name, _)| {
+        (a_kind, a_name).cmp(&(b_kind, b_name))
+    });
+    io_devices
+        .into_iter()
+        .map(|(kind, name, cpuset)| Element::IoDevice { kind, name, cpuset })
+        .collect()
+}
+
+/// Returns the memory attributes of the NUMA node described by the given sysfs directory (e.g.,
+/// `/sys/devices/system/node/node0`), out of the `MemTotal` of its `meminfo` and the sizes and
+/// numbers of the huge pages under its `hugepages` directory, or `None` if its memory is not
+/// reported.
+pub(crate) fn numa_memory_in(node_dir: &Path) -> Option<NumaAttributes> {
+    let meminfo = fs::read_to_string(node_dir.join("meminfo")).ok()?;
+    // e.g., "Node 0 MemTotal:       32657356 kB"
+    let local_memory = meminfo.lines().find_map(|line| {
+        let mut fields = line.split_once("MemTotal:")?.1.split_whitespace();
+        let value = fields.next()?.parse::<u64>().ok()?;
+        match fields.next() {
+            Some("kB") => Some(value << 10),
+            None => Some(value),
+            _ => None,
+        }
+    })?;
+
+    // e.g., "hugepages-2048kB/nr_hugepages"
+    let mut page_types = fs::read_dir(node_dir.join("hugepages"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name();
+            let size = name
+                .to_str()?
+                .strip_prefix("hugepages-")?
+                .strip_suffix("kB")?
+                .parse::<u64>()
+                .ok()?;
+            let count = fs::read_to_string(entry.path().join("nr_hugepages"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            Some(PageType::new(size << 10, count))
+        })
+        .collect::<Vec<_>>();
+    page_types.sort_by_key(PageType::size);
+    Some(NumaAttributes::new(local_memory, page_types))
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use immutree::NodeId;
+
+use crate::{CacheLevel, CpuSet, Element, ProcessingElement, Topology};
+
+/// An isolation domain of the [`Topology`]; i.e., a set of hardware threads that can be allocated
+/// as a partition, without sharing any resource (e.g., a cache, or a memory controller) with the
+/// hardware threads outside of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsolationDomain {
+    /// The [`NodeId`] of the outermost element of the [`Topology`] that the domain consists of.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub id: NodeId,
+
+    /// The OS indices of the hardware threads that are members of the domain.
+    pub cpuset: CpuSet,
+}
+
+/// A cache domain of the [`Topology`]; i.e., the physical cores and hardware threads that share
+/// a [`Cache`] (see [`Topology::cache_domains`]).
+///
+/// [`Cache`]: crate::Element::Cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheDomain {
+    /// The [`NodeId`] of the [`Cache`] that is shared among the members of the domain.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Cache`]: crate::Element::Cache
+    pub id: NodeId,
+
+    /// The OS indices of the physical cores that are members of the domain, in ascending order;
+    /// empty if [`Core`]s have been excluded from the [`Topology`].
+    ///
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub cores: Vec<u32>,
+
+    /// The OS indices of the hardware threads that are members of the domain.
+    pub cpuset: CpuSet,
+}
+
+impl Topology {
+    /// Returns the cache domains of the given [`CacheLevel`], in pre-order; i.e., for each
+    /// [`Cache`] of that level, the physical cores and hardware threads under it (e.g., grouping
+    /// the cores by their shared L3 cache, in order to partition them).
+    ///
+    /// # Note
+    ///
+    /// Data and instruction caches of the same level form distinct (albeit identical) domains.
+    /// Caches that have been excluded from the topology (e.g., by
+    /// [`DetectionMode::IsolationBoundariesOnly`], when they are not shared among [`Core`]s) do
+    /// not form any.
+    ///
+    /// [`Cache`]: crate::Element::Cache
+    /// [`DetectionMode::IsolationBoundariesOnly`]: crate::DetectionMode::IsolationBoundariesOnly
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub fn cache_domains(&self, level: CacheLevel) -> Vec<CacheDomain> {
+        self.caches()
+            .filter(|(_, l, _)| *l == level)
+            .map(|(id, _, _)| {
+                let os_index_of = |id: NodeId| {
+                    self.tree
+                        .get_by_id(&id)
+                        .and_then(Element::processing)
+                        .map(|pe| match pe {
+                            ProcessingElement::Package(os_index)
+                            | ProcessingElement::Die(os_index)
+                            | ProcessingElement::NumaNode(os_index)
+                            | ProcessingElement::Core(os_index)
+                            | ProcessingElement::Thread(os_index) => *os_index,
+                        })
+                };
+                let mut cores: Vec<_> = self
+                    .cores_sharing_cache(id)
+                    .filter_map(os_index_of)
+                    .collect();
+                cores.sort_unstable();
+                cores.dedup();
+                CacheDomain {
+                    id,
+                    cores,
+                    cpuset: self
+                        .threads_sharing_cache(id)
+                        .filter_map(os_index_of)
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the isolation domains of the topology, in pre-order; i.e., the elements that would
+    /// be retained by [`DetectionMode::IsolationBoundariesOnly`], regardless of the
+    /// [`DetectionMode`] that the topology was actually detected with.
+    ///
+    /// An element is considered an isolation boundary if its hardware threads differ from the
+    /// ones of its parent (i.e., if it shares its parent's resources with some sibling), or if it
+    /// is a [`NumaNode`]. Chains of elements with the same hardware threads (e.g., a [`Core`] and
+    /// its private L1 and L2 caches) are represented by the outermost of them, hence each domain
+    /// is maximal. I/O devices are not considered.
+    ///
+    /// [`DetectionMode::IsolationBoundariesOnly`]: crate::DetectionMode::IsolationBoundariesOnly
+    /// [`DetectionMode`]: crate::DetectionMode
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub fn isolation_domains(&self) -> Vec<IsolationDomain> {
+        self.traverse()
+            .filter(|(id, _, elem)| match elem {
+                Element::IoDevice { .. } => false,
+                Element::Processing {
+                    element: ProcessingElement::NumaNode(_),
+                    ..
+                } => true,
+                _ => {
+                    let parent = self
+                        .tree
+                        .parent_id(id)
+                        .and_then(|pid| self.tree.get_by_id(&pid));
+                    !matches!(parent, Some(parent) if parent.cpuset() == elem.cpuset())
+                }
+            })
+            .map(|(id, _, elem)| IsolationDomain {
+                id,
+                cpuset: elem.cpuset().clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the partial view of the topology, consisting only of its isolation domains (see
+    /// [`Topology::isolation_domains`]); i.e., what [`DetectionMode::IsolationBoundariesOnly`]
+    /// would have produced, derived from an already detected (e.g., full) topology.
+    ///
+    /// # Note
+    ///
+    /// I/O devices are not retained.
+    ///
+    /// [`DetectionMode::IsolationBoundariesOnly`]: crate::DetectionMode::IsolationBoundariesOnly
+    pub fn isolation_boundaries_only(&self) -> Self {
+        let domain_ids: HashSet<_> = self
+            .isolation_domains()
+            .into_iter()
+            .map(|domain| domain.id)
+            .collect();
+        self.retain(|id, _| domain_ids.contains(&id))
+    }
+}
@@ -19,11 +19,83 @@ pub enum Error {
     #[error("Topology appears empty, but it should not be")]
     EmptyTopology,
 
+    /// Returned when the root element of a [`Topology`] (e.g., one that is being deserialized) is
+    /// not the [`Machine`].
+    ///
+    /// [`Topology`]: crate::Topology
+    /// [`Machine`]: crate::Element::Machine
+    #[error("The root of the Topology must be the Machine; found {0}")]
+    InvalidRoot(String),
+
     /// Returned when an `hwloc2::Object`'s memory arity is found to be greater than 1, which is
     /// currently not supported by this crate.
     #[error("A topology object's memory arity equals {0}, which is > 1, thus unsupported")]
     MemoryArity(u32),
 
+    /// Returned when a string cannot be parsed as a cpulist (e.g., `0-5,12-17`).
+    #[error("Invalid cpulist: '{0}'")]
+    InvalidCpuList(String),
+
+    /// Returned when the hardware threads and NUMA nodes that the current process is allowed to
+    /// use (e.g., due to its cgroup's cpuset) cannot be retrieved.
+    #[error("Failed to retrieve the allowed resources: {0}")]
+    AllowedResources(String),
+
+    /// Returned when the online and present hardware threads cannot be retrieved (e.g., from
+    /// sysfs).
+    #[error("Failed to retrieve the offline CPUs: {0}")]
+    OfflineCpus(String),
+
+    /// Returned when the distances between the NUMA nodes cannot be retrieved (e.g., from sysfs).
+    #[error("Failed to retrieve the NUMA distances: {0}")]
+    NumaDistances(String),
+
+    /// Returned when a [`VersionedTopology`] is serialized with a schema version that is not
+    /// supported by this version of the crate.
+    ///
+    /// [`VersionedTopology`]: crate::VersionedTopology
+    #[error(
+        "Unsupported Topology schema version {0} (supported: {})",
+        crate::SCHEMA_VERSION
+    )]
+    UnsupportedSchemaVersion(u32),
+
+    /// Returned when a [`Topology`] cannot be encoded into some serialized representation.
+    ///
+    /// [`Topology`]: crate::Topology
+    #[error("Failed to encode the Topology: {0}")]
+    Encoding(String),
+
+    /// Returned when a [`Topology`] cannot be decoded out of some serialized representation.
+    ///
+    /// [`Topology`]: crate::Topology
+    #[error("Failed to decode the Topology: {0}")]
+    Decoding(String),
+
+    /// Returned when a [`TopologyDelta`] cannot be applied to a [`Topology`] (e.g., because it
+    /// was produced against a different baseline).
+    ///
+    /// [`TopologyDelta`]: crate::TopologyDelta
+    /// [`Topology`]: crate::Topology
+    #[error("Failed to apply the TopologyDelta: {0}")]
+    Delta(String),
+
+    /// Returned when fewer physical cores than the requested ones are available for allocation.
+    #[error("Requested {requested} physical cores, but only {available} are available")]
+    InsufficientCores {
+        /// The number of physical cores requested.
+        requested: usize,
+        /// The number of physical cores available.
+        available: usize,
+    },
+
+    /// Returned when the current thread cannot be bound to the hardware threads of an element of
+    /// a [`Topology`].
+    ///
+    /// [`Topology`]: crate::Topology
+    #[error("Failed to bind the current thread: {0}")]
+    Binding(String),
+
     /// Error emanating from the [`immutree`] crate.
     #[error("Tree Error: {source}")]
     ImmuTree {
@@ -32,6 +104,7 @@ pub enum Error {
     },
 
     /// Error emanating from `libhwloc2-rs`.
+    #[cfg(feature = "detect")]
     #[error("libhwloc2-rs Error: {source}")]
     Hwloc {
         #[from]
@@ -0,0 +1,66 @@
+use std::{borrow::Cow, cmp::Ordering};
+
+use immutree::{NodeId, Tree};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{Element, Topology};
+
+/// An element along with the canonical forms of its children, sorted.
+///
+/// The [`MachineInfo`] of the root is not part of the canonical form, since it does not describe
+/// the hardware topology itself.
+///
+/// [`MachineInfo`]: crate::MachineInfo
+#[derive(PartialEq, PartialOrd, Serialize)]
+pub(crate) struct Canonical<'topo> {
+    elem: Cow<'topo, Element>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Canonical<'topo>>,
+}
+
+impl<'topo> Canonical<'topo> {
+    fn of(tree: &'topo Tree<Element>, id: &NodeId) -> Self {
+        let mut children = tree
+            .immediate_descendant_ids(id)
+            .expect("NodeId out of bounds")
+            .map(|child_id| Self::of(tree, &child_id))
+            .collect::<Vec<_>>();
+        children.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let elem = tree.get_by_id(id).expect("NodeId out of bounds");
+        let elem = if elem.machine_info().is_some() {
+            let mut elem = elem.clone();
+            elem.set_machine_info(None);
+            Cow::Owned(elem)
+        } else {
+            Cow::Borrowed(elem)
+        };
+        Self { elem, children }
+    }
+}
+
+impl Topology {
+    /// Returns the canonical form of the topology; i.e., its hierarchy of [`Element`]s, regardless
+    /// of the [`NodeId`]s assigned to them, the order of siblings or the machine's metadata.
+    pub(crate) fn canonical(&self) -> Option<Canonical<'_>> {
+        self.tree
+            .root_id()
+            .map(|root_id| Canonical::of(&self.tree, &root_id))
+    }
+
+    /// Returns a stable fingerprint of the topology's content, as the hex-encoded SHA-256 digest
+    /// of its canonical form.
+    ///
+    /// Topologies that are [semantically equal] have the same fingerprint, regardless of how they
+    /// were constructed (e.g., detected or deserialized), hence it is suitable for stamping the
+    /// topology's version (e.g., in an `ActiNode`'s metadata) and for cheaply detecting whether it
+    /// has changed.
+    ///
+    /// [semantically equal]: Topology::semantically_equal
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        serde_json::to_writer(&mut hasher, &self.canonical())
+            .expect("failed to serialize the canonical form of the topology (BUG)");
+        format!("{:x}", hasher.finalize())
+    }
+}
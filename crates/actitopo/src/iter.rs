@@ -4,7 +4,8 @@ use immutree::NodeId;
 
 use crate::{Element, Topology};
 
-/// An iterator over [`NodeId`]s that correspond to [`Element`]s in the [`Topology`].
+/// An iterator over [`NodeId`]s that correspond to [`Element`]s in the [`Topology`], in ascending
+/// [`NodeId`] order (or in descending order, when iterated from the back).
 ///
 /// [`NodeId`]: immutree::NodeId
 /// [`Element`]: crate::types::Element
@@ -15,7 +16,10 @@ where
 {
     topo: &'topo Topology,
     match_fn: F,
+    /// The next [`NodeId`] to examine from the front.
     curr: NodeId,
+    /// One past the next [`NodeId`] to examine from the back.
+    back: NodeId,
 }
 
 impl<'topo, F> NodeIds<'topo, F>
@@ -27,6 +31,7 @@ where
             topo: topology,
             match_fn,
             curr: 0,
+            back: topology.tree.len() as NodeId,
         }
     }
 }
@@ -38,10 +43,26 @@ where
     type Item = NodeId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(e) = self.topo.tree.get_by_id(&self.curr) {
+        while self.curr < self.back {
+            let id = self.curr;
             self.curr += 1;
-            if (self.match_fn)(e) {
-                return Some(self.curr - 1);
+            if matches!(self.topo.tree.get_by_id(&id), Some(e) if (self.match_fn)(e)) {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+impl<'topo, F> DoubleEndedIterator for NodeIds<'topo, F>
+where
+    F: Fn(&Element) -> bool,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.curr < self.back {
+            self.back -= 1;
+            if matches!(self.topo.tree.get_by_id(&self.back), Some(e) if (self.match_fn)(e)) {
+                return Some(self.back);
             }
         }
         None
@@ -49,3 +70,101 @@ where
 }
 
 impl<'topo, F: Fn(&Element) -> bool> FusedIterator for NodeIds<'topo, F> {}
+
+/// An iterator over [`NodeId`]s that correspond to [`Element`]s in a subtree of the [`Topology`].
+///
+/// [`NodeId`]: immutree::NodeId
+/// [`Element`]: crate::types::Element
+/// [`Topology`]: crate::Topology
+pub struct NodeIdsUnder<'topo, F>
+where
+    F: Fn(&Element) -> bool,
+{
+    traverse: Traverse<'topo>,
+    match_fn: F,
+}
+
+impl<'topo, F> NodeIdsUnder<'topo, F>
+where
+    F: Fn(&Element) -> bool,
+{
+    pub(crate) fn new(traverse: Traverse<'topo>, match_fn: F) -> Self {
+        Self { traverse, match_fn }
+    }
+}
+
+impl<'topo, F> Iterator for NodeIdsUnder<'topo, F>
+where
+    F: Fn(&Element) -> bool,
+{
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.traverse
+            .by_ref()
+            .find(|(_, _, e)| (self.match_fn)(e))
+            .map(|(id, _, _)| id)
+    }
+}
+
+impl<'topo, F: Fn(&Element) -> bool> FusedIterator for NodeIdsUnder<'topo, F> {}
+
+/// A depth-first, pre-order iterator over the [`Element`]s of the [`Topology`] (or of a subtree of
+/// it), along with their [`NodeId`]s and depths (i.e., `0` for the element it starts from).
+///
+/// [`NodeId`]: immutree::NodeId
+/// [`Element`]: crate::types::Element
+/// [`Topology`]: crate::Topology
+pub struct Traverse<'topo> {
+    topo: &'topo Topology,
+    stack: Vec<(NodeId, usize)>,
+}
+
+impl<'topo> Traverse<'topo> {
+    pub(crate) fn new(topology: &'topo Topology) -> Self {
+        Self {
+            topo: topology,
+            stack: topology
+                .tree
+                .root_id()
+                .map(|id| (id, 0))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// The caller must make sure that the given [`NodeId`] is stored in the [`Topology`].
+    pub(crate) fn under(topology: &'topo Topology, id: NodeId) -> Self {
+        Self {
+            topo: topology,
+            stack: vec![(id, 0)],
+        }
+    }
+}
+
+impl<'topo> Iterator for Traverse<'topo> {
+    type Item = (NodeId, usize, &'topo Element);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.stack.pop()?;
+        let elem = self
+            .topo
+            .tree
+            .get_by_id(&id)
+            .expect("Tree::child_ids_slice returned invalid NodeId");
+        let child_ids = self
+            .topo
+            .tree
+            .child_ids_slice(&id)
+            .expect("Tree::child_ids_slice returned invalid NodeId");
+        self.stack.extend(
+            child_ids
+                .iter()
+                .rev()
+                .map(|&child_id| (child_id, depth + 1)),
+        );
+        Some((id, depth, elem))
+    }
+}
+
+impl<'topo> FusedIterator for Traverse<'topo> {}
@@ -2,205 +2,210 @@
 //! deserialize and work with the hierarchical hardware topology of a physical machine for the
 //! purposes of the ActiK8s project.
 
+mod allocator;
+#[cfg(all(feature = "bind", target_os = "linux"))]
+mod bind;
+mod builder;
+mod codec;
+mod cpuset;
+mod delta;
+#[cfg(feature = "detect")]
+mod detect;
+mod domain;
 mod error;
+mod fingerprint;
 mod iter;
+mod path;
+#[cfg(feature = "proto")]
+pub mod proto;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(feature = "proptest")]
+mod strategy;
+mod sysfs;
 mod types;
-
+mod versioned;
+mod xml;
+
+pub use allocator::AllocationPolicy;
+pub use builder::{ElementBuilder, TopologyBuilder};
+pub use codec::MAX_DECOMPRESSED_LEN;
+pub use cpuset::{CpuSet, CpuSetIter};
+pub use delta::TopologyDelta;
+#[cfg(feature = "detect")]
+pub use detect::{DetectionMode, DetectionOptions};
+pub use domain::{CacheDomain, IsolationDomain};
 pub use error::Error;
-pub use iter::NodeIds;
+pub use iter::{NodeIds, NodeIdsUnder, Traverse};
+#[cfg(feature = "proptest")]
+pub use strategy::TopologyBounds;
 pub use types::CacheAttributes;
+pub use types::CacheKind;
 pub use types::CacheLevel;
+pub use types::CoreKind;
+pub use types::CpuFrequency;
 pub use types::Element;
+pub use types::IoDeviceKind;
+pub use types::MachineInfo;
+pub use types::MemoryTier;
+pub use types::NumaAttributes;
+pub use types::PageType;
 pub use types::ProcessingElement;
+pub use versioned::{VersionedTopology, SCHEMA_VERSION};
+
+use std::{cmp::Ordering, collections::HashMap};
 
-use hwloc2::{topology::Filter, ObjectType};
 use immutree::{InsertMode, NodeId, Tree};
-use serde::{Deserialize, Serialize};
-
-/// Although hardware topology detection always happens the same way, the produced [`Topology`] may
-/// vary based on the selected [`DetectionMode`].
-pub enum DetectionMode {
-    /// `Full` detection includes all hardware topology nodes that may be examined for the purposes
-    /// of the ActiK8s project.
-    Full,
-    /// `IsolationBoundariesOnly` detection excludes any intermediate nodes in the hardware
-    /// topology hierarchy; i.e., nodes that are the only child of their parent are excluded from
-    /// the [`Topology`].
-    ///
-    /// # Note
-    ///
-    /// The only exception to this node exclusion rule on Intel is [`NumaNode`] as a child of
-    /// [`Package`].
-    ///
-    /// [`Package`]: crate::ProcessingElement::Package
-    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
-    IsolationBoundariesOnly,
-}
+use serde::{de, Deserialize, Deserializer, Serialize};
 
 /// Acti Topology is a subset of the hardware topology detected through `libhwloc2-rs`, useful for
 /// the purposes of the ActiK8s project.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(transparent)]
 pub struct Topology {
     tree: Tree<Element>,
+
+    /// The [`NodeId`]s of all processing elements, indexed by their kind and OS index; it is
+    /// built along with the [`Topology`], and it is never (de)serialized.
+    #[serde(skip)]
+    index: HashMap<ProcessingElement, Vec<NodeId>>,
+}
+
+/// Deserialized [`Topology`]s are validated and indexed before being returned.
+///
+/// Since they may originate from untrusted sources (e.g., user-editable Kubernetes annotations),
+/// deserialization fails if children [`NodeId`]s do not correspond to elements of the
+/// [`Topology`], if any element is listed as the child of more than one element (or more than
+/// once), if any element is unreachable from the root, or if the root is not the [`Machine`].
+///
+/// Topologies that were serialized before [`CpuSet`]s were introduced (i.e., whose root carries
+/// none) are still accepted; the [`CpuSet`]s of their elements are computed out of the
+/// [`Thread`]s under them, whose OS indices must be lower than [`CpuSet::MAX_CPUS`].
+///
+/// [`Machine`]: Element::Machine
+/// [`Thread`]: ProcessingElement::Thread
+impl<'de> Deserialize<'de> for Topology {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut tree = Tree::<Element>::deserialize(deserializer)?;
+        if let Some(root_id) = tree.root_id() {
+            if matches!(tree.root(), Some(root) if root.cpuset().is_empty()) {
+                if let Some((_, thread)) = tree.iter().find(|(_, e)| {
+                    matches!(e.processing(), Some(ProcessingElement::Thread(os_index))
+                        if *os_index >= CpuSet::MAX_CPUS)
+                }) {
+                    return Err(de::Error::custom(format!("{thread} is out of bounds")));
+                }
+                Self::fill_cpusets(&mut tree, &root_id, &CpuSet::new());
+            }
+        }
+        Self::validated(tree).map_err(de::Error::custom)
+    }
 }
 
 impl Topology {
-    /// Detect the underlying hardware topology employing `libhwloc2-rs`, process it, and return a
-    /// new immutable Acti-[`Topology`].
+    /// Wraps the given `Tree<Element>` into a new [`Topology`], indexing its processing elements.
+    pub(crate) fn new(tree: Tree<Element>) -> Self {
+        let mut index: HashMap<_, Vec<_>> = HashMap::new();
+        for (id, elem) in tree.iter() {
+            if let Some(element) = elem.processing() {
+                index.entry(*element).or_default().push(id);
+            }
+        }
+        Self { tree, index }
+    }
+
+    /// Wraps the given `Tree<Element>` into a new [`Topology`], after making sure that its root
+    /// element is the [`Machine`].
     ///
     /// # Errors
     ///
-    /// An [`Error`] is returned when any operation in `libhwloc2-rs` or [`immutree`] fails.
+    /// An [`Error::EmptyTopology`] is returned if the given `Tree` is empty, or an
+    /// [`Error::InvalidRoot`] if its root element is not the [`Machine`].
     ///
-    /// # Panics
-    ///
-    /// Only in cases of unexpected results (certainly bugs) from the underlying `libhwloc2-rs`.
-    pub fn detect(mode: DetectionMode) -> Result<Self, Error> {
-        let topo = hwloc2::Topology::builder()?
-            .all_types_filter(Filter::KeepNone)?
-            .type_filter(ObjectType::Machine, Filter::KeepAll)?
-            //.type_filter(ObjectType::Group, Filter::KeepAll)?
-            .type_filter(ObjectType::Package, Filter::KeepAll)?
-            .type_filter(ObjectType::Die, Filter::KeepAll)?
-            .type_filter(ObjectType::NumaNode, Filter::KeepAll)?
-            .type_filter(ObjectType::L1Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L2Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L3Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L4Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L5Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::Core, Filter::KeepAll)?
-            .type_filter(ObjectType::PU, Filter::KeepAll)?
-            .build()?;
-
-        let mut tree = Tree::new();
-        let root_obj = topo.root_object().ok_or(Error::EmptyTopology)?;
-        let root_id = tree.insert(Element::try_from(&root_obj)?, InsertMode::AsRoot)?;
-
-        let add_descendants_fn = match mode {
-            DetectionMode::Full => Self::add_all_descendants,
-            DetectionMode::IsolationBoundariesOnly => Self::add_isol_bound_descendants,
-        };
-        add_descendants_fn(&mut tree, &root_id, &root_obj)?;
-
-        Ok(Self { tree })
-    }
-
-    /// Recursively add all descendant objects into the given `Tree<Element>`.
-    fn add_all_descendants<'topo, 'tree>(
-        tree: &'tree mut Tree<Element>,
-        parent_node_id: &'tree NodeId,
-        parent_obj: &'topo hwloc2::Object,
-    ) -> Result<(), Error> {
-        // First, insert any memory child (i.e., only a single NUMA node in our case).
-        let parent_mem_node_id = match parent_obj.memory_arity() {
-            0 => None,
-            1 => {
-                let mem_child_obj = parent_obj
-                    .memory_first_child()
-                    .expect("memory_first_child() is None, despite memory_arity() == 1");
-                match mem_child_obj.object_type() {
-                    ObjectType::NumaNode => Some(tree.insert(
-                        Element::try_from(&mem_child_obj)?,
-                        InsertMode::Under(parent_node_id),
-                    )?),
-                    _ => unreachable!("Memory child's type is '{}'", mem_child_obj.object_type()),
-                }
-            }
-            _ => {
-                // NOTE(ckatsak): I am not sure if memory_arity can ever be > 1, but we currently
-                // do not support it anyway, because I don't know how to handle it in the hierarchy
-                return Err(Error::MemoryArity(parent_obj.memory_arity()));
-            }
-        };
+    /// [`Machine`]: Element::Machine
+    pub(crate) fn validated(tree: Tree<Element>) -> Result<Self, Error> {
+        match tree.root_id().and_then(|root_id| tree.get_by_id(&root_id)) {
+            Some(Element::Machine { .. }) => Ok(Self::new(tree)),
+            Some(root) => Err(Error::InvalidRoot(root.to_string())),
+            None => Err(Error::EmptyTopology),
+        }
+    }
 
-        // Then, deal with "normal" descendants.
-        for child_idx in 0..parent_obj.arity() {
-            let child_obj = parent_obj.children()[child_idx as usize];
-
-            match Element::try_from(&child_obj) {
-                Ok(child_elem) => {
-                    let child_node_id = tree.insert(
-                        child_elem,
-                        InsertMode::Under(&parent_mem_node_id.unwrap_or(*parent_node_id)),
-                    )?;
-                    Self::add_all_descendants(tree, &child_node_id, &child_obj)?;
-                }
-                Err(Error::NoEquivalentElement) => {
-                    Self::add_all_descendants(
-                        tree,
-                        &parent_mem_node_id.unwrap_or(*parent_node_id),
-                        &child_obj,
-                    )?;
-                }
-                Err(err) => unreachable!("Element::try_from() returned {err:?}"),
+    /// Returns a copy of the given `Tree<Element>`, retaining only its root and the elements for
+    /// which the provided `predicate` returns `true`; the descendants of the excluded elements are
+    /// attached to their closest retained ancestor.
+    fn retain_tree<F>(tree: &Tree<Element>, mut predicate: F) -> Result<Tree<Element>, Error>
+    where
+        F: FnMut(NodeId, &Element) -> bool,
+    {
+        fn add_descendants<F: FnMut(NodeId, &Element) -> bool>(
+            src: &Tree<Element>,
+            src_id: &NodeId,
+            dst: &mut Tree<Element>,
+            dst_parent_id: &NodeId,
+            predicate: &mut F,
+        ) -> Result<(), Error> {
+            for child_id in src.child_ids_slice(src_id)? {
+                let elem = src.get_by_id(child_id).expect("NodeId out of bounds");
+                let dst_id = if predicate(*child_id, elem) {
+                    dst.insert(elem.clone(), InsertMode::Under(dst_parent_id))?
+                } else {
+                    *dst_parent_id
+                };
+                add_descendants(src, child_id, dst, &dst_id, predicate)?;
             }
+            Ok(())
         }
 
-        Ok(())
+        let src_root_id = tree.root_id().ok_or(Error::EmptyTopology)?;
+        let root = tree.root().ok_or(Error::EmptyTopology)?.clone();
+        let (mut dst, dst_root_id) = Tree::with_root(root);
+        add_descendants(tree, &src_root_id, &mut dst, &dst_root_id, &mut predicate)?;
+        dst.shrink_to_fit();
+        Ok(dst)
     }
 
-    /// Recursively add into the given `Tree<Element>` only descendant objects at isolation
-    /// boundaries.
-    fn add_isol_bound_descendants<'topo, 'tree>(
-        tree: &'tree mut Tree<Element>,
-        parent_node_id: &'tree NodeId,
-        parent_obj: &'topo hwloc2::Object,
-    ) -> Result<(), Error> {
-        // First, insert any memory child (i.e., only a single NUMA node in our case).
-        let parent_mem_node_id = match parent_obj.memory_arity() {
-            0 => None,
-            1 => {
-                let mem_child_obj = parent_obj
-                    .memory_first_child()
-                    .expect("memory_first_child() is None, despite memory_arity() == 1");
-                match mem_child_obj.object_type() {
-                    ObjectType::NumaNode => Some(tree.insert(
-                        Element::try_from(&mem_child_obj)?,
-                        InsertMode::Under(parent_node_id),
-                    )?),
-                    _ => unreachable!("Memory child's type is '{}'", mem_child_obj.object_type()),
-                }
-            }
+    /// Recursively fills the [`CpuSet`] of the element stored under the given [`NodeId`] and of
+    /// its descendants out of the [`Thread`]s under them, returning the former.
+    ///
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub(crate) fn fill_cpusets(
+        tree: &mut Tree<Element>,
+        id: &NodeId,
+        parent_cpuset: &CpuSet,
+    ) -> CpuSet {
+        let elem = tree.get_by_id(id).expect("NodeId out of bounds");
+        let cpuset = match elem {
+            // Threads are the leaves that all CpuSets consist of
+            Element::Processing {
+                element: ProcessingElement::Thread(os_index),
+                ..
+            } => std::iter::once(*os_index).collect(),
+            // I/O devices are local to the hardware threads of their parent
+            Element::IoDevice { .. } => parent_cpuset.clone(),
             _ => {
-                // NOTE(ckatsak): I am not sure if memory_arity can ever be > 1, but we currently
-                // do not support it anyway, because I don't know how to handle it in the hierarchy
-                return Err(Error::MemoryArity(parent_obj.memory_arity()));
-            }
-        };
-
-        // Then, deal with "normal" descendants.
-        for child_idx in 0..parent_obj.arity() {
-            let child_obj = parent_obj.children()[child_idx as usize];
-
-            match Element::try_from(&child_obj) {
-                Ok(child_elem) => {
-                    if parent_obj.arity() > 1 {
-                        let child_node_id = tree.insert(
-                            child_elem,
-                            InsertMode::Under(&parent_mem_node_id.unwrap_or(*parent_node_id)),
-                        )?;
-                        Self::add_isol_bound_descendants(tree, &child_node_id, &child_obj)?;
-                    } else {
-                        Self::add_isol_bound_descendants(
-                            tree,
-                            &parent_mem_node_id.unwrap_or(*parent_node_id),
-                            &child_obj,
-                        )?;
+                let child_ids: Vec<_> = tree
+                    .immediate_descendant_ids(id)
+                    .expect("NodeId out of bounds")
+                    .collect();
+                let mut cpuset = CpuSet::new();
+                for child_id in &child_ids {
+                    if !matches!(tree.get_by_id(child_id), Some(Element::IoDevice { .. })) {
+                        cpuset.union_with(&Self::fill_cpusets(tree, child_id, parent_cpuset));
                     }
                 }
-                Err(Error::NoEquivalentElement) => {
-                    Self::add_isol_bound_descendants(
-                        tree,
-                        &parent_mem_node_id.unwrap_or(*parent_node_id),
-                        &child_obj,
-                    )?;
+                for child_id in &child_ids {
+                    if matches!(tree.get_by_id(child_id), Some(Element::IoDevice { .. })) {
+                        Self::fill_cpusets(tree, child_id, &cpuset);
+                    }
                 }
-                Err(err) => unreachable!("Element::try_from() returned {err:?}"),
+                cpuset
             }
-        }
-
-        Ok(())
+        };
+        tree.get_mut_by_id(id)
+            .expect("NodeId out of bounds")
+            .set_cpuset(cpuset.clone());
+        cpuset
     }
 
     /// Returns an immutable reference to the inner `Tree<Element>` structure.
@@ -209,6 +214,165 @@ impl Topology {
         &self.tree
     }
 
+    /// Returns the metadata about the machine that the topology has been detected on (e.g., its
+    /// hostname), if any.
+    pub fn machine_info(&self) -> Option<&MachineInfo> {
+        self.tree.root().and_then(Element::machine_info)
+    }
+
+    /// Returns the [`NodeId`]s of the given [`ProcessingElement`] (i.e., of the given kind and OS
+    /// index) in constant time; it is empty if there is no such element in the topology.
+    ///
+    /// # Note
+    ///
+    /// The result may consist of multiple [`NodeId`]s only for [`ProcessingElement::Core`]s, since
+    /// the OS indices of physical cores are not necessarily unique across packages.
+    pub fn ids_of(&self, element: &ProcessingElement) -> &[NodeId] {
+        self.index.get(element).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the [`NodeId`] of the [`Package`] with the given OS index, in constant time.
+    ///
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub fn package_by_os_index(&self, os_index: u32) -> Option<NodeId> {
+        self.ids_of(&ProcessingElement::Package(os_index))
+            .first()
+            .copied()
+    }
+
+    /// Returns the [`NodeId`] of the [`Die`] with the given OS index, in constant time.
+    ///
+    /// [`Die`]: crate::ProcessingElement::Die
+    pub fn die_by_os_index(&self, os_index: u32) -> Option<NodeId> {
+        self.ids_of(&ProcessingElement::Die(os_index))
+            .first()
+            .copied()
+    }
+
+    /// Returns the [`NodeId`] of the [`NumaNode`] with the given OS index, in constant time.
+    ///
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    pub fn numa_node_by_os_index(&self, os_index: u32) -> Option<NodeId> {
+        self.ids_of(&ProcessingElement::NumaNode(os_index))
+            .first()
+            .copied()
+    }
+
+    /// Returns the [`NodeId`]s of the [`Core`]s with the given OS index, in constant time.
+    ///
+    /// Note that the OS indices of physical cores are not necessarily unique across packages.
+    ///
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub fn core_ids_by_os_index(&self, os_index: u32) -> &[NodeId] {
+        self.ids_of(&ProcessingElement::Core(os_index))
+    }
+
+    /// Returns the [`NodeId`] of the [`Thread`] with the given OS index, in constant time.
+    ///
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn thread_by_os_index(&self, os_index: u32) -> Option<NodeId> {
+        self.ids_of(&ProcessingElement::Thread(os_index))
+            .first()
+            .copied()
+    }
+
+    /// Returns `true` if both topologies consist of the same [`Element`]s in the same hierarchy,
+    /// regardless of the [`NodeId`]s assigned to them, the order of siblings or the
+    /// [`MachineInfo`] of the root.
+    ///
+    /// Contrary to the (derived) [`PartialEq`] implementation, this is suitable for checking
+    /// whether the hardware topology has changed, when comparing against a [`Topology`] that may
+    /// have been constructed (e.g., detected or deserialized) in a different way.
+    pub fn semantically_equal(&self, other: &Self) -> bool {
+        self.tree.len() == other.tree.len() && self.canonical() == other.canonical()
+    }
+
+    /// Returns a copy of the topology that retains only the root [`Machine`] and the elements for
+    /// which the provided `predicate` (given each element along with its [`NodeId`]) returns
+    /// `true`; the descendants of each excluded element are attached to its closest retained
+    /// ancestor, and the [`CpuSet`]s of all retained elements are left intact.
+    ///
+    /// For example, [`Topology::isolation_boundaries_only`] derives the partial view of a full
+    /// topology through it, without detecting the hardware topology again.
+    ///
+    /// [`Machine`]: crate::Element::Machine
+    /// [`NodeId`]: immutree::NodeId
+    pub fn retain<F: FnMut(NodeId, &Element) -> bool>(&self, predicate: F) -> Self {
+        match Self::retain_tree(&self.tree, predicate) {
+            Ok(tree) => Self::new(tree),
+            // Only an empty topology has no root
+            Err(_) => self.clone(),
+        }
+    }
+
+    /// Returns a copy of the topology that consists only of the root [`Machine`] and the subtrees
+    /// rooted at the elements stored under the given [`NodeId`]s (e.g., the NUMA nodes that are
+    /// allocated to ActiK8s); the roots of the subtrees are attached directly under the
+    /// [`Machine`], whose [`CpuSet`] is narrowed down to theirs.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::ImmuTree`] is returned if any of the given [`NodeId`]s does not correspond to
+    /// an element of the topology.
+    ///
+    /// [`Machine`]: crate::Element::Machine
+    /// [`NodeId`]: immutree::NodeId
+    pub fn subtrees(&self, ids: &[NodeId]) -> Result<Self, Error> {
+        let cpuset = self.cpuset_of(ids.iter().copied())?;
+        let mut tree = Self::retain_tree(&self.tree, |id, _| {
+            std::iter::once(id)
+                .chain(self.tree.ancestor_ids(&id))
+                .any(|id| ids.contains(&id))
+        })?;
+        if let Some(root) = tree.root_mut() {
+            root.set_cpuset(cpuset);
+        }
+        Ok(Self::new(tree))
+    }
+
+    /// Returns a normalized copy of the topology, in which the children of each element are
+    /// ordered deterministically; i.e., by their kind and then by their OS index (or, for caches,
+    /// by their level and logical index), and [`NodeId`]s are assigned to elements in pre-order.
+    ///
+    /// Since the order in which `libhwloc2-rs` reports sibling elements may differ across boots
+    /// (e.g., after a BIOS update), normalized topologies are suitable for comparing their
+    /// serialized representations byte by byte.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub fn normalize(&self) -> Self {
+        fn add_sorted_children(
+            src: &Tree<Element>,
+            src_id: &NodeId,
+            dst: &mut Tree<Element>,
+            dst_parent_id: &NodeId,
+        ) {
+            let mut child_ids = src
+                .child_ids_slice(src_id)
+                .expect("NodeId out of bounds")
+                .to_vec();
+            child_ids.sort_by(|a, b| {
+                let (a, b) = (src.get_by_id(a), src.get_by_id(b));
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            });
+            for child_id in &child_ids {
+                let elem = src.get_by_id(child_id).expect("NodeId out of bounds");
+                let dst_id = dst
+                    .insert(elem.clone(), InsertMode::Under(dst_parent_id))
+                    .expect("NodeId out of bounds");
+                add_sorted_children(src, child_id, dst, &dst_id);
+            }
+        }
+
+        let (src_root_id, root) = match (self.tree.root_id(), self.tree.root()) {
+            (Some(root_id), Some(root)) => (root_id, root.clone()),
+            _ => return self.clone(),
+        };
+        let (mut normalized, root_id) = Tree::with_root(root);
+        normalized.reserve(self.tree.len() - 1);
+        add_sorted_children(&self.tree, &src_root_id, &mut normalized, &root_id);
+        Self::new(normalized)
+    }
+
     /// Returns an iterator over the [`NodeId`]s that correspond to [`Element`]s in the topology
     /// for which the provided `match_fn` returns `true`.
     ///
@@ -217,13 +381,80 @@ impl Topology {
         NodeIds::new(self, match_fn)
     }
 
+    /// Returns the [`NodeId`]s that correspond to [`Element`]s in the topology for which the
+    /// provided `match_fn` returns `true`, in ascending order, evaluating it on all [`Element`]s
+    /// in parallel, backed by `rayon`.
+    ///
+    /// This is the parallel counterpart of [`Topology::filter_elements`], which is preferable
+    /// for small topologies, or for cheap predicates.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    #[cfg(feature = "rayon")]
+    pub fn par_filter_elements<F>(&self, match_fn: F) -> Vec<NodeId>
+    where
+        F: Fn(&Element) -> bool + Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        self.tree
+            .par_iter()
+            .filter(|(_, elem)| match_fn(elem))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Returns the [`NodeId`] of the first [`Element`] in the topology (i.e., the one with the
+    /// lowest [`NodeId`]) for which the provided `match_fn` returns `true`, without examining the
+    /// ones after it.
+    ///
+    /// The last one can be found through [`Topology::filter_elements`] instead, which can be
+    /// iterated from the back as well (e.g., `topo.thread_ids().next_back()`).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub fn find<F: Fn(&Element) -> bool>(&self, match_fn: F) -> Option<NodeId> {
+        self.filter_elements(match_fn).next()
+    }
+
+    /// Applies the provided `f` to the [`Element`]s in the topology (along with their
+    /// [`NodeId`]s), in ascending [`NodeId`] order, and returns its first non-`None` result,
+    /// without examining the [`Element`]s after it.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub fn find_map<B, F: FnMut(NodeId, &Element) -> Option<B>>(&self, mut f: F) -> Option<B> {
+        self.tree.iter().find_map(|(id, elem)| f(id, elem))
+    }
+
+    /// Returns an iterator over the [`NodeId`]s that correspond to [`Element`]s in the subtree
+    /// rooted at the provided `root_id` (including the root of the subtree itself) for which the
+    /// provided `match_fn` returns `true`, in pre-order.
+    ///
+    /// Contrary to filtering globally and then checking the ancestry of each match, only the
+    /// elements of the subtree are visited.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::ImmuTree`] if the provided [`NodeId`] does not correspond to an
+    /// [`Element`] in the topology.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub fn filter_elements_under<F: Fn(&Element) -> bool>(
+        &self,
+        root_id: &NodeId,
+        match_fn: F,
+    ) -> Result<NodeIdsUnder<F>, Error> {
+        if !self.tree.contains_id(root_id) {
+            return Err(immutree::Error::InvalidNodeId(*root_id).into());
+        }
+        Ok(NodeIdsUnder::new(Traverse::under(self, *root_id), match_fn))
+    }
+
     /// Returns an iterator over all [`NodeId`]s that correspond to a [`ProcessingElement`]s in the
     /// topology.
     ///
     /// [`NodeId`]: immutree::NodeId
     /// [`Package`]: crate::ProcessingElement::Package
     pub fn processing_element_ids(&self) -> NodeIds<'_, impl Fn(&Element) -> bool> {
-        self.filter_elements(|e| matches!(e, Element::Processing(_)))
+        self.filter_elements(|e| matches!(e, Element::Processing { .. }))
     }
 
     /// Returns an iterator over all [`NodeId`]s that correspond to [`Package`]s in the topology.
@@ -231,7 +462,15 @@ impl Topology {
     /// [`NodeId`]: immutree::NodeId
     /// [`Package`]: crate::ProcessingElement::Package
     pub fn package_ids(&self) -> NodeIds<'_, impl Fn(&Element) -> bool> {
-        self.filter_elements(|e| matches!(e, Element::Processing(ProcessingElement::Package(_))))
+        self.filter_elements(|e| matches!(e.processing(), Some(ProcessingElement::Package(_))))
+    }
+
+    /// Returns an iterator over all [`NodeId`]s that correspond to [`Die`]s in the topology.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Die`]: crate::ProcessingElement::Die
+    pub fn die_ids(&self) -> NodeIds<'_, impl Fn(&Element) -> bool> {
+        self.filter_elements(|e| matches!(e.processing(), Some(ProcessingElement::Die(_))))
     }
 
     /// Returns an iterator over all [`NodeId`]s that correspond to [`NumaNode`]s in the topology.
@@ -239,7 +478,34 @@ impl Topology {
     /// [`NodeId`]: immutree::NodeId
     /// [`NumaNode`]: crate::ProcessingElement::NumaNode
     pub fn numa_node_ids(&self) -> NodeIds<'_, impl Fn(&Element) -> bool> {
-        self.filter_elements(|e| matches!(e, Element::Processing(ProcessingElement::NumaNode(_))))
+        self.filter_elements(|e| matches!(e.processing(), Some(ProcessingElement::NumaNode(_))))
+    }
+
+    /// Returns a depth-first iterator over all elements of the topology, in pre-order (i.e., each
+    /// element is yielded before its children, which are yielded in order), along with their
+    /// [`NodeId`]s and their depths (i.e., `0` for the root [`Machine`]).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Machine`]: crate::Element::Machine
+    pub fn traverse(&self) -> Traverse<'_> {
+        Traverse::new(self)
+    }
+
+    /// Returns all elements of the topology, in ascending [`NodeId`] order, each along with its
+    /// [`NodeId`] and the [`NodeId`] of its parent (i.e., `None` for the root [`Machine`]); e.g.,
+    /// to bulk-load the topology into other data structures in a single pass.
+    ///
+    /// # Note
+    ///
+    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Machine`]: crate::Element::Machine
+    pub fn flatten(&self) -> Vec<(NodeId, Option<NodeId>, &Element)> {
+        self.tree
+            .iter()
+            .map(|(id, elem)| (id, self.tree.parent_id(&id), elem))
+            .collect()
     }
 
     /// Returns an iterator over all [`NodeId`]s that correspond to [`Core`]s in the topology.
@@ -247,7 +513,7 @@ impl Topology {
     /// [`NodeId`]: immutree::NodeId
     /// [`Core`]: crate::ProcessingElement::Core
     pub fn core_ids(&self) -> NodeIds<'_, impl Fn(&Element) -> bool> {
-        self.filter_elements(|e| matches!(e, Element::Processing(ProcessingElement::Core(_))))
+        self.filter_elements(|e| matches!(e.processing(), Some(ProcessingElement::Core(_))))
     }
 
     /// Returns an iterator over all [`NodeId`]s that correspond to [`Thread`]s in the topology.
@@ -255,7 +521,7 @@ impl Topology {
     /// [`NodeId`]: immutree::NodeId
     /// [`Thread`]: crate::ProcessingElement::Thread
     pub fn thread_ids(&self) -> NodeIds<'_, impl Fn(&Element) -> bool> {
-        self.filter_elements(|e| matches!(e, Element::Processing(ProcessingElement::Thread(_))))
+        self.filter_elements(|e| matches!(e.processing(), Some(ProcessingElement::Thread(_))))
     }
 
     /// Returns an iterator over all [`NodeId`]s that correspond to [`Cache`]s in the topology.
@@ -321,103 +587,513 @@ impl Topology {
         self.filter_elements(|e| matches!(e, Element::Cache { level: L5, .. }))
     }
 
-    //pub fn packages_original(&self) -> Vec<NodeId> {
-    //    (0..self.tree.len())
-    //        .filter_map(|id| {
-    //            self.tree.get_by_id(&(id as NodeId)).and_then(|&e| {
-    //                matches!(&e, Element::Processing(ProcessingElement::Package(_)))
-    //                    .then_some(id as NodeId)
-    //            })
-    //        })
-    //        .collect()
-    //}
-    ///// Returns the [`NodeId`]s that correspond to [`Element`]s in the topology for which the
-    ///// provided `match_fn` returns `true`.
-    /////
-    ///// The complexity of the function is `O(|V| * M)`, where `|V|` is the number of [`Element`]s
-    ///// in the topology's [`Tree`], and `M` is the complexity of the provided `match_fn`.
-    //pub fn filter_elems<F: Fn(&Element) -> bool>(&self, match_fn: F) -> Vec<NodeId> {
-    //    (0..self.tree.len())
-    //        .filter_map(|id| {
-    //            self.tree
-    //                .get_by_id(&(id as NodeId))
-    //                .and_then(|e| match_fn(e).then_some(id as NodeId))
-    //        })
-    //        .collect()
-    //}
-    //pub fn numa_nodes(&self) -> Vec<NodeId> {
-    //    self.filter_elems(|e| matches!(e, Element::Processing(ProcessingElement::NumaNode(_))))
-    //}
-    //pub fn l1_caches(&self) -> Vec<NodeId> {
-    //    use CacheLevel::L1;
-    //    self.filter_elems(|e| matches!(e, Element::Cache { level: L1, .. }))
-    //}
-}
+    /// Returns an iterator over all [`Package`]s in the topology, along with their [`NodeId`]s.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub fn packages(&self) -> impl DoubleEndedIterator<Item = (NodeId, &ProcessingElement)> + '_ {
+        self.processing_elements_of(self.package_ids())
+    }
 
-#[cfg(test)]
-mod tests {
-    use std::{
-        fs::{self, OpenOptions},
-        io::{BufWriter, Write},
-    };
+    /// Returns an iterator over all [`Die`]s in the topology, along with their [`NodeId`]s.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Die`]: crate::ProcessingElement::Die
+    pub fn dies(&self) -> impl DoubleEndedIterator<Item = (NodeId, &ProcessingElement)> + '_ {
+        self.processing_elements_of(self.die_ids())
+    }
 
-    use anyhow::Result;
-    use hwloc2::{topology::Filter, Object, ObjectType};
+    /// Returns an iterator over all [`NumaNode`]s in the topology, along with their [`NodeId`]s.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    pub fn numa_nodes(&self) -> impl DoubleEndedIterator<Item = (NodeId, &ProcessingElement)> + '_ {
+        self.processing_elements_of(self.numa_node_ids())
+    }
 
-    use crate::{DetectionMode, Topology};
+    /// Returns an iterator over all [`Core`]s in the topology, along with their [`NodeId`]s.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub fn cores(&self) -> impl DoubleEndedIterator<Item = (NodeId, &ProcessingElement)> + '_ {
+        self.processing_elements_of(self.core_ids())
+    }
 
-    //const TERMI5_TOPO_FILE: &str = "test-artifacts/topo__actitree.json";
+    /// Returns an iterator over all [`Thread`]s in the topology, along with their [`NodeId`]s.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn threads(&self) -> impl DoubleEndedIterator<Item = (NodeId, &ProcessingElement)> + '_ {
+        self.processing_elements_of(self.thread_ids())
+    }
 
-    fn print_children_attrs(obj: Object, depth: usize) {
-        let padding = " ".repeat(4 * depth);
-        eprintln!(
-            "\n\n{}{} ({}): #{}(L#{})\n{}└-attributes: {:?}",
-            padding,
-            obj,
-            obj.object_type(),
-            obj.os_index(),
-            obj.logical_index(),
-            padding,
-            obj.attributes(),
-        );
+    /// Returns an iterator over all [`Cache`]s in the topology, along with their [`NodeId`]s,
+    /// levels and attributes.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Cache`]: crate::Element::Cache
+    pub fn caches(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (NodeId, CacheLevel, &CacheAttributes)> + '_ {
+        self.cache_ids()
+            .map(move |id| match self.tree.get_by_id(&id) {
+                Some(Element::Cache {
+                    level, attributes, ..
+                }) => (id, *level, attributes),
+                _ => unreachable!("NodeIds yielded a NodeId that does not match"),
+            })
+    }
 
-        if obj.memory_arity() > 0 {
-            let mem_child = obj
-                .memory_first_child()
-                .expect("failed to retrieve first memory child");
-            eprintln!(
-                "{}└-{} ({}): #{}(L#{}) ({} children)\n{}  └-attributes: {:?}",
-                padding,
-                mem_child,
-                mem_child.object_type(),
-                mem_child.os_index(),
-                mem_child.logical_index(),
-                mem_child.arity(),
-                padding,
-                mem_child.attributes(),
-            );
-        }
-        for i in 0..obj.arity() {
-            print_children_attrs(obj.children()[i as usize], depth + 1)
-        }
+    /// Pairs each of the given [`NodeId`]s, which must correspond to [`ProcessingElement`]s, with
+    /// the [`ProcessingElement`] stored under it.
+    fn processing_elements_of<'topo>(
+        &'topo self,
+        ids: impl DoubleEndedIterator<Item = NodeId> + 'topo,
+    ) -> impl DoubleEndedIterator<Item = (NodeId, &'topo ProcessingElement)> + 'topo {
+        ids.map(move |id| {
+            let elem = self.tree.get_by_id(&id).and_then(Element::processing);
+            (
+                id,
+                elem.expect("NodeIds yielded a NodeId that does not match"),
+            )
+        })
     }
 
-    fn get_topo() -> Result<hwloc2::Topology> {
-        Ok(hwloc2::Topology::builder()?
-            .all_types_filter(Filter::KeepNone)?
-            .type_filter(ObjectType::Machine, Filter::KeepAll)?
-            .type_filter(ObjectType::Package, Filter::KeepAll)?
-            .type_filter(ObjectType::NumaNode, Filter::KeepAll)?
-            .type_filter(ObjectType::L1Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L2Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L3Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L4Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::L5Cache, Filter::KeepAll)?
-            .type_filter(ObjectType::Core, Filter::KeepAll)?
+    /// Returns an iterator over the [`NodeId`]s of all [`Core`]s that share the [`Cache`] stored
+    /// under the given [`NodeId`] (i.e., all [`Core`]s under it).
+    ///
+    /// The iterator is empty if the given [`NodeId`] does not correspond to a [`Cache`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Core`]: crate::ProcessingElement::Core
+    /// [`Cache`]: crate::Element::Cache
+    pub fn cores_sharing_cache(&self, cache_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.ids_under_cache(cache_id, |e| {
+            matches!(e.processing(), Some(ProcessingElement::Core(_)))
+        })
+    }
+
+    /// Returns an iterator over the [`NodeId`]s of all [`Thread`]s that share the [`Cache`] stored
+    /// under the given [`NodeId`] (i.e., all [`Thread`]s under it).
+    ///
+    /// The iterator is empty if the given [`NodeId`] does not correspond to a [`Cache`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    /// [`Cache`]: crate::Element::Cache
+    pub fn threads_sharing_cache(&self, cache_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.ids_under_cache(cache_id, |e| {
+            matches!(e.processing(), Some(ProcessingElement::Thread(_)))
+        })
+    }
+
+    /// Returns an iterator over the [`NodeId`]s of the elements under the [`Cache`] stored under
+    /// the given [`NodeId`] for which the provided `match_fn` returns `true`, visiting only the
+    /// subtree of the [`Cache`].
+    ///
+    /// The iterator is empty if the given [`NodeId`] does not correspond to a [`Cache`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Cache`]: crate::Element::Cache
+    fn ids_under_cache<'topo, F>(
+        &'topo self,
+        cache_id: NodeId,
+        match_fn: F,
+    ) -> impl Iterator<Item = NodeId> + 'topo
+    where
+        F: Fn(&Element) -> bool + 'topo,
+    {
+        matches!(self.tree.get_by_id(&cache_id), Some(Element::Cache { .. }))
+            .then(|| self.filter_elements_under(&cache_id, match_fn).ok())
+            .flatten()
+            .into_iter()
+            .flatten()
+    }
+
+    /// Returns an iterator over the [`NodeId`]s of the other [`Thread`]s on the same physical core
+    /// as the [`Thread`] stored under the given [`NodeId`] (i.e., its SMT siblings).
+    ///
+    /// The iterator is empty if the given [`NodeId`] does not correspond to a [`Thread`], or if
+    /// its physical core cannot be identified (see [`Topology::threads_per_core`]).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn smt_siblings_of(&self, thread_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let core_id = match self
+            .tree
+            .get_by_id(&thread_id)
+            .and_then(Element::processing)
+        {
+            Some(ProcessingElement::Thread(_)) => self.physical_core_of(&thread_id),
+            _ => None,
+        };
+        core_id
+            .and_then(|core_id| {
+                self.filter_elements_under(&core_id, |e| {
+                    matches!(e.processing(), Some(ProcessingElement::Thread(_)))
+                })
+                .ok()
+            })
+            .into_iter()
+            .flatten()
+            .filter(move |id| *id != thread_id)
+    }
+
+    /// Returns the maximum number of [`Thread`]s per physical core in the topology (e.g., it may
+    /// differ between the kinds of cores of hybrid CPUs).
+    ///
+    /// # Note
+    ///
+    /// The physical core of a [`Thread`] is identified as its closest [`Core`] ancestor or, when
+    /// [`Core`]s have been excluded (e.g., by [`DetectionMode::IsolationBoundariesOnly`]), as its
+    /// closest L1 or L2 [`Cache`] ancestor. The lowest one of these caches that is left in the
+    /// topology is private to the physical core (e.g., an L2 cache that is shared among a cluster
+    /// of cores has multiple L1 caches under it, which are therefore not excluded). [`Thread`]s
+    /// whose physical core cannot be identified are considered to be the only ones on it.
+    ///
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    /// [`Core`]: crate::ProcessingElement::Core
+    /// [`Cache`]: crate::Element::Cache
+    pub fn threads_per_core(&self) -> usize {
+        let mut threads_per_core: HashMap<NodeId, usize> = HashMap::new();
+        for core_id in self
+            .thread_ids()
+            .filter_map(|id| self.physical_core_of(&id))
+        {
+            *threads_per_core.entry(core_id).or_default() += 1;
+        }
+        threads_per_core.into_values().max().unwrap_or(1)
+    }
+
+    /// Returns `true` if simultaneous multithreading (SMT) is enabled; i.e., if there are physical
+    /// cores with more than one [`Thread`].
+    ///
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn smt_enabled(&self) -> bool {
+        self.threads_per_core() > 1
+    }
+
+    /// Returns the [`NodeId`] of the [`Package`] that encloses the element stored under the given
+    /// [`NodeId`] (or of the element itself, if it is a [`Package`]).
+    ///
+    /// # Note
+    ///
+    /// `None` is also returned if the topology has no [`Package`] elements at all; e.g., when the
+    /// single [`Package`] of the machine has been excluded by
+    /// [`DetectionMode::IsolationBoundariesOnly`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub fn package_of(&self, id: NodeId) -> Option<NodeId> {
+        self.enclosing(id, |e| {
+            matches!(e.processing(), Some(ProcessingElement::Package(_)))
+        })
+    }
+
+    /// Returns the [`NodeId`] of the [`Die`] that encloses the element stored under the given
+    /// [`NodeId`] (or of the element itself, if it is a [`Die`]).
+    ///
+    /// # Note
+    ///
+    /// `None` is also returned if the [`Die`] has been excluded from the topology; e.g., by
+    /// [`DetectionMode::IsolationBoundariesOnly`], when it is the only one in its [`Package`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Die`]: crate::ProcessingElement::Die
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub fn die_of(&self, id: NodeId) -> Option<NodeId> {
+        self.enclosing(id, |e| {
+            matches!(e.processing(), Some(ProcessingElement::Die(_)))
+        })
+    }
+
+    /// Returns the [`NodeId`] of the [`NumaNode`] that encloses the element stored under the given
+    /// [`NodeId`] (or of the element itself, if it is a [`NumaNode`]).
+    ///
+    /// # Note
+    ///
+    /// [`NumaNode`]s are never excluded by [`DetectionMode::IsolationBoundariesOnly`], hence `None`
+    /// is only returned for invalid [`NodeId`]s and for elements that are not local to any
+    /// [`NumaNode`] (e.g., the root of the topology).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    pub fn numa_node_of(&self, id: NodeId) -> Option<NodeId> {
+        self.enclosing(id, |e| {
+            matches!(e.processing(), Some(ProcessingElement::NumaNode(_)))
+        })
+    }
+
+    /// Returns the [`NodeId`]s of all other [`NumaNode`]s, ordered by their distance from the
+    /// [`NumaNode`] stored under the given [`NodeId`] (i.e., closest first); e.g., to pick the
+    /// closest remote NUMA node when the local one is full.
+    ///
+    /// [`NumaNode`]s at the same (or at an unknown) distance are ordered by their OS indices;
+    /// those at an unknown distance come last (see [`NumaAttributes::distances`]). It is empty if
+    /// the given [`NodeId`] does not correspond to a [`NumaNode`].
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    pub fn numa_nodes_by_distance(&self, from: NodeId) -> Vec<NodeId> {
+        let from_elem = match self.tree.get_by_id(&from) {
+            Some(elem) if matches!(elem.processing(), Some(ProcessingElement::NumaNode(_))) => elem,
+            _ => return Vec::new(),
+        };
+        let distance_to = |os_index: u32| {
+            from_elem
+                .memory()
+                .and_then(|memory| memory.distance_to(os_index))
+                .unwrap_or(u32::MAX)
+        };
+        let mut numa_nodes: Vec<_> = self
+            .numa_nodes()
+            .filter(|&(id, _)| id != from)
+            .filter_map(|(id, numa_node)| match numa_node {
+                ProcessingElement::NumaNode(os_index) => {
+                    Some((distance_to(*os_index), *os_index, id))
+                }
+                _ => None,
+            })
+            .collect();
+        numa_nodes.sort_unstable();
+        numa_nodes.into_iter().map(|(_, _, id)| id).collect()
+    }
+
+    /// Returns the OS indices of all (leaf) [`Thread`]s under the element stored under the given
+    /// [`NodeId`] (including itself, if it is a [`Thread`]), in ascending order; i.e., the CPUs
+    /// that a container must be pinned to, in order to run on that element.
+    ///
+    /// It is empty if the given [`NodeId`] does not correspond to an element of the topology.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn cpu_os_indices_under(&self, id: NodeId) -> Vec<u32> {
+        let mut os_indices: Vec<_> = self
+            .tree
+            .leaf_descendants(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| match e.processing() {
+                Some(ProcessingElement::Thread(os_index)) => Some(*os_index),
+                _ => None,
+            })
+            .collect();
+        os_indices.sort_unstable();
+        os_indices
+    }
+
+    /// Returns the [`CpuSet`] of the elements stored under the given [`NodeId`]s; i.e., the
+    /// hardware threads that a container must be pinned to, in order to run on all of them (e.g.,
+    /// as a cgroup's `cpuset.cpus`, through its [`Display`] implementation).
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::ImmuTree`] is returned if any of the given [`NodeId`]s does not correspond to
+    /// an element of the topology.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Display`]: std::fmt::Display
+    pub fn cpuset_of<I>(&self, ids: I) -> Result<CpuSet, Error>
+    where
+        I: IntoIterator<Item = NodeId>,
+    {
+        let mut cpuset = CpuSet::new();
+        for id in ids {
+            let elem = self
+                .tree
+                .get_by_id(&id)
+                .ok_or(immutree::Error::InvalidNodeId(id))?;
+            cpuset.union_with(elem.cpuset());
+        }
+        Ok(cpuset)
+    }
+
+    /// Returns the [`NodeId`]s of the [`Thread`]s with the OS indices in the given [`CpuSet`]
+    /// (e.g., as parsed out of a cgroup's `cpuset.cpus`), in ascending order of OS index.
+    ///
+    /// OS indices that do not correspond to a [`Thread`] of the topology are ignored.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn thread_ids_in(&self, cpus: &CpuSet) -> Vec<NodeId> {
+        cpus.iter()
+            .filter_map(|os_index| self.thread_by_os_index(os_index))
+            .collect()
+    }
+
+    /// Returns the [`NodeId`] of the [`Cache`] of the given [`CacheLevel`] that encloses the
+    /// element stored under the given [`NodeId`] (or of the element itself, if it is such a
+    /// [`Cache`]); e.g., the L3 cache that a [`Core`] or a [`Thread`] belongs to.
+    ///
+    /// # Note
+    ///
+    /// `None` is also returned if the [`Cache`] has been excluded from the topology; e.g., by
+    /// [`DetectionMode::IsolationBoundariesOnly`], when it is not shared among [`Core`]s.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Cache`]: crate::Element::Cache
+    /// [`Core`]: crate::ProcessingElement::Core
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    pub fn cache_for(&self, node: NodeId, level: CacheLevel) -> Option<NodeId> {
+        self.enclosing(
+            node,
+            |e| matches!(e, Element::Cache { level: l, .. } if *l == level),
+        )
+    }
+
+    /// Returns the [`NodeId`] of the closest element that matches the given predicate, among the
+    /// element stored under the given [`NodeId`] and its ancestors.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    fn enclosing<F>(&self, id: NodeId, match_fn: F) -> Option<NodeId>
+    where
+        F: Fn(&Element) -> bool,
+    {
+        if !self.tree.contains_id(&id) {
+            return None;
+        }
+        std::iter::once(id)
+            .chain(self.tree.ancestor_ids(&id))
+            .find(|id| match_fn(self.tree.get_by_id(id).expect("NodeId out of bounds")))
+    }
+
+    /// Returns the [`NodeId`] of the element that represents the physical core of the element
+    /// stored under the given [`NodeId`] (see [`Topology::threads_per_core`]).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    fn physical_core_of(&self, id: &NodeId) -> Option<NodeId> {
+        let mut cache_id = None;
+        for ancestor_id in self.tree.ancestor_ids(id) {
+            match self.tree.get_by_id(&ancestor_id) {
+                Some(Element::Processing {
+                    element: ProcessingElement::Core(_),
+                    ..
+                }) => return Some(ancestor_id),
+                Some(Element::Cache {
+                    level: CacheLevel::L1 | CacheLevel::L2,
+                    ..
+                }) if cache_id.is_none() => cache_id = Some(ancestor_id),
+                _ => {}
+            }
+        }
+        cache_id
+    }
+
+    //pub fn packages_original(&self) -> Vec<NodeId> {
+    //    (0..self.tree.len())
+    //        .filter_map(|id| {
+    //            self.tree.get_by_id(&(id as NodeId)).and_then(|&e| {
+    //                matches!(&e, Element::Processing(ProcessingElement::Package(_)))
+    //                    .then_some(id as NodeId)
+    //            })
+    //        })
+    //        .collect()
+    //}
+    ///// Returns the [`NodeId`]s that correspond to [`Element`]s in the topology for which the
+    ///// provided `match_fn` returns `true`.
+    /////
+    ///// The complexity of the function is `O(|V| * M)`, where `|V|` is the number of [`Element`]s
+    ///// in the topology's [`Tree`], and `M` is the complexity of the provided `match_fn`.
+    //pub fn filter_elems<F: Fn(&Element) -> bool>(&self, match_fn: F) -> Vec<NodeId> {
+    //    (0..self.tree.len())
+    //        .filter_map(|id| {
+    //            self.tree
+    //                .get_by_id(&(id as NodeId))
+    //                .and_then(|e| match_fn(e).then_some(id as NodeId))
+    //        })
+    //        .collect()
+    //}
+    //pub fn numa_nodes(&self) -> Vec<NodeId> {
+    //    self.filter_elems(|e| matches!(e, Element::Processing(ProcessingElement::NumaNode(_))))
+    //}
+    //pub fn l1_caches(&self) -> Vec<NodeId> {
+    //    use CacheLevel::L1;
+    //    self.filter_elems(|e| matches!(e, Element::Cache { level: L1, .. }))
+    //}
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "detect")]
+    use std::{
+        fs::{self, OpenOptions},
+        io::{BufWriter, Write},
+    };
+
+    use anyhow::Result;
+    #[cfg(feature = "detect")]
+    use hwloc2::{topology::Filter, Object, ObjectType};
+    use immutree::NodeId;
+
+    #[cfg(feature = "detect")]
+    use crate::DetectionMode;
+    use crate::Topology;
+
+    //const TERMI5_TOPO_FILE: &str = "test-artifacts/topo__actitree.json";
+
+    /// The serialized topology of a dual-socket machine, detected in
+    /// `DetectionMode::IsolationBoundariesOnly` (see `t4_de`).
+    const T4_JSON: &str = include_str!("../test-artifacts/t4_de_cpusets.json");
+
+    /// Deserializes the topology of [`T4_JSON`].
+    fn t4_topology() -> Result<Topology> {
+        Ok(Topology::from_json(T4_JSON)?)
+    }
+
+    #[cfg(feature = "detect")]
+    fn print_children_attrs(obj: Object, depth: usize) {
+        let padding = " ".repeat(4 * depth);
+        eprintln!(
+            "\n\n{}{} ({}): #{}(L#{})\n{}└-attributes: {:?}",
+            padding,
+            obj,
+            obj.object_type(),
+            obj.os_index(),
+            obj.logical_index(),
+            padding,
+            obj.attributes(),
+        );
+
+        if obj.memory_arity() > 0 {
+            let mem_child = obj
+                .memory_first_child()
+                .expect("failed to retrieve first memory child");
+            eprintln!(
+                "{}└-{} ({}): #{}(L#{}) ({} children)\n{}  └-attributes: {:?}",
+                padding,
+                mem_child,
+                mem_child.object_type(),
+                mem_child.os_index(),
+                mem_child.logical_index(),
+                mem_child.arity(),
+                padding,
+                mem_child.attributes(),
+            );
+        }
+        for i in 0..obj.arity() {
+            print_children_attrs(obj.children()[i as usize], depth + 1)
+        }
+    }
+
+    #[cfg(feature = "detect")]
+    fn get_topo() -> Result<hwloc2::Topology> {
+        Ok(hwloc2::Topology::builder()?
+            .all_types_filter(Filter::KeepNone)?
+            .type_filter(ObjectType::Machine, Filter::KeepAll)?
+            .type_filter(ObjectType::Package, Filter::KeepAll)?
+            .type_filter(ObjectType::Die, Filter::KeepAll)?
+            .type_filter(ObjectType::NumaNode, Filter::KeepAll)?
+            .type_filter(ObjectType::L1Cache, Filter::KeepAll)?
+            .type_filter(ObjectType::L2Cache, Filter::KeepAll)?
+            .type_filter(ObjectType::L3Cache, Filter::KeepAll)?
+            .type_filter(ObjectType::L4Cache, Filter::KeepAll)?
+            .type_filter(ObjectType::L5Cache, Filter::KeepAll)?
+            .type_filter(ObjectType::Core, Filter::KeepAll)?
             .type_filter(ObjectType::PU, Filter::KeepAll)?
             .build()?)
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn t1() -> Result<()> {
         let topo = get_topo()?;
@@ -431,6 +1107,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn t2() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -443,9 +1120,10 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn t4_de() -> Result<()> {
-        const T4_JSON_FILE: &str = "test-artifacts/t4_de.json";
+        const T4_JSON_FILE: &str = "test-artifacts/t4_de_cpusets.json";
         const T4_TXT_FILE: &str = "test-artifacts/t4_de.txt";
 
         let topo = Topology::detect(DetectionMode::IsolationBoundariesOnly)?;
@@ -476,7 +1154,7 @@ mod tests {
                     .open(T4_TXT_FILE)?,
             );
             let dt = detopo.tree();
-            for id in 0..dt.len() as u32 {
+            for id in 0..dt.len() as NodeId {
                 assert_eq!(dt.ancestor_ids(&id).next(), dt.parent_id(&id));
                 assert_eq!(dt.ancestors(&id).next(), dt.parent(&id));
 
@@ -526,6 +1204,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_package_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -542,6 +1221,24 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_filter_die_ids() -> Result<()> {
+        let topo = Topology::detect(DetectionMode::Full)?;
+
+        for id in topo.die_ids() {
+            eprintln!(
+                "- NodeID {id}: {}",
+                topo.tree()
+                    .get_by_id(&id)
+                    .expect("Topology::die_ids returned invalid id")
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_numa_nodes_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -558,6 +1255,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_core_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -653,6 +1351,7 @@ mod tests {
     //    Ok(())
     //}
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_thread_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -669,6 +1368,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_cache_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -685,6 +1385,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_l1_cache_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -701,6 +1402,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_l2_cache_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -717,6 +1419,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_l3_cache_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -733,6 +1436,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_l4_cache_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -749,6 +1453,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_filter_l5_cache_ids() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -765,6 +1470,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "detect")]
     #[test]
     fn test_both_topo_and_tree() -> Result<()> {
         let topo = Topology::detect(DetectionMode::Full)?;
@@ -799,4 +1505,2155 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_cpuset() -> Result<()> {
+        use crate::CpuSet;
+
+        let cpuset: CpuSet = " 0-5, 12-17,64,3 ".parse()?;
+        assert_eq!(cpuset.len(), 13);
+        assert!(cpuset.contains(64) && cpuset.contains(3) && !cpuset.contains(6));
+        assert_eq!(cpuset.to_string(), "0-5,12-17,64");
+        assert_eq!(serde_json::to_string(&cpuset)?, r#""0-5,12-17,64""#);
+        assert_eq!(serde_json::from_str::<CpuSet>(r#""0-5,12-17,64""#)?, cpuset);
+
+        let mut other: CpuSet = [64, 12].into_iter().collect();
+        assert!(other.is_subset(&cpuset));
+        other.insert(128);
+        assert!(!other.is_subset(&cpuset));
+        assert!(other.remove(128));
+        assert_eq!(other, "12,64".parse()?);
+
+        assert!("".parse::<CpuSet>()?.is_empty());
+        assert!("3-1".parse::<CpuSet>().is_err());
+        assert!("0,a".parse::<CpuSet>().is_err());
+
+        // Ranges within and across words, and OS indices out of bounds
+        assert_eq!("60-130".parse::<CpuSet>()?, (60..=130).collect());
+        assert_eq!("64-127".parse::<CpuSet>()?, (64..=127).collect());
+        assert_eq!("5-5".parse::<CpuSet>()?.to_string(), "5");
+        let max = CpuSet::MAX_CPUS;
+        assert_eq!(
+            format!("0-{}", max - 1).parse::<CpuSet>()?.len(),
+            max as usize
+        );
+        assert!(max.to_string().parse::<CpuSet>().is_err());
+        assert!("4294967295".parse::<CpuSet>().is_err());
+        assert!("0-4294967295".parse::<CpuSet>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_cpusets() -> Result<()> {
+        use crate::{CpuSet, ProcessingElement};
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let tree = topo.tree();
+        for id in topo
+            .package_ids()
+            .chain(topo.core_ids())
+            .chain(topo.cache_ids())
+        {
+            let threads: CpuSet = tree
+                .leaf_descendants(&id)?
+                .filter_map(|e| match e.processing() {
+                    Some(ProcessingElement::Thread(os_index)) => Some(*os_index),
+                    _ => None,
+                })
+                .collect();
+            let elem = tree.get_by_id(&id).expect("invalid NodeId");
+            assert_eq!(elem.cpuset(), &threads, "{elem}");
+        }
+        let root = tree.get_by_id(&0).expect("no root");
+        assert_eq!(root.cpuset().to_string(), "0-23");
+        Ok(())
+    }
+
+    #[test]
+    fn test_numa_node_memory() -> Result<()> {
+        use crate::{Element, ProcessingElement};
+
+        let json = r#"{"processing":{"kind":"numanode","id":1,"cpuset":"6-11,18-23","mem":{"size":34359738368,"pages":[{"size":4096,"count":8388608}]}}}"#;
+        let numa_node: Element = serde_json::from_str(json)?;
+        assert_eq!(
+            numa_node.processing(),
+            Some(&ProcessingElement::NumaNode(1))
+        );
+        let memory = numa_node.memory().expect("NUMA node memory not found");
+        assert_eq!(memory.local_memory(), 32 << 30);
+        assert_eq!(memory.page_types().len(), 1);
+        assert_eq!(memory.page_types()[0].size(), 4096);
+        assert_eq!(serde_json::to_string(&numa_node)?, json);
+
+        let numa_node: Element =
+            serde_json::from_str(r#"{"processing":{"kind":"numanode","id":0}}"#)?;
+        assert!(numa_node.memory().is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_core_kinds() -> Result<()> {
+        use std::fs;
+
+        use crate::{
+            detect::cpu_kinds_in, CoreKind, Element, ProcessingElement, Topology, TopologyBuilder,
+        };
+
+        let core_kinds = Topology::core_kinds_by_pu(vec![(0, vec![8, 9]), (1, vec![0, 1, 2])]);
+        assert_eq!(core_kinds.len(), 5);
+        assert_eq!(core_kinds[&0], CoreKind::Performance);
+        assert_eq!(core_kinds[&9], CoreKind::Efficiency);
+        // Non-hybrid CPUs, or CPU kinds of unknown efficiency
+        assert!(Topology::core_kinds_by_pu(vec![(0, vec![0, 1])]).is_empty());
+        assert!(Topology::core_kinds_by_pu(vec![(-1, vec![0]), (-1, vec![1])]).is_empty());
+
+        // A fake sysfs of a hybrid CPU, with a P-core of two hardware threads and two E-cores
+        let devices = std::env::temp_dir().join(format!("actitopo-devices-{}", std::process::id()));
+        for (pmu, cpus) in [("cpu_core", "0-1\n"), ("cpu_atom", "2-3\n")] {
+            fs::create_dir_all(devices.join(pmu))?;
+            fs::write(devices.join(pmu).join("cpus"), cpus)?;
+        }
+        let cpu_kinds = cpu_kinds_in(&devices);
+        fs::remove_dir_all(&devices)?;
+        assert_eq!(cpu_kinds, [(0, vec![2, 3]), (1, vec![0, 1])]);
+        assert!(cpu_kinds_in(&devices).is_empty());
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0).thread(1);
+                })
+                .core(8, |c| {
+                    c.thread(2);
+                })
+                .core(9, |c| {
+                    c.thread(3);
+                });
+            })
+            .build();
+        let mut tree = topo.tree().clone();
+        Topology::add_core_kinds(&mut tree, &Topology::core_kinds_by_pu(cpu_kinds));
+        let topo = Topology::new(tree);
+        let core_kind_of = |path: &str| {
+            topo.resolve_path(path)
+                .and_then(|id| topo.tree().get_by_id(&id))
+                .and_then(Element::core_kind)
+        };
+        assert_eq!(
+            core_kind_of("package:0/core:0"),
+            Some(CoreKind::Performance)
+        );
+        assert_eq!(
+            core_kind_of("package:0/core:0/thread:1"),
+            Some(CoreKind::Performance)
+        );
+        assert_eq!(core_kind_of("package:0/core:9"), Some(CoreKind::Efficiency));
+        assert_eq!(core_kind_of("package:0"), None);
+
+        let mut core = Element::from(ProcessingElement::Core(3));
+        assert_eq!(core.core_kind(), None);
+        assert!(!serde_json::to_string(&core)?.contains("corekind"));
+        core.set_core_kind(Some(CoreKind::Efficiency));
+        let json = serde_json::to_string(&core)?;
+        assert!(json.contains(r#""corekind":"efficiency""#));
+        assert_eq!(serde_json::from_str::<Element>(&json)?, core);
+        assert_eq!(CoreKind::Performance.to_string(), "P-core");
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_restrict_to_allowed() -> Result<()> {
+        use crate::{CpuSet, ProcessingElement};
+
+        let status = "Name:\tregistrant\nCpus_allowed:\t3f\nCpus_allowed_list:\t0-5\nMems_allowed:\t00000001\nMems_allowed_list:\t0\n";
+        let (allowed_cpus, allowed_mems) = Topology::parse_allowed_resources(status)?;
+        assert_eq!(allowed_cpus, "0-5".parse::<CpuSet>()?);
+        assert_eq!(allowed_mems, "0".parse::<CpuSet>()?);
+        assert!(Topology::parse_allowed_resources("Cpus_allowed_list:\t0-5\n").is_err());
+
+        let topo = t4_topology()?;
+        let restricted = Topology::new(Topology::restrict_tree(
+            topo.tree(),
+            &allowed_cpus,
+            &allowed_mems,
+        )?);
+        assert_eq!(restricted.package_ids().count(), 1);
+        assert_eq!(restricted.numa_node_ids().count(), 1);
+        assert_eq!(restricted.thread_ids().count(), 6);
+        for (_, elem) in restricted.tree().iter() {
+            assert!(!elem.cpuset().is_empty(), "{elem}");
+            assert!(elem.cpuset().is_subset(&allowed_cpus), "{elem}");
+            assert_ne!(elem.processing(), Some(&ProcessingElement::NumaNode(1)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_io_device() -> Result<()> {
+        use crate::{CpuSet, Element, IoDeviceKind};
+
+        let json = r#"{"iodevice":{"kind":"network","name":"eth0","cpuset":"0-5,12-17"}}"#;
+        let nic: Element = serde_json::from_str(json)?;
+        assert_eq!(
+            nic,
+            Element::IoDevice {
+                kind: IoDeviceKind::Network,
+                name: "eth0".to_owned(),
+                cpuset: "0-5,12-17".parse::<CpuSet>()?,
+            }
+        );
+        assert!(nic.processing().is_none());
+        assert_eq!(nic.to_string(), r#"Network I/O device "eth0""#);
+        assert_eq!(serde_json::to_string(&nic)?, json);
+        Ok(())
+    }
+
+    #[test]
+    fn test_semantically_equal() -> Result<()> {
+        use crate::{Element, ProcessingElement};
+        use immutree::{InsertMode, NodeId, Tree};
+
+        fn insert_reversed(
+            src: &Tree<Element>,
+            src_id: &NodeId,
+            dst: &mut Tree<Element>,
+            dst_id: &NodeId,
+        ) -> Result<()> {
+            for child_id in src.immediate_descendant_ids(src_id)?.rev() {
+                let elem = src.get_by_id(&child_id).expect("invalid NodeId").clone();
+                let new_id = dst.insert(elem, InsertMode::Under(dst_id))?;
+                insert_reversed(src, &child_id, dst, &new_id)?;
+            }
+            Ok(())
+        }
+
+        let topo = t4_topology()?;
+        let (mut tree, root_id) = Tree::with_root(topo.tree().root().expect("no root").clone());
+        insert_reversed(topo.tree(), &0, &mut tree, &root_id)?;
+        let reversed = Topology::new(tree.clone());
+        assert_ne!(reversed, topo);
+        assert!(reversed.semantically_equal(&topo));
+        assert!(topo.semantically_equal(&reversed));
+
+        let id = reversed.thread_ids().next().expect("no threads");
+        *tree.get_mut_by_id(&id).expect("invalid NodeId") =
+            Element::from(ProcessingElement::Thread(64));
+        assert!(!Topology::new(tree).semantically_equal(&topo));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_by_os_index() -> Result<()> {
+        use crate::ProcessingElement;
+
+        let topo = t4_topology()?;
+        for id in topo.thread_ids() {
+            let elem = topo.tree().get_by_id(&id).expect("invalid NodeId");
+            let os_index = match elem.processing() {
+                Some(ProcessingElement::Thread(os_index)) => *os_index,
+                _ => unreachable!("{elem} is not a Thread"),
+            };
+            assert_eq!(topo.thread_by_os_index(os_index), Some(id));
+        }
+        assert_eq!(topo.thread_by_os_index(24), None);
+        assert!(topo.numa_node_by_os_index(1).is_some());
+        assert!(topo.package_by_os_index(1).is_some());
+        assert!(topo.ids_of(&ProcessingElement::Core(0)).is_empty());
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        assert_eq!(topo.core_ids_by_os_index(0).len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sharing_cache() -> Result<()> {
+        use crate::CpuSet;
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let tree = topo.tree();
+        let l3_id = topo.l3_cache_ids().next().expect("no L3 caches");
+        let l3_cpuset = tree.get_by_id(&l3_id).expect("invalid NodeId").cpuset();
+        assert_eq!(topo.cores_sharing_cache(l3_id).count(), 6);
+        let threads: CpuSet = topo
+            .threads_sharing_cache(l3_id)
+            .flat_map(|id| tree.get_by_id(&id).expect("invalid NodeId").cpuset())
+            .collect();
+        assert_eq!(&threads, l3_cpuset);
+
+        let l1_id = topo.l1_cache_ids().next().expect("no L1 caches");
+        assert_eq!(topo.cores_sharing_cache(l1_id).count(), 1);
+        assert_eq!(topo.threads_sharing_cache(l1_id).count(), 2);
+
+        // Not a cache
+        let core_id = topo.core_ids().next().expect("no cores");
+        assert_eq!(topo.threads_sharing_cache(core_id).count(), 0);
+        assert_eq!(topo.threads_sharing_cache(1 << 20).count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_smt_siblings() -> Result<()> {
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let thread_id = topo.thread_by_os_index(0).expect("no Thread P#0");
+        let siblings: Vec<_> = topo.smt_siblings_of(thread_id).collect();
+        assert_eq!(
+            siblings,
+            [topo.thread_by_os_index(12).expect("no Thread P#12")]
+        );
+        assert_eq!(topo.threads_per_core(), 2);
+        assert!(topo.smt_enabled());
+        // Not a thread
+        let core_id = topo.core_ids().next().expect("no cores");
+        assert_eq!(topo.smt_siblings_of(core_id).count(), 0);
+
+        // Cores are excluded, hence physical cores are identified through their private caches
+        let topo = t4_topology()?;
+        let thread_id = topo.thread_by_os_index(13).expect("no Thread P#13");
+        let siblings: Vec<_> = topo.smt_siblings_of(thread_id).collect();
+        assert_eq!(
+            siblings,
+            [topo.thread_by_os_index(1).expect("no Thread P#1")]
+        );
+        assert_eq!(topo.threads_per_core(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_enclosing_elements() -> Result<()> {
+        let topo = t4_topology()?;
+        let thread_id = topo.thread_by_os_index(18).expect("no Thread P#18");
+        let numa_node_id = topo.numa_node_by_os_index(1).expect("no NUMA node P#1");
+        assert_eq!(topo.numa_node_of(thread_id), Some(numa_node_id));
+        assert_eq!(topo.numa_node_of(numa_node_id), Some(numa_node_id));
+        assert_eq!(topo.package_of(thread_id), topo.package_by_os_index(1));
+        assert_eq!(topo.package_of(numa_node_id), topo.package_by_os_index(1));
+        // No dies in the topology, no NUMA node encloses the root, and invalid NodeIds
+        assert_eq!(topo.die_of(thread_id), None);
+        assert_eq!(topo.numa_node_of(0), None);
+        assert_eq!(topo.package_of(1 << 20), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cpu_os_indices_under() -> Result<()> {
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        assert_eq!(topo.cpu_os_indices_under(0), (0..24).collect::<Vec<_>>());
+        let package_id = topo.package_by_os_index(1).expect("no Package P#1");
+        assert_eq!(
+            topo.cpu_os_indices_under(package_id),
+            [6, 7, 8, 9, 10, 11, 18, 19, 20, 21, 22, 23]
+        );
+        let core_id = topo.core_ids_by_os_index(0)[0];
+        assert_eq!(topo.cpu_os_indices_under(core_id), [0, 12]);
+        let thread_id = topo.thread_by_os_index(12).expect("no Thread P#12");
+        assert_eq!(topo.cpu_os_indices_under(thread_id), [12]);
+        assert!(topo.cpu_os_indices_under(1 << 20).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_versioned_topology() -> Result<()> {
+        use crate::{VersionedTopology, SCHEMA_VERSION};
+
+        let topo = t4_topology()?;
+        let json = serde_json::to_string(&VersionedTopology::from(topo.clone()))?;
+        assert!(json.starts_with(&format!(r#"{{"version":{SCHEMA_VERSION},"topology":"#)));
+        assert_eq!(
+            serde_json::from_str::<VersionedTopology>(&json)?.into_inner(),
+            topo
+        );
+
+        // Fields in any order, unknown fields ignored
+        let mut value: serde_json::Value = serde_json::from_str(&json)?;
+        value["comment"] = serde_json::json!("forward-compatible");
+        let reordered = format!(
+            r#"{{"topology":{},"version":{SCHEMA_VERSION}}}"#,
+            value["topology"]
+        );
+        assert_eq!(serde_json::from_value::<VersionedTopology>(value)?.0, topo);
+        assert_eq!(
+            serde_json::from_str::<VersionedTopology>(&reordered)?.0,
+            topo
+        );
+
+        // Unsupported or missing versions
+        let err = serde_json::from_str::<VersionedTopology>(r#"{"version":42,"topology":{}}"#)
+            .expect_err("unsupported version accepted");
+        assert!(err
+            .to_string()
+            .contains("Unsupported Topology schema version 42"));
+        // ...even when the topology (of an unknown layout) precedes the version
+        let err = serde_json::from_str::<VersionedTopology>(
+            r#"{"topology":{"nodes":"a newer layout"},"version":42}"#,
+        )
+        .expect_err("unsupported version accepted");
+        assert!(err
+            .to_string()
+            .contains("Unsupported Topology schema version 42"));
+        assert!(serde_json::from_str::<VersionedTopology>(
+            &reordered.replace(&format!(r#","version":{SCHEMA_VERSION}"#), "")
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() -> Result<()> {
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let bytes = topo.to_bytes()?;
+        assert!(bytes.len() < serde_json::to_vec(&topo)?.len());
+        let decoded = Topology::from_bytes(&bytes)?;
+        assert_eq!(decoded, topo);
+        assert_eq!(decoded.thread_by_os_index(7), topo.thread_by_os_index(7));
+
+        assert!(Topology::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+        assert!(Topology::from_bytes(b"not cbor").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotation_string() -> Result<()> {
+        let topo = t4_topology()?;
+        let annotation = topo.to_annotation_string()?;
+        assert!(annotation.len() < T4_JSON.len());
+        assert_eq!(Topology::from_annotation_string(&annotation)?, topo);
+        // Legacy, uncompressed values, as written by earlier versions of the registrant
+        let legacy = include_str!("../test-artifacts/t4_de.json");
+        assert_eq!(Topology::from_annotation_string(legacy)?, topo);
+        assert_eq!(Topology::from_annotation_string(T4_JSON)?, topo);
+
+        assert!(Topology::from_annotation_string("not base64!").is_err());
+        assert!(Topology::from_annotation_string("bm90IGd6aXA=").is_err());
+
+        // Values that decompress into too many bytes (e.g., gzip bombs) are rejected early
+        let bomb = {
+            use base64::{engine::general_purpose::STANDARD, Engine};
+            use flate2::{write::GzEncoder, Compression};
+            use std::io::Write;
+
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(&vec![b' '; crate::MAX_DECOMPRESSED_LEN as usize + 1])?;
+            STANDARD.encode(encoder.finish()?)
+        };
+        assert!(bomb.len() < 64 << 10);
+        let err = Topology::from_annotation_string(&bomb).expect_err("gzip bomb accepted");
+        assert!(matches!(err, crate::Error::Decoding(_)), "{err}");
+        assert!(err.to_string().contains("more than"), "{err}");
+        Ok(())
+    }
+
+    #[cfg(feature = "proto")]
+    #[test]
+    fn test_proto_roundtrip() -> Result<()> {
+        use crate::{
+            proto, CoreKind, Element, MachineInfo, MemoryTier, NumaAttributes, ProcessingElement,
+        };
+        use prost::Message;
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let bytes = proto::Topology::from(&topo).encode_to_vec();
+        let decoded = Topology::try_from(proto::Topology::decode(bytes.as_slice())?)?;
+        assert_eq!(decoded, topo);
+
+        let topo = t4_topology()?;
+        let decoded = Topology::try_from(proto::Topology::from(&topo))?;
+        assert_eq!(decoded, topo);
+
+        // NodeIds are assigned in pre-order
+        let topo = Topology::from_json(NUMA_DISTANCES_JSON)?;
+        let bytes = proto::Topology::from(&topo).encode_to_vec();
+        let decoded = Topology::try_from(proto::Topology::decode(bytes.as_slice())?)?;
+        assert!(decoded.semantically_equal(&topo));
+
+        let mut thread = Element::from(ProcessingElement::Thread(3));
+        thread.set_core_kind(Some(CoreKind::Efficiency));
+        assert_eq!(Element::try_from(proto::Element::from(&thread))?, thread);
+
+        let mut numa_node = Element::from(ProcessingElement::NumaNode(1));
+        if let Element::Processing { memory, .. } = &mut numa_node {
+            *memory = Some(NumaAttributes::new(1024, vec![]).with_tier(Some(MemoryTier::Cxl)));
+        }
+        assert_eq!(
+            Element::try_from(proto::Element::from(&numa_node))?,
+            numa_node
+        );
+
+        let mut machine = topo.tree().root().unwrap().clone();
+        machine.set_machine_info(Some(MachineInfo {
+            hostname: Some("termi5".into()),
+            total_memory: 1 << 30,
+            ..Default::default()
+        }));
+        assert_eq!(Element::try_from(proto::Element::from(&machine))?, machine);
+
+        // Parents must precede their children
+        let mut message = proto::Topology::from(&topo);
+        message.nodes[1].parent = Some(2);
+        assert!(Topology::try_from(message).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_topology_builder() -> Result<()> {
+        use crate::{CacheLevel, CpuSet, Element, IoDeviceKind, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(0, |n| {
+                    n.cache(CacheLevel::L2, |c| {
+                        c.core(0, |c| {
+                            c.thread(0).thread(2);
+                        });
+                    })
+                    .io_device(IoDeviceKind::Network, "eth0");
+                })
+                .numa(1, |n| {
+                    n.cache(CacheLevel::L2, |c| {
+                        c.core(1, |c| {
+                            c.thread(1).thread(3);
+                        });
+                    });
+                });
+            })
+            .build();
+        assert_eq!(topo.thread_ids().count(), 4);
+        assert_eq!(topo.cpu_os_indices_under(0), [0, 1, 2, 3]);
+
+        let numa = topo.numa_node_by_os_index(1).unwrap();
+        assert_eq!(topo.cpu_os_indices_under(numa), [1, 3]);
+        let caches: Vec<_> = topo
+            .tree
+            .iter()
+            .filter_map(|(_, elem)| match elem {
+                Element::Cache { logical_index, .. } => Some(*logical_index),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(caches, [0, 1]);
+        let io_device = topo
+            .tree
+            .iter()
+            .find(|(_, elem)| matches!(elem, Element::IoDevice { .. }))
+            .unwrap()
+            .1;
+        assert_eq!(io_device.cpuset(), &"0,2".parse::<CpuSet>()?);
+
+        let json = serde_json::to_string(&topo)?;
+        assert_eq!(serde_json::from_str::<Topology>(&json)?, topo);
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict_deserialization() -> Result<()> {
+        const MACHINE: &str = r#"{"machine":{"cpuset":"0-1"}}"#;
+        const THREAD_0: &str = r#"{"processing":{"kind":"thread","id":0,"cpuset":"0"}}"#;
+        const THREAD_1: &str = r#"{"processing":{"kind":"thread","id":1,"cpuset":"1"}}"#;
+
+        let valid = format!(
+            r#"{{"nodes":[{{"data":{MACHINE},"desc":[1,2]}},{{"data":{THREAD_0}}},{{"data":{THREAD_1}}}]}}"#
+        );
+        assert_eq!(serde_json::from_str::<Topology>(&valid)?.tree().len(), 3);
+
+        // Out-of-range child
+        let out_of_range = format!(
+            r#"{{"nodes":[{{"data":{MACHINE},"desc":[1,3]}},{{"data":{THREAD_0}}},{{"data":{THREAD_1}}}]}}"#
+        );
+        assert!(serde_json::from_str::<Topology>(&out_of_range).is_err());
+
+        // Element listed as the child of two parents
+        let two_parents = format!(
+            r#"{{"nodes":[{{"data":{MACHINE},"desc":[1,2]}},{{"data":{THREAD_0},"desc":[2]}},{{"data":{THREAD_1}}}]}}"#
+        );
+        assert!(serde_json::from_str::<Topology>(&two_parents).is_err());
+
+        // Non-Machine root
+        let thread_root = format!(r#"{{"nodes":[{{"data":{THREAD_0}}}]}}"#);
+        let err = serde_json::from_str::<Topology>(&thread_root).unwrap_err();
+        assert!(err.to_string().contains("root"));
+
+        // Empty Topology
+        assert!(serde_json::from_str::<Topology>(r#"{"nodes":[]}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_traverse() -> Result<()> {
+        use crate::{CacheLevel, ProcessingElement, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.cache(CacheLevel::L2, |c| {
+                    c.thread(0).thread(1);
+                })
+                .thread(2);
+            })
+            .package(1, |p| {
+                p.thread(3);
+            })
+            .build();
+        let traversal: Vec<_> = topo.traverse().map(|(id, depth, _)| (id, depth)).collect();
+        assert_eq!(
+            traversal,
+            [
+                (0, 0),
+                (1, 1),
+                (2, 2),
+                (3, 3),
+                (4, 3),
+                (5, 2),
+                (6, 1),
+                (7, 2)
+            ]
+        );
+        let (_, _, elem) = topo.traverse().nth(6).unwrap();
+        assert_eq!(elem.processing(), Some(&ProcessingElement::Package(1)));
+
+        let topo = t4_topology()?;
+        assert_eq!(topo.traverse().count(), topo.tree().len());
+        for (id, depth, elem) in topo.traverse() {
+            assert_eq!(topo.tree().ancestor_ids(&id).count(), depth);
+            assert_eq!(topo.tree().get_by_id(&id), Some(elem));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_elements_under() -> Result<()> {
+        use crate::{Element, ProcessingElement};
+
+        let topo = t4_topology()?;
+        let is_thread = |e: &Element| matches!(e.processing(), Some(ProcessingElement::Thread(_)));
+        let numa_id = topo.numa_node_by_os_index(1).unwrap();
+        let threads: Vec<_> = topo
+            .filter_elements_under(&numa_id, is_thread)?
+            .map(|id| topo.tree().get_by_id(&id).unwrap().cpuset().to_string())
+            .collect();
+        assert_eq!(threads.len(), 12);
+        for cpu in [6, 11, 18, 23] {
+            assert!(threads.contains(&cpu.to_string()));
+        }
+        let expected: Vec<_> = topo
+            .filter_elements(is_thread)
+            .filter(|id| topo.tree().is_ancestor_of(&numa_id, id).unwrap())
+            .collect();
+        let mut actual: Vec<_> = topo.filter_elements_under(&numa_id, is_thread)?.collect();
+        actual.sort_unstable();
+        assert_eq!(actual, expected);
+
+        // The root of the subtree is included
+        let numa = |e: &Element| matches!(e.processing(), Some(ProcessingElement::NumaNode(_)));
+        assert_eq!(
+            topo.filter_elements_under(&numa_id, numa)?
+                .collect::<Vec<_>>(),
+            [numa_id]
+        );
+        assert!(topo.filter_elements_under(&9999, is_thread).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_iterators() -> Result<()> {
+        use crate::{CacheLevel, ProcessingElement};
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        let packages: Vec<_> = topo.packages().map(|(_, pkg)| *pkg).collect();
+        assert_eq!(
+            packages,
+            [ProcessingElement::Package(0), ProcessingElement::Package(1)]
+        );
+        assert_eq!(
+            topo.packages().map(|(id, _)| id).collect::<Vec<_>>(),
+            topo.package_ids().collect::<Vec<_>>()
+        );
+        assert_eq!(topo.cores().count(), topo.core_ids().count());
+        assert_eq!(topo.threads().count(), 24);
+        assert!(topo
+            .threads()
+            .all(|(_, thread)| matches!(thread, ProcessingElement::Thread(_))));
+        assert_eq!(topo.dies().count(), topo.die_ids().count());
+        assert_eq!(topo.numa_nodes().count(), 0);
+
+        let topo = t4_topology()?;
+        assert_eq!(topo.numa_nodes().count(), 2);
+        let caches: Vec<_> = topo.caches().collect();
+        assert_eq!(caches.len(), topo.cache_ids().count());
+        for (_, level, attributes) in caches {
+            assert_eq!(level, CacheLevel::L2);
+            assert_eq!(attributes.size(), 262144);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_for() -> Result<()> {
+        use crate::CacheLevel;
+
+        let topo = t4_topology()?;
+        let thread_id = topo.thread_by_os_index(12).unwrap();
+        let l2_id = topo.cache_for(thread_id, CacheLevel::L2).unwrap();
+        assert_eq!(topo.cpu_os_indices_under(l2_id), [0, 12]);
+        assert_eq!(topo.cache_for(l2_id, CacheLevel::L2), Some(l2_id));
+        assert_eq!(topo.cache_for(thread_id, CacheLevel::L3), None);
+        assert_eq!(topo.cache_for(9999, CacheLevel::L2), None);
+
+        let topo: Topology = serde_json::from_str(include_str!(
+            "../test-artifacts/topo__actitree_cpusets.json"
+        ))?;
+        for (core_id, _) in topo.cores() {
+            let l3_id = topo.cache_for(core_id, CacheLevel::L3).unwrap();
+            assert_eq!(topo.package_of(l3_id), topo.package_of(core_id));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_isolation_domains() -> Result<()> {
+        use crate::{CacheLevel, CpuSet, TopologyBuilder};
+
+        // Full-like synthetic topology, with private L1/L2 caches per core
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(0, |n| {
+                    n.cache(CacheLevel::L3, |l3| {
+                        for core in 0..2 {
+                            l3.cache(CacheLevel::L2, |l2| {
+                                l2.cache(CacheLevel::L1, |l1| {
+                                    l1.core(core, |c| {
+                                        c.thread(core).thread(core + 2);
+                                    });
+                                });
+                            });
+                        }
+                    });
+                });
+            })
+            .build();
+        let domains: Vec<_> = topo
+            .isolation_domains()
+            .into_iter()
+            .map(|domain| {
+                (
+                    topo.tree().get_by_id(&domain.id).unwrap().to_string(),
+                    domain.cpuset,
+                )
+            })
+            .collect();
+        let cpuset = |s: &str| s.parse::<CpuSet>();
+        assert_eq!(domains.len(), 8);
+        assert_eq!(domains[0].1, cpuset("0-3")?);
+        assert!(domains[1].0.contains("NUMA"));
+        assert_eq!(domains[2].1, cpuset("0,2")?);
+        assert!(domains[2].0.contains("L2"));
+        assert_eq!(domains[3].1, cpuset("0")?);
+        assert_eq!(domains[4].1, cpuset("2")?);
+
+        // Topologies detected with IsolationBoundariesOnly consist of isolation domains only
+        let topo = t4_topology()?;
+        assert_eq!(topo.isolation_domains().len(), topo.tree().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_allocate() -> Result<()> {
+        use crate::{AllocationPolicy, CacheLevel, CpuSet, Error, TopologyBuilder};
+
+        // 2 packages x 2 L3 caches x 2 cores x 2 threads; core `c` runs threads `c` and `c + 8`
+        let mut builder = TopologyBuilder::machine();
+        for pkg in 0..2 {
+            builder = builder.package(pkg, |p| {
+                p.numa(pkg, |n| {
+                    for l3 in 0..2 {
+                        n.cache(CacheLevel::L3, |c| {
+                            for core in [4 * pkg + 2 * l3, 4 * pkg + 2 * l3 + 1] {
+                                c.core(core, |c| {
+                                    c.thread(core).thread(core + 8);
+                                });
+                            }
+                        });
+                    }
+                });
+            });
+        }
+        let topo = builder.build();
+        let none = CpuSet::new();
+
+        // Packed within a single L3 cache, preferring the most occupied one that fits
+        assert_eq!(
+            topo.allocate(2, &none, AllocationPolicy::Pack)?,
+            [0, 1, 8, 9]
+        );
+        let assigned: CpuSet = [0, 8].into_iter().collect();
+        assert_eq!(topo.allocate(1, &assigned, AllocationPolicy::Pack)?, [1, 9]);
+        assert_eq!(
+            topo.allocate(2, &assigned, AllocationPolicy::Pack)?,
+            [2, 3, 10, 11]
+        );
+        // ...or else within a single NUMA node
+        assert_eq!(
+            topo.allocate(4, &assigned, AllocationPolicy::Pack)?,
+            [4, 5, 6, 7, 12, 13, 14, 15]
+        );
+        assert_eq!(
+            topo.allocate(3, &assigned, AllocationPolicy::Pack)?,
+            [1, 2, 3, 9, 10, 11]
+        );
+
+        // Spread across NUMA nodes
+        assert_eq!(
+            topo.allocate(2, &none, AllocationPolicy::Spread)?,
+            [0, 4, 8, 12]
+        );
+        assert_eq!(
+            topo.allocate(3, &assigned, AllocationPolicy::Spread)?,
+            [1, 4, 5, 9, 12, 13]
+        );
+
+        assert!(matches!(
+            topo.allocate(8, &assigned, AllocationPolicy::Pack),
+            Err(Error::InsufficientCores {
+                requested: 8,
+                available: 7
+            })
+        ));
+        assert!(topo
+            .allocate(0, &none, AllocationPolicy::Spread)?
+            .is_empty());
+
+        // Physical cores are identified by their L2 caches in the absence of cores
+        let topo = t4_topology()?;
+        assert_eq!(
+            topo.allocate(1, &none, AllocationPolicy::default())?,
+            [0, 12]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_coarse_detection_modes() -> Result<()> {
+        let topo = Topology::detect(DetectionMode::CoresOnly)?;
+        assert_eq!(topo.cache_ids().count(), 0);
+        assert_eq!(topo.die_ids().count(), 0);
+        assert!(topo.core_ids().count() > 0);
+
+        let topo = Topology::detect(DetectionMode::NumaOnly)?;
+        assert_eq!(topo.cache_ids().count(), 0);
+        assert_eq!(topo.package_ids().count(), 0);
+        assert_eq!(topo.core_ids().count(), 0);
+        assert!(topo.thread_ids().count() > 0);
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_exclude_packages() -> Result<()> {
+        use crate::{ProcessingElement, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(0, |n| {
+                    n.thread(0).thread(1);
+                });
+            })
+            .package(1, |p| {
+                p.numa(1, |n| {
+                    n.thread(2).thread(3);
+                });
+            })
+            .build();
+        let topo = Topology::new(Topology::exclude_packages(&topo.tree)?);
+        assert_eq!(topo.tree().len(), 7);
+        assert_eq!(topo.package_ids().count(), 0);
+        for (numa_id, _) in topo.numa_nodes() {
+            assert_eq!(topo.tree().parent_id(&numa_id), topo.tree().root_id());
+        }
+        let numa_id = topo.numa_node_by_os_index(1).unwrap();
+        assert_eq!(topo.cpu_os_indices_under(numa_id), [2, 3]);
+        assert_eq!(
+            topo.tree().get_by_id(&numa_id).unwrap().processing(),
+            Some(&ProcessingElement::NumaNode(1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_tier() -> Result<()> {
+        use crate::{Element, MemoryTier, NumaAttributes, ProcessingElement};
+
+        let json = r#"{"processing":{"kind":"numanode","id":1,"cpuset":"0-3","mem":{"size":1024,"tier":"cxl"}}}"#;
+        let numa_node: Element = serde_json::from_str(json)?;
+        assert_eq!(numa_node.memory_tier(), Some(MemoryTier::Cxl));
+        assert_eq!(serde_json::to_string(&numa_node)?, json);
+
+        // Untiered NUMA nodes are (de)serialized as before
+        let json =
+            r#"{"processing":{"kind":"numanode","id":0,"cpuset":"0-3","mem":{"size":1024}}}"#;
+        let numa_node: Element = serde_json::from_str(json)?;
+        assert_eq!(numa_node.memory_tier(), None);
+        assert_eq!(serde_json::to_string(&numa_node)?, json);
+
+        let mut numa_node = Element::from(ProcessingElement::NumaNode(0));
+        if let Element::Processing { memory, .. } = &mut numa_node {
+            *memory = Some(NumaAttributes::new(1024, vec![]).with_tier(Some(MemoryTier::Hbm)));
+        }
+        assert_eq!(numa_node.memory_tier(), Some(MemoryTier::Hbm));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_kind() -> Result<()> {
+        use crate::{CacheKind, Element};
+
+        let json = r#"{"cache":{"lvl":"L1","kind":"data","li":0,"attrs":{"size":32768,"line":64,"ways":8},"cpuset":"0,12"}}"#;
+        let l1d: Element = serde_json::from_str(json)?;
+        assert!(matches!(
+            l1d,
+            Element::Cache {
+                kind: CacheKind::Data,
+                ..
+            }
+        ));
+        assert!(l1d.to_string().starts_with("L1d Cache L#0"));
+        assert_eq!(serde_json::to_string(&l1d)?, json);
+
+        // Caches serialized without a kind are unified
+        let json = r#"{"cache":{"lvl":"L2","li":0,"attrs":{"size":262144,"line":64,"ways":8},"cpuset":"0,12"}}"#;
+        let l2: Element = serde_json::from_str(json)?;
+        assert!(matches!(
+            l2,
+            Element::Cache {
+                kind: CacheKind::Unified,
+                ..
+            }
+        ));
+        assert!(l2.to_string().starts_with("L2 Cache L#0"));
+        assert_eq!(serde_json::to_string(&l2)?, json);
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_offline_threads() -> anyhow::Result<()> {
+        use crate::{AllocationPolicy, CpuSet, ProcessingElement, Topology, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0).thread(2);
+                })
+                .core(1, |c| {
+                    c.thread(1).thread(3);
+                });
+            })
+            .build();
+        let mut tree = topo.tree.clone();
+        Topology::add_offline_threads(&mut tree, &"0-2".parse()?, &"0-4".parse()?)?;
+        let topo = Topology::new(tree);
+
+        let offline: Vec<_> = topo
+            .threads()
+            .filter(|(id, _)| topo.tree.get_by_id(id).unwrap().is_offline())
+            .map(|(_, thread)| *thread)
+            .collect();
+        assert_eq!(
+            offline,
+            [ProcessingElement::Thread(3), ProcessingElement::Thread(4)]
+        );
+        let root_id = topo.tree.root_id().unwrap();
+        let (thread4_id, _) = topo
+            .threads()
+            .find(|(_, thread)| **thread == ProcessingElement::Thread(4))
+            .unwrap();
+        assert_eq!(topo.tree.parent_id(&thread4_id), Some(root_id));
+
+        // The core with the offline hardware thread is not allocatable
+        assert_eq!(
+            topo.allocate(1, &CpuSet::new(), AllocationPolicy::Pack)?,
+            [0, 2]
+        );
+        assert!(topo
+            .allocate(2, &CpuSet::new(), AllocationPolicy::Pack)
+            .is_err());
+
+        let serialized = serde_json::to_string(&topo)?;
+        assert_eq!(serialized.matches(r#""offline":true"#).count(), 2);
+        let deserialized: Topology = serde_json::from_str(&serialized)?;
+        assert_eq!(deserialized, topo);
+        assert!(!serde_json::to_string(topo.tree.root().unwrap())?.contains("offline"));
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_exclude_cpus() -> anyhow::Result<()> {
+        use crate::{ProcessingElement, Topology, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .numa(0, |n| {
+                n.core(0, |c| {
+                    c.thread(0).thread(2);
+                })
+                .core(1, |c| {
+                    c.thread(1).thread(3);
+                });
+            })
+            .numa(1, |n| {
+                n.core(2, |c| {
+                    c.thread(4).thread(5);
+                });
+            })
+            .build();
+        let topo = Topology::new(Topology::exclude_cpus(&topo.tree, &"0,2,4-5".parse()?)?);
+
+        assert_eq!(topo.cpu_os_indices_under(0), [1, 3]);
+        assert_eq!(topo.cores().count(), 1);
+        assert_eq!(
+            topo.numa_nodes().map(|(_, numa)| *numa).collect::<Vec<_>>(),
+            [ProcessingElement::NumaNode(0)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprint() -> anyhow::Result<()> {
+        use crate::{Topology, TopologyBuilder};
+
+        let topo = t4_topology()?;
+        let fingerprint = topo.fingerprint();
+        assert_eq!(fingerprint.len(), 64);
+        assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+        let deserialized: Topology = serde_json::from_str(&serde_json::to_string(&topo)?)?;
+        assert_eq!(deserialized.fingerprint(), fingerprint);
+
+        // Independent of the order of siblings, but not of their content
+        let a = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0);
+                });
+            })
+            .package(1, |p| {
+                p.core(0, |c| {
+                    c.thread(1);
+                });
+            })
+            .build();
+        let b = TopologyBuilder::machine()
+            .package(1, |p| {
+                p.core(0, |c| {
+                    c.thread(1);
+                });
+            })
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0);
+                });
+            })
+            .build();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), fingerprint);
+        assert_ne!(
+            a.fingerprint(),
+            TopologyBuilder::machine().build().fingerprint()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize() -> anyhow::Result<()> {
+        use crate::{CacheLevel, TopologyBuilder};
+
+        let topo = t4_topology()?;
+        let normalized = topo.normalize();
+        assert!(normalized.semantically_equal(&topo));
+        assert_eq!(normalized.normalize(), normalized);
+
+        let ordered = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.cache(CacheLevel::L2, |c| {
+                    c.core(0, |c| {
+                        c.thread(0).thread(2);
+                    });
+                });
+            })
+            .package(1, |p| {
+                p.core(1, |c| {
+                    c.thread(1).thread(3);
+                });
+            })
+            .build();
+        let reversed = TopologyBuilder::machine()
+            .package(1, |p| {
+                p.core(1, |c| {
+                    c.thread(3).thread(1);
+                });
+            })
+            .package(0, |p| {
+                p.cache(CacheLevel::L2, |c| {
+                    c.core(0, |c| {
+                        c.thread(2).thread(0);
+                    });
+                });
+            })
+            .build();
+        assert_ne!(
+            serde_json::to_string(&ordered)?,
+            serde_json::to_string(&reversed)?
+        );
+        assert_eq!(
+            serde_json::to_string(&ordered)?,
+            serde_json::to_string(&reversed.normalize())?
+        );
+        assert_eq!(
+            reversed.normalize().package_ids().collect::<Vec<_>>(),
+            [1, 6]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_retain() -> anyhow::Result<()> {
+        use crate::{CacheLevel, Element, ProcessingElement, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.cache(CacheLevel::L3, |c| {
+                    c.core(0, |c| {
+                        c.thread(0).thread(2);
+                    })
+                    .core(1, |c| {
+                        c.thread(1).thread(3);
+                    });
+                });
+            })
+            .build();
+        let cores = topo.retain(|_, elem| {
+            matches!(
+                elem.processing(),
+                Some(ProcessingElement::Core(_) | ProcessingElement::Thread(_))
+            )
+        });
+        assert_eq!(cores.tree().len(), 7);
+        assert_eq!(cores.package_ids().count(), 0);
+        assert_eq!(cores.cache_ids().count(), 0);
+        for (core_id, _) in cores.cores() {
+            assert_eq!(cores.tree().parent_id(&core_id), cores.tree().root_id());
+        }
+        assert_eq!(cores.cpu_os_indices_under(0), [0, 1, 2, 3]);
+
+        // The single package and its L3 cache share all hardware threads with the machine
+        let partial = topo.isolation_boundaries_only();
+        assert_eq!(partial.tree().len(), 7);
+        assert_eq!(partial.package_ids().count(), 0);
+        assert_eq!(partial.cache_ids().count(), 0);
+        assert_eq!(partial.core_ids().count(), 2);
+        assert_eq!(partial.thread_ids().count(), 4);
+        assert!(matches!(
+            partial.tree().root(),
+            Some(Element::Machine { .. })
+        ));
+
+        let topo = t4_topology()?;
+        assert!(topo.isolation_boundaries_only().semantically_equal(&topo));
+        Ok(())
+    }
+
+    #[test]
+    fn test_machine_info() -> anyhow::Result<()> {
+        use crate::{MachineInfo, Topology};
+
+        // Topologies serialized before the introduction of `MachineInfo` are still valid
+        let topo = t4_topology()?;
+        assert!(topo.machine_info().is_none());
+
+        let info = MachineInfo {
+            hostname: Some("termi5".into()),
+            architecture: Some("x86_64".into()),
+            total_memory: 64 << 30,
+            os_name: Some("Linux".into()),
+            os_release: Some("5.15.0".into()),
+            hwloc_version: Some("2.7.1".into()),
+        };
+        let mut tree = topo.tree().clone();
+        tree.root_mut()
+            .unwrap()
+            .set_machine_info(Some(info.clone()));
+        let stamped = Topology::new(tree);
+        assert_eq!(stamped.machine_info(), Some(&info));
+
+        let serialized = serde_json::to_string(&stamped)?;
+        assert!(serialized.contains(r#""hostname":"termi5","arch":"x86_64""#));
+        let deserialized: Topology = serde_json::from_str(&serialized)?;
+        assert_eq!(deserialized, stamped);
+
+        // The metadata do not describe the hardware topology itself
+        assert_ne!(stamped, topo);
+        assert!(stamped.semantically_equal(&topo));
+        assert_eq!(stamped.fingerprint(), topo.fingerprint());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hwloc_xml() -> anyhow::Result<()> {
+        use crate::{CacheLevel, TopologyBuilder};
+
+        let topo = t4_topology()?;
+        let xml = topo.to_hwloc_xml();
+        assert!(xml.starts_with("<?xml"));
+        assert_eq!(
+            xml.matches("<object ").count(),
+            xml.matches("</object>").count()
+        );
+        assert_eq!(xml.matches(r#"type="NUMANode""#).count(), 2);
+        assert_eq!(xml.matches(r#"type="Group""#).count(), 2);
+        assert!(xml.contains(
+            r#"<object type="NUMANode" os_index="1" cpuset="0x00fc0fc0" complete_cpuset="0x00fc0fc0" nodeset="0x00000002""#
+        ));
+
+        // A NUMA node is made up, and the cpusets span multiple words
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.cache(CacheLevel::L3, |c| {
+                    c.core(0, |c| {
+                        c.thread(0).thread(40);
+                    });
+                });
+            })
+            .build();
+        let xml = topo.to_hwloc_xml();
+        assert!(
+            xml.contains(r#"<object type="Machine" os_index="0" cpuset="0x00000100,0x00000001""#)
+        );
+        assert!(xml.contains(r#"<object type="NUMANode" os_index="0""#));
+        assert!(xml.contains(r#"type="L3Cache""#));
+        assert_eq!(xml.matches(r#"type="PU""#).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cpuset_conversions() -> anyhow::Result<()> {
+        use crate::CpuSet;
+
+        let cpus: CpuSet = "0-3,8,10-11".parse()?;
+        let os_indices = Vec::from(&cpus);
+        assert_eq!(os_indices, [0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(CpuSet::from(os_indices.as_slice()), cpus);
+        assert_eq!(cpus.to_string(), "0-3,8,10-11");
+
+        let topo = t4_topology()?;
+        let numa_ids: Vec<_> = topo.numa_node_ids().collect();
+        assert_eq!(topo.cpuset_of(numa_ids)?, "0-23".parse()?);
+        assert!(topo.cpuset_of([topo.tree().len() as NodeId]).is_err());
+
+        let thread_ids = topo.thread_ids_in(&"0,12,99".parse()?);
+        assert_eq!(thread_ids.len(), 2);
+        assert_eq!(topo.cpuset_of(thread_ids)?.to_string(), "0,12");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "detect", feature = "bind", target_os = "linux"))]
+    fn test_bind_current_thread() -> anyhow::Result<()> {
+        use crate::{Error, Topology, TopologyBuilder};
+
+        let status = fs::read_to_string("/proc/thread-self/status")?;
+        let (allowed_cpus, _) = Topology::parse_allowed_resources(&status)?;
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                for cpu in &allowed_cpus {
+                    p.core(cpu, |c| {
+                        c.thread(cpu);
+                    });
+                }
+            })
+            .build();
+
+        let first_cpu = allowed_cpus.iter().next().unwrap();
+        topo.bind_current_thread(topo.thread_by_os_index(first_cpu).unwrap())?;
+        let status = fs::read_to_string("/proc/thread-self/status")?;
+        let (bound_cpus, _) = Topology::parse_allowed_resources(&status)?;
+        assert_eq!(bound_cpus.to_string(), first_cpu.to_string());
+
+        assert!(matches!(
+            topo.bind_current_thread(topo.tree().len() as NodeId),
+            Err(Error::ImmuTree { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_json_schema() -> anyhow::Result<()> {
+        use crate::Topology;
+
+        let schema = serde_json::to_value(schemars::schema_for!(Topology))?;
+        assert_eq!(schema["required"], serde_json::json!(["nodes"]));
+        let node = &schema["properties"]["nodes"]["items"];
+        assert_eq!(node["properties"]["data"]["$ref"], "#/definitions/Element");
+        // ProcessingElement is flattened into Element, hence it is inlined
+        let processing = &schema["definitions"]["Element"]["oneOf"]
+            .as_array()
+            .expect("Element is not a oneOf")
+            .iter()
+            .find_map(|variant| variant["properties"].get("processing"))
+            .expect("no processing variant");
+        assert_eq!(
+            processing["properties"]["kind"]["enum"],
+            serde_json::json!(["package", "die", "numanode", "core", "thread"])
+        );
+        assert_eq!(processing["properties"]["id"]["type"], "integer");
+        for name in [
+            "Element",
+            "CacheLevel",
+            "CacheAttributes",
+            "NumaAttributes",
+            "CpuSet",
+        ] {
+            assert!(schema["definitions"].get(name).is_some(), "{name} missing");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_find() -> anyhow::Result<()> {
+        use crate::{CacheLevel, Element, ProcessingElement, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(0, |n| {
+                    n.cache(CacheLevel::L2, |c| {
+                        c.core(0, |c| {
+                            c.thread(0).thread(2);
+                        });
+                    })
+                    .cache(CacheLevel::L2, |c| {
+                        c.core(1, |c| {
+                            c.thread(1).thread(3);
+                        });
+                    });
+                });
+            })
+            .build();
+        let is_thread = |e: &Element| matches!(e.processing(), Some(ProcessingElement::Thread(_)));
+        assert_eq!(topo.find(is_thread), Some(5));
+        assert_eq!(topo.find(|_| false), None);
+        let core_1 = topo.find_map(|id, e| match e.processing() {
+            Some(ProcessingElement::Core(1)) => Some(id),
+            _ => None,
+        });
+        assert_eq!(core_1, Some(8));
+
+        // Reverse iteration, and iteration from both ends
+        assert_eq!(topo.thread_ids().next_back(), Some(10));
+        assert_eq!(topo.thread_ids().rev().collect::<Vec<_>>(), [10, 9, 6, 5]);
+        assert_eq!(
+            topo.threads().next_back(),
+            Some((10, &ProcessingElement::Thread(3)))
+        );
+        let mut thread_ids = topo.thread_ids();
+        assert_eq!(thread_ids.next(), Some(5));
+        assert_eq!(thread_ids.next_back(), Some(10));
+        assert_eq!(thread_ids.next(), Some(6));
+        assert_eq!(thread_ids.next_back(), Some(9));
+        assert_eq!((thread_ids.next(), thread_ids.next_back()), (None, None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_flatten() -> anyhow::Result<()> {
+        use crate::{Element, ProcessingElement, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0).thread(1);
+                });
+            })
+            .package(1, |p| {
+                p.core(1, |c| {
+                    c.thread(2);
+                });
+            })
+            .build();
+        let flat = topo.flatten();
+        assert_eq!(flat.len(), topo.tree().len());
+        let edges: Vec<_> = flat
+            .iter()
+            .map(|&(id, parent_id, _)| (id, parent_id))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (0, None),
+                (1, Some(0)),
+                (2, Some(1)),
+                (3, Some(2)),
+                (4, Some(2)),
+                (5, Some(0)),
+                (6, Some(5)),
+                (7, Some(6)),
+            ]
+        );
+        assert!(matches!(flat[0].2, Element::Machine { .. }));
+        assert_eq!(flat[7].2.processing(), Some(&ProcessingElement::Thread(2)));
+
+        let topo = t4_topology()?;
+        for (id, parent_id, elem) in topo.flatten() {
+            assert_eq!(topo.tree().get_by_id(&id), Some(elem));
+            assert_eq!(topo.tree().parent_id(&id), parent_id);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_helpers() -> anyhow::Result<()> {
+        let topo = t4_topology()?;
+        assert_eq!(topo.to_json()?, serde_json::to_string(&topo)?);
+        assert_eq!(Topology::from_json(&topo.to_json()?)?, topo);
+        let pretty = topo.to_json_pretty()?;
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(Topology::from_json(&pretty)?, topo);
+
+        // Deserialized topologies are validated
+        let invalid_root =
+            r#"{"nodes":[{"data":{"processing":{"kind":"core","id":0}},"desc":[]}]}"#;
+        assert!(matches!(
+            Topology::from_json(invalid_root),
+            Err(crate::Error::Decoding(_))
+        ));
+        assert!(Topology::from_json("not json").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_roundtrip() -> anyhow::Result<()> {
+        let topo = t4_topology()?;
+        let yaml = topo.to_yaml()?;
+        assert_eq!(Topology::from_yaml(&yaml)?, topo);
+        assert!(Topology::from_yaml("nodes: []").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_addressing() -> anyhow::Result<()> {
+        use crate::{CacheLevel, IoDeviceKind, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(0, |n| {
+                    n.cache(CacheLevel::L2, |c| {
+                        c.core(0, |c| {
+                            c.thread(0).thread(2);
+                        });
+                    })
+                    .cache(CacheLevel::L2, |c| {
+                        c.core(1, |c| {
+                            c.thread(1).thread(3);
+                        });
+                    });
+                })
+                .io_device(IoDeviceKind::Network, "eth0");
+            })
+            .build();
+        assert_eq!(topo.path_of(0).as_deref(), Some(""));
+        assert_eq!(
+            topo.path_of(10).as_deref(),
+            Some("package:0/numa:0/l2:1/core:1/thread:3")
+        );
+        assert_eq!(topo.path_of(11).as_deref(), Some("package:0/io:eth0"));
+        assert_eq!(topo.path_of(12), None);
+
+        assert_eq!(topo.resolve_path(""), Some(0));
+        assert_eq!(topo.resolve_path("package:0/numa:0/l2:1/core:1"), Some(8));
+        assert_eq!(topo.resolve_path("/package:0/io:eth0/"), Some(11));
+        // Each element is only referred to through its full path
+        assert_eq!(topo.resolve_path("package:0/core:1"), None);
+        assert_eq!(topo.resolve_path("package:1"), None);
+        assert_eq!(topo.resolve_path("package:0/numa:0/l3:1"), None);
+
+        let topo = t4_topology()?;
+        for (id, _) in topo.tree().iter() {
+            let path = topo.path_of(id).expect("no path for a valid NodeId");
+            assert_eq!(topo.resolve_path(&path), Some(id));
+        }
+        Ok(())
+    }
+
+    /// A machine with 4 NUMA nodes, each with a single hardware thread; NUMA node P#0 knows its
+    /// distances, while P#3 does not.
+    const NUMA_DISTANCES_JSON: &str = r#"{"nodes":[
+        {"data":{"machine":{"cpuset":"0-3"}},"desc":[1,2,3,4]},
+        {"data":{"processing":{"kind":"numanode","id":0,"cpuset":"0","mem":{"size":1024,"dist":{"0":10,"1":21,"2":31,"3":21}}}},"desc":[5]},
+        {"data":{"processing":{"kind":"numanode","id":1,"cpuset":"1","mem":{"size":1024}}},"desc":[6]},
+        {"data":{"processing":{"kind":"numanode","id":2,"cpuset":"2","mem":{"size":1024}}},"desc":[7]},
+        {"data":{"processing":{"kind":"numanode","id":3,"cpuset":"3","mem":{"size":1024}}},"desc":[8]},
+        {"data":{"processing":{"kind":"thread","id":0,"cpuset":"0"}}},
+        {"data":{"processing":{"kind":"thread","id":1,"cpuset":"1"}}},
+        {"data":{"processing":{"kind":"thread","id":2,"cpuset":"2"}}},
+        {"data":{"processing":{"kind":"thread","id":3,"cpuset":"3"}}}
+    ]}"#;
+
+    #[test]
+    fn test_numa_nodes_by_distance() -> anyhow::Result<()> {
+        let topo = Topology::from_json(NUMA_DISTANCES_JSON)?;
+        let memory = topo.tree().get_by_id(&1).and_then(|e| e.memory());
+        assert_eq!(memory.and_then(|memory| memory.distance_to(2)), Some(31));
+        assert_eq!(Topology::from_json(&topo.to_json()?)?, topo);
+
+        // Ties are broken by OS index
+        assert_eq!(topo.numa_nodes_by_distance(1), [2, 4, 3]);
+        // Unknown distances
+        assert_eq!(topo.numa_nodes_by_distance(4), [1, 2, 3]);
+        // Not NUMA nodes
+        assert!(topo.numa_nodes_by_distance(0).is_empty());
+        assert!(topo.numa_nodes_by_distance(5).is_empty());
+        assert!(topo.numa_nodes_by_distance(42).is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_add_numa_distances() -> anyhow::Result<()> {
+        let topo = Topology::from_json(NUMA_DISTANCES_JSON)?;
+        let rows = ["10 21 31 21", "21 10 21 31", "31 21 10 21", "21 31 21 10\n"];
+        let mut tree = topo.tree().clone();
+        Topology::add_numa_distances(&mut tree, &"0-3".parse()?, |os_index| {
+            Ok(rows[os_index as usize].to_owned())
+        })?;
+        let topo = Topology::new(tree);
+        let memory = topo.tree().get_by_id(&4).and_then(|e| e.memory());
+        assert_eq!(
+            memory.map(|memory| memory.distances().values().copied().collect::<Vec<_>>()),
+            Some(vec![21, 31, 21, 10])
+        );
+        assert_eq!(topo.numa_nodes_by_distance(4), [1, 3, 2]);
+
+        // Rows that do not match the online NUMA nodes
+        let mut tree = topo.tree().clone();
+        let err =
+            Topology::add_numa_distances(&mut tree, &"0-3".parse()?, |_| Ok("10 21".to_owned()));
+        assert!(matches!(err, Err(crate::Error::NumaDistances(_))));
+        let err = Topology::add_numa_distances(&mut tree, &"0-3".parse()?, |_| {
+            Ok("10 21 x 21".to_owned())
+        });
+        assert!(matches!(err, Err(crate::Error::NumaDistances(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_elements() -> anyhow::Result<()> {
+        use crate::Element;
+
+        let json = r#"{"nodes":[
+            {"data":{"machine":{"cpuset":"0-1"}},"desc":[1]},
+            {"data":{"accelerator":{"model":"xpu","cpuset":"0-1","lanes":[1,2]}},"desc":[2,3]},
+            {"data":{"processing":{"kind":"thread","id":0,"cpuset":"0"}}},
+            {"data":{"processing":{"kind":"thread","id":1,"cpuset":"1"}}}
+        ]}"#;
+        let topo = Topology::from_json(json)?;
+        let unknown = topo.tree().get_by_id(&1).expect("no unknown element");
+        match unknown {
+            Element::Unknown { kind, cpuset, .. } => {
+                assert_eq!(kind, "accelerator");
+                assert_eq!(cpuset.to_string(), "0-1");
+            }
+            _ => panic!("expected an unknown element, found {unknown}"),
+        }
+        assert_eq!(unknown.to_string(), r#"Unknown element "accelerator""#);
+        assert_eq!(topo.thread_ids().count(), 2);
+
+        // Serialized back as it was
+        let reserialized: serde_json::Value = serde_json::from_str(&topo.to_json()?)?;
+        assert_eq!(
+            reserialized,
+            serde_json::from_str::<serde_json::Value>(json)?
+        );
+        assert_eq!(Topology::from_json(&topo.to_json()?)?, topo);
+        assert_eq!(Topology::from_bytes(&topo.to_bytes()?)?, topo);
+
+        // Malformed elements of known kinds are still rejected
+        let malformed = json.replace(r#""kind":"thread","id":1"#, r#""kind":"bogus","id":1"#);
+        assert!(Topology::from_json(&malformed).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_add_frequencies() -> anyhow::Result<()> {
+        use crate::{CpuFrequency, Element, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.core(0, |c| {
+                    c.thread(0).thread(2);
+                })
+                .core(1, |c| {
+                    c.thread(1).thread(3);
+                });
+            })
+            .build();
+        let frequency = CpuFrequency {
+            base: Some(2100),
+            max: Some(4800),
+        };
+        let mut tree = topo.tree().clone();
+        Topology::add_frequencies(&mut tree, |os_index| match os_index {
+            0 | 2 => Some(frequency),
+            1 => Some(CpuFrequency::default()),
+            _ => None,
+        });
+        let topo = Topology::new(tree);
+        let frequency_of = |path: &str| {
+            topo.resolve_path(path)
+                .and_then(|id| topo.tree().get_by_id(&id))
+                .and_then(Element::frequency)
+        };
+        assert_eq!(frequency_of("package:0/core:0"), Some(frequency));
+        assert_eq!(frequency_of("package:0/core:0/thread:2"), Some(frequency));
+        assert_eq!(frequency_of("package:0/core:1"), None);
+        assert_eq!(frequency_of("package:0/core:1/thread:3"), None);
+        assert_eq!(frequency_of("package:0"), None);
+
+        let json = topo.to_json()?;
+        assert!(json.contains(r#""freq":{"base":2100,"max":4800}"#));
+        assert_eq!(Topology::from_json(&json)?, topo);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_domains() -> anyhow::Result<()> {
+        use crate::{CacheLevel, TopologyBuilder};
+
+        // Two L3 caches (e.g., CCXs) of two cores each, on a single package
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                for ccx in 0..2 {
+                    p.cache(CacheLevel::L3, |l3| {
+                        for core in 2 * ccx..2 * ccx + 2 {
+                            l3.core(core, |c| {
+                                c.thread(core).thread(core + 4);
+                            });
+                        }
+                    });
+                }
+            })
+            .build();
+        let domains = topo.cache_domains(CacheLevel::L3);
+        assert_eq!(domains.len(), 2);
+        assert_eq!(domains[0].cores, [0, 1]);
+        assert_eq!(domains[0].cpuset, "0-1,4-5".parse()?);
+        assert_eq!(domains[1].cores, [2, 3]);
+        assert_eq!(domains[1].cpuset, "2-3,6-7".parse()?);
+        assert_eq!(
+            topo.cache_for(topo.core_ids().next_back().unwrap(), CacheLevel::L3),
+            Some(domains[1].id)
+        );
+        assert!(topo.cache_domains(CacheLevel::L2).is_empty());
+
+        // Cores excluded by `DetectionMode::IsolationBoundariesOnly`
+        let topo = t4_topology()?;
+        let domains = topo.cache_domains(CacheLevel::L2);
+        assert_eq!(domains.len(), 12);
+        assert!(domains.iter().all(|domain| domain.cores.is_empty()));
+        assert_eq!(domains[11].cpuset, "11,23".parse()?);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_filter_elements() -> anyhow::Result<()> {
+        use crate::{Element, ProcessingElement};
+
+        let topo = t4_topology()?;
+        let is_thread = |e: &Element| matches!(e.processing(), Some(ProcessingElement::Thread(_)));
+        let threads = topo.par_filter_elements(is_thread);
+        assert_eq!(threads.len(), 24);
+        assert_eq!(threads, topo.filter_elements(is_thread).collect::<Vec<_>>());
+        assert!(topo.par_filter_elements(|e| e.is_offline()).is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn test_arbitrary_topologies(
+            topo in proptest::prelude::any_with::<Topology>(crate::TopologyBounds {
+                max_packages: 2,
+                max_numa_nodes_per_package: 4,
+                max_cores_per_numa_node: 3,
+                max_threads_per_core: 4,
+            })
+        ) {
+            use crate::ProcessingElement;
+
+            let packages = topo.package_ids().count();
+            let numa_nodes = topo.numa_node_ids().count();
+            proptest::prop_assert!((1..=2).contains(&packages));
+            proptest::prop_assert!((packages..=4 * packages).contains(&numa_nodes));
+            proptest::prop_assert!(topo.threads_per_core() <= 4);
+
+            // Threads are unique, and the root spans all of them
+            let threads: Vec<_> = topo
+                .threads()
+                .map(|(_, pe)| match pe {
+                    ProcessingElement::Thread(os_index) => *os_index,
+                    _ => unreachable!(),
+                })
+                .collect();
+            let root_id = topo.tree().root_id().expect("empty topology");
+            proptest::prop_assert_eq!(&topo.cpu_os_indices_under(root_id), &threads);
+            proptest::prop_assert_eq!(
+                topo.tree().root().map(|root| root.cpuset().len()),
+                Some(threads.len())
+            );
+            proptest::prop_assert_eq!(Topology::from_json(&topo.to_json().unwrap()).unwrap(), topo);
+        }
+    }
+
+    #[test]
+    fn test_serialize_subtrees() -> anyhow::Result<()> {
+        let topo = t4_topology()?;
+        let numa_node = topo.numa_node_by_os_index(1).expect("no NUMA node P#1");
+
+        let mut json = Vec::new();
+        topo.serialize_subtrees(&[numa_node], &mut serde_json::Serializer::new(&mut json))?;
+        let partial: Topology = serde_json::from_slice(&json)?;
+        assert_eq!(partial.package_ids().count(), 0);
+        assert_eq!(partial.numa_node_ids().count(), 1);
+        assert_eq!(partial.l2_cache_ids().count(), 6);
+        assert_eq!(partial.thread_ids().count(), 12);
+        let root = partial.tree().root().expect("empty topology");
+        assert_eq!(root.cpuset(), &"6-11,18-23".parse()?);
+        assert_eq!(partial.tree().arity(&0)?, 1);
+
+        // The whole topology, if its root is selected
+        assert_eq!(topo.subtrees(&[0, numa_node])?, topo);
+        assert!(topo
+            .serialize_subtrees(&[999], &mut serde_json::Serializer::new(Vec::new()))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_topology_delta() -> anyhow::Result<()> {
+        use crate::{Error, ProcessingElement, TopologyDelta};
+
+        let topo = t4_topology()?;
+        let without = |os_index| {
+            topo.retain(|_, e| e.processing() != Some(&ProcessingElement::Thread(os_index)))
+        };
+
+        // No changes
+        let delta = topo.delta(&topo);
+        assert!(delta.is_empty());
+        assert_eq!(delta.baseline_fingerprint(), topo.fingerprint());
+        assert_eq!(topo.apply_delta(&delta)?, topo);
+
+        // The last hardware thread goes offline, and back online
+        let unplugged = without(23);
+        let delta = unplugged.delta(&topo);
+        assert!(!delta.is_empty());
+        let json = serde_json::to_string(&delta)?;
+        assert!(json.len() < 200, "delta too large: {json}");
+        let delta: TopologyDelta = serde_json::from_str(&json)?;
+        assert_eq!(topo.apply_delta(&delta)?, unplugged);
+        let delta = topo.delta(&unplugged);
+        assert_eq!(delta.baseline_fingerprint(), unplugged.fingerprint());
+        assert_eq!(unplugged.apply_delta(&delta)?, topo);
+
+        // Elements are renumbered, and the delta is applied to the wrong baseline
+        let unplugged = without(0);
+        let delta = unplugged.delta(&topo);
+        assert_eq!(topo.apply_delta(&delta)?, unplugged);
+        assert!(matches!(
+            unplugged.apply_delta(&delta),
+            Err(Error::Delta(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sysfs_path() -> anyhow::Result<()> {
+        use std::{fs, path::Path};
+
+        use crate::{CacheLevel, IoDeviceKind, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(1, |n| {
+                    n.cache(CacheLevel::L3, |l3| {
+                        l3.cache(CacheLevel::L2, |l2| {
+                            l2.cache(CacheLevel::L1, |l1| {
+                                l1.core(4, |c| {
+                                    c.thread(2).thread(6);
+                                });
+                            });
+                        });
+                    });
+                });
+            })
+            .io_device(IoDeviceKind::Network, "eth0")
+            .io_device(IoDeviceKind::CoProcessor, "cuda0")
+            .build();
+        let sysfs_path = |path: &str| topo.resolve_path(path).and_then(|id| topo.sysfs_path(id));
+
+        assert_eq!(sysfs_path(""), None);
+        assert_eq!(
+            sysfs_path("package:0").as_deref(),
+            Some(Path::new("/sys/devices/system/cpu/cpu2/topology"))
+        );
+        assert_eq!(
+            sysfs_path("package:0/numa:1").as_deref(),
+            Some(Path::new("/sys/devices/system/node/node1"))
+        );
+        assert_eq!(
+            sysfs_path("package:0/numa:1/l3:0/l2:0/l1:0/core:4/thread:6").as_deref(),
+            Some(Path::new("/sys/devices/system/cpu/cpu6"))
+        );
+        assert_eq!(
+            sysfs_path("io:eth0").as_deref(),
+            Some(Path::new("/sys/class/net/eth0"))
+        );
+        assert_eq!(sysfs_path("io:cuda0"), None);
+        assert_eq!(topo.sysfs_path(999), None);
+
+        // The directories of caches are looked up by their level and type, in a fake sysfs where
+        // L1 is either unified or split
+        let sysfs = std::env::temp_dir().join(format!("actitopo-sysfs-{}", std::process::id()));
+        let cache_dir = sysfs.join("devices/system/cpu/cpu2/cache");
+        let (l1, l2, l3) = (
+            "package:0/numa:1/l3:0/l2:0/l1:0",
+            "package:0/numa:1/l3:0/l2:0",
+            "package:0/numa:1/l3:0",
+        );
+        let layouts = [
+            (
+                &[(1, "Unified"), (2, "Unified"), (3, "Unified")][..],
+                [(l1, "index0"), (l2, "index1"), (l3, "index2")],
+            ),
+            (
+                &[
+                    (1, "Data"),
+                    (1, "Instruction"),
+                    (2, "Unified"),
+                    (3, "Unified"),
+                ][..],
+                [(l1, "index0"), (l2, "index2"), (l3, "index3")],
+            ),
+        ];
+        for (caches, expected) in layouts {
+            let _ = fs::remove_dir_all(&sysfs);
+            for (index, (level, type_)) in caches.iter().enumerate() {
+                let index_dir = cache_dir.join(format!("index{index}"));
+                fs::create_dir_all(&index_dir)?;
+                fs::write(index_dir.join("level"), format!("{level}\n"))?;
+                fs::write(index_dir.join("type"), format!("{type_}\n"))?;
+            }
+            for (path, index) in expected {
+                let id = topo.resolve_path(path).expect("unresolved path");
+                assert_eq!(
+                    topo.sysfs_path_under(id, &sysfs),
+                    Some(cache_dir.join(index)),
+                    "{caches:?}: {path}"
+                );
+            }
+        }
+        fs::remove_dir_all(&sysfs)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_topologies() -> anyhow::Result<()> {
+        use crate::Element;
+
+        // Serialized before elements carried their CpuSets (and the Machine any field)
+        let legacy = [
+            (include_str!("../test-artifacts/t4_de.json"), T4_JSON),
+            (
+                include_str!("../test-artifacts/topo__actitree.json"),
+                include_str!("../test-artifacts/topo__actitree_cpusets.json"),
+            ),
+        ];
+        for (legacy, current) in legacy {
+            assert_eq!(Topology::from_json(legacy)?, Topology::from_json(current)?);
+        }
+        assert_eq!(
+            serde_json::from_str::<Element>(r#""machine""#)?,
+            Element::Machine {
+                cpuset: Default::default(),
+                info: None,
+            }
+        );
+        let huge = r#"{"nodes":[
+            {"data":"machine","desc":[1]},
+            {"data":{"processing":{"kind":"thread","id":4294967295}}}
+        ]}"#;
+        assert!(Topology::from_json(huge).is_err());
+        let cache = r#"{"cache":{"lvl":"L2","li":0,"attrs":{"size":262144,"line":64,"ways":8}}}"#;
+        assert!(serde_json::from_str::<Element>(cache)?.cpuset().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ancestor_queries_scale_with_depth() -> anyhow::Result<()> {
+        use crate::TopologyBuilder;
+        use std::time::{Duration, Instant};
+
+        // Parent links are restored on deserialization, hence ancestor queries only walk the
+        // (here, 3) ancestors of each element, regardless of the size of the topology; walking
+        // the whole topology per query instead takes minutes for the queries below.
+        let mut builder = TopologyBuilder::machine();
+        for package in 0..64 {
+            builder = builder.package(package, |p| {
+                for core in 0..128 {
+                    p.core(core, |c| {
+                        c.thread(package * 128 + core);
+                    });
+                }
+            });
+        }
+        let topo = Topology::from_json(&builder.build().to_json()?)?;
+        assert_eq!(topo.tree().len(), 1 + 64 + 64 * 128 * 2);
+
+        let thread_ids: Vec<NodeId> = topo.thread_ids().collect();
+        let start = Instant::now();
+        for _ in 0..100 {
+            for id in &thread_ids {
+                let core_id = topo.tree().parent_id(id).expect("orphan Thread");
+                assert_eq!(topo.tree().ancestor_ids(id).next(), Some(core_id));
+                assert_eq!(topo.tree().ancestors(id).count(), 3);
+            }
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "{:?}",
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_add_numa_memory() -> anyhow::Result<()> {
+        use std::fs;
+
+        use crate::{detect::numa_memory_in, PageType, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.numa(0, |n| {
+                    n.core(0, |c| {
+                        c.thread(0);
+                    });
+                });
+            })
+            .package(1, |p| {
+                p.numa(1, |n| {
+                    n.core(1, |c| {
+                        c.thread(1);
+                    });
+                });
+            })
+            .build();
+
+        // A fake sysfs, where the memory of NUMA node P#1 is not reported
+        let sysfs = std::env::temp_dir().join(format!("actitopo-node-{}", std::process::id()));
+        let node_dir = sysfs.join("node0");
+        for (size, count) in [("2048", "4\n"), ("1048576", "0\n")] {
+            let hugepages_dir = node_dir.join(format!("hugepages/hugepages-{size}kB"));
+            fs::create_dir_all(&hugepages_dir)?;
+            fs::write(hugepages_dir.join("nr_hugepages"), count)?;
+        }
+        fs::write(
+            node_dir.join("meminfo"),
+            "Node 0 MemTotal:       32657356 kB\nNode 0 MemFree:        30123456 kB\n",
+        )?;
+
+        let mut tree = topo.tree().clone();
+        Topology::add_numa_memory(&mut tree, |os_index| {
+            numa_memory_in(&sysfs.join(format!("node{os_index}")))
+        });
+        fs::remove_dir_all(&sysfs)?;
+        let topo = Topology::new(tree);
+        let memory_of = |path: &str| {
+            topo.resolve_path(path)
+                .and_then(|id| topo.tree().get_by_id(&id))
+                .and_then(|elem| elem.memory().cloned())
+        };
+        let memory = memory_of("package:0/numa:0").expect("NUMA node memory not found");
+        assert_eq!(memory.local_memory(), 32657356 << 10);
+        assert_eq!(
+            memory.page_types(),
+            [PageType::new(2 << 20, 4), PageType::new(1 << 30, 0)]
+        );
+        assert_eq!(memory_of("package:1/numa:1"), None);
+
+        // Distances are only set on the NUMA nodes whose memory has been detected
+        let mut tree = topo.tree().clone();
+        Topology::add_numa_distances(&mut tree, &"0-1".parse()?, |_| Ok("10 21".to_owned()))?;
+        let topo = Topology::new(tree);
+        let memory_of = |path: &str| {
+            topo.resolve_path(path)
+                .and_then(|id| topo.tree().get_by_id(&id))
+                .and_then(|elem| elem.memory().cloned())
+        };
+        let memory = memory_of("package:0/numa:0").expect("NUMA node memory not found");
+        assert_eq!(memory.distance_to(1), Some(21));
+        assert_eq!(memory_of("package:1/numa:1"), None);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "detect", unix))]
+    #[test]
+    fn test_io_devices_in() -> anyhow::Result<()> {
+        use std::{fs, os::unix::fs::symlink};
+
+        use crate::{detect::io_devices_in, Element, IoDeviceKind};
+
+        // A fake sysfs with a NIC and an NVMe drive on PCI devices local to different NUMA nodes,
+        // a GPU with a display connector, and the loopback interface
+        let sysfs = std::env::temp_dir().join(format!("actitopo-class-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&sysfs);
+        let pci_dir = sysfs.join("devices/pci0000:00");
+        let devices = [
+            ("0000:00:01.0", "0-5,12-17\n", "net/eth0", "net/eth0"),
+            ("0000:00:02.0", "6-11\n", "nvme/nvme0", "block/nvme0n1"),
+            ("0000:00:03.0", "0-23\n", "drm/card0", "drm/card0"),
+            ("0000:00:03.0", "0-23\n", "drm/card0", "drm/card0-HDMI-A-1"),
+        ];
+        for (pci_device, local_cpulist, device, class_entry) in devices {
+            let device_dir = pci_dir.join(pci_device).join(device);
+            fs::create_dir_all(&device_dir)?;
+            fs::write(
+                pci_dir.join(pci_device).join("local_cpulist"),
+                local_cpulist,
+            )?;
+            let class_entry = sysfs.join("class").join(class_entry);
+            fs::create_dir_all(&class_entry)?;
+            symlink(&device_dir, class_entry.join("device"))?;
+        }
+        fs::create_dir_all(sysfs.join("class/net/lo"))?;
+
+        let io_devices = io_devices_in(&sysfs.join("class"));
+        fs::remove_dir_all(&sysfs)?;
+        let io_device = |kind, name: &str, cpuset: &str| -> anyhow::Result<Element> {
+            Ok(Element::IoDevice {
+                kind,
+                name: name.to_owned(),
+                cpuset: cpuset.parse()?,
+            })
+        };
+        assert_eq!(
+            io_devices,
+            [
+                io_device(IoDeviceKind::Block, "nvme0n1", "6-11")?,
+                io_device(IoDeviceKind::Gpu, "card0", "0-23")?,
+                io_device(IoDeviceKind::Network, "eth0", "0-5,12-17")?,
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_add_cache_kinds() -> anyhow::Result<()> {
+        use std::fs;
+
+        use crate::{detect::cache_kind_in, CacheKind, CacheLevel, Element, TopologyBuilder};
+
+        let topo = TopologyBuilder::machine()
+            .package(0, |p| {
+                p.cache(CacheLevel::L2, |l2| {
+                    l2.cache(CacheLevel::L1, |l1| {
+                        l1.core(0, |c| {
+                            c.thread(0);
+                        });
+                    });
+                })
+                .cache(CacheLevel::L2, |l2| {
+                    l2.cache(CacheLevel::L1, |l1| {
+                        l1.core(1, |c| {
+                            c.thread(1);
+                        });
+                    });
+                });
+            })
+            .build();
+
+        // A fake sysfs, where L1 is split, while the caches of CPU 1 are not reported
+        let sysfs = std::env::temp_dir().join(format!("actitopo-cache-{}", std::process::id()));
+        let cache_dir = |os_index| sysfs.join(format!("cpu{os_index}/cache"));
+        let caches = [(1, "Data"), (1, "Instruction"), (2, "Unified")];
+        for (index, (level, type_)) in caches.iter().enumerate() {
+            let index_dir = cache_dir(0).join(format!("index{index}"));
+            fs::create_dir_all(&index_dir)?;
+            fs::write(index_dir.join("level"), format!("{level}\n"))?;
+            fs::write(index_dir.join("type"), format!("{type_}\n"))?;
+        }
+
+        let mut tree = topo.tree().clone();
+        Topology::add_cache_kinds(&mut tree, |os_index, level| {
+            cache_kind_in(&cache_dir(os_index), level)
+        });
+        fs::remove_dir_all(&sysfs)?;
+        let topo = Topology::new(tree);
+        let kind_of = |path: &str| match topo
+            .resolve_path(path)
+            .and_then(|id| topo.tree().get_by_id(&id))
+        {
+            Some(Element::Cache { kind, .. }) => Some(*kind),
+            _ => None,
+        };
+        assert_eq!(kind_of("package:0/l2:0/l1d:0"), Some(CacheKind::Data));
+        assert_eq!(kind_of("package:0/l2:0"), Some(CacheKind::Unified));
+        assert_eq!(kind_of("package:0/l2:1/l1:1"), Some(CacheKind::Unified));
+        Ok(())
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_add_machine_info() -> anyhow::Result<()> {
+        use crate::MachineInfo;
+
+        let topo = Topology::from_json(NUMA_DISTANCES_JSON)?;
+        let mut tree = topo.tree().clone();
+        Topology::add_machine_info(&mut tree, |name| match name {
+            "hostname" => Some("termi5".to_owned()),
+            "ostype" => Some("Linux".to_owned()),
+            _ => None,
+        });
+        let stamped = Topology::new(tree);
+        assert_eq!(
+            stamped.machine_info(),
+            Some(&MachineInfo {
+                hostname: Some("termi5".to_owned()),
+                architecture: Some(std::env::consts::ARCH.to_owned()),
+                total_memory: 4 * 1024,
+                os_name: Some("Linux".to_owned()),
+                os_release: None,
+                hwloc_version: None,
+            })
+        );
+        assert!(stamped.semantically_equal(&topo));
+        Ok(())
+    }
 }
@@ -0,0 +1,90 @@
+use immutree::NodeId;
+
+use crate::{CacheKind, Element, ProcessingElement, Topology};
+
+impl Topology {
+    /// Returns the [`NodeId`] of the element that the given textual path refers to (e.g.,
+    /// `package:0/numa:1/l3:2/core:5`), as produced by [`Topology::path_of`], or `None` if it does
+    /// not refer to any.
+    ///
+    /// A path consists of one `/`-separated segment per element on the way from the root
+    /// [`Machine`] (which is referred to by the empty path) down to the referred element, each of
+    /// which is one of:
+    ///
+    /// - `package:<P#>`, `die:<P#>`, `numa:<P#>`, `core:<P#>` or `thread:<P#>`, for processing
+    ///   elements, by their OS index;
+    /// - `l<level>:<L#>` for unified caches, or `l<level>d:<L#>` and `l<level>i:<L#>` for data
+    ///   and instruction caches, respectively, by their logical index (e.g., `l3:0`, `l1d:4`);
+    /// - `io:<name>`, for I/O devices, by their name (e.g., `io:eth0`);
+    /// - `<kind>`, for elements of unknown kinds (see [`Element::Unknown`]), which cannot be told
+    ///   apart from their siblings of the same kind.
+    ///
+    /// Leading and trailing `/`s are ignored.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Machine`]: crate::Element::Machine
+    pub fn resolve_path(&self, path: &str) -> Option<NodeId> {
+        let mut id = self.tree.root_id()?;
+        for segment in path.trim_matches('/').split('/').filter(|s| !s.is_empty()) {
+            id = self
+                .tree
+                .child_ids_slice(&id)
+                .ok()?
+                .iter()
+                .copied()
+                .find(|child_id| {
+                    let child = self.tree.get_by_id(child_id).and_then(path_segment);
+                    matches!(child, Some(child) if child == segment)
+                })?;
+        }
+        Some(id)
+    }
+
+    /// Returns the textual path of the element stored under the given [`NodeId`] (e.g.,
+    /// `package:0/numa:1/l3:2/core:5`; see [`Topology::resolve_path`] for its format), or `None`
+    /// if it does not correspond to an element of the topology.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub fn path_of(&self, id: NodeId) -> Option<String> {
+        let elem = self.tree.get_by_id(&id)?;
+        let mut segments: Vec<_> = std::iter::once(elem)
+            .chain(self.tree.ancestors(&id))
+            .filter_map(path_segment)
+            .collect();
+        segments.reverse();
+        Some(segments.join("/"))
+    }
+}
+
+/// Returns the path segment that refers to the given [`Element`] among its siblings, or `None`
+/// for the [`Machine`], which is the root of all paths.
+///
+/// [`Machine`]: crate::Element::Machine
+fn path_segment(elem: &Element) -> Option<String> {
+    let segment = match elem {
+        Element::Machine { .. } => return None,
+        Element::Processing { element, .. } => match element {
+            ProcessingElement::Package(os_index) => format!("package:{os_index}"),
+            ProcessingElement::Die(os_index) => format!("die:{os_index}"),
+            ProcessingElement::NumaNode(os_index) => format!("numa:{os_index}"),
+            ProcessingElement::Core(os_index) => format!("core:{os_index}"),
+            ProcessingElement::Thread(os_index) => format!("thread:{os_index}"),
+        },
+        Element::Cache {
+            level,
+            kind,
+            logical_index,
+            ..
+        } => {
+            let suffix = match kind {
+                CacheKind::Data => "d",
+                CacheKind::Instruction => "i",
+                CacheKind::Unified => "",
+            };
+            format!("l{}{suffix}:{logical_index}", *level as u8 + 1)
+        }
+        Element::IoDevice { name, .. } => format!("io:{name}"),
+        Element::Unknown { kind, .. } => kind.clone(),
+    };
+    Some(segment)
+}
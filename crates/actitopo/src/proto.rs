@@ -0,0 +1,566 @@
+//! Protobuf bindings for [`Topology`], mirroring the schema in `proto/actitopo.proto`, along with
+//! conversions from and to it.
+//!
+//! The messages are written by hand (rather than generated by `prost-build`), so that building
+//! this crate does not require `protoc`; they must be kept in sync with the schema.
+//!
+//! [`Topology`]: crate::Topology
+
+use immutree::{InsertMode, NodeId, Tree};
+
+use crate::{
+    CacheAttributes as CrateCacheAttributes, CacheKind as CrateCacheKind,
+    CacheLevel as CrateCacheLevel, CoreKind as CrateCoreKind, CpuFrequency as CrateCpuFrequency,
+    Element as CrateElement, Error, IoDeviceKind as CrateIoDeviceKind,
+    MachineInfo as CrateMachineInfo, MemoryTier as CrateMemoryTier,
+    NumaAttributes as CrateNumaAttributes, PageType as CratePageType, ProcessingElement,
+    Topology as CrateTopology,
+};
+
+/// A hardware topology, as a list of nodes in pre-order (i.e., each node appears after its
+/// parent, and siblings appear in order).
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Topology {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: Vec<Node>,
+}
+
+/// A node of the topology.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Node {
+    #[prost(message, optional, tag = "1")]
+    pub element: Option<Element>,
+    /// The position of the parent node in [`Topology::nodes`]; unset only for the root node.
+    #[prost(uint32, optional, tag = "2")]
+    pub parent: Option<u32>,
+}
+
+/// A topology element (see [`crate::Element`]).
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Element {
+    #[prost(oneof = "element::Kind", tags = "1, 2, 3, 4, 6")]
+    pub kind: Option<element::Kind>,
+    /// The hardware threads of the element, as a cpulist (e.g., `0-5,12-17`).
+    #[prost(string, tag = "5")]
+    pub cpuset: String,
+}
+
+/// Nested types of [`Element`].
+pub mod element {
+    /// The kind of an [`Element`](super::Element).
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        Machine(super::Machine),
+        #[prost(message, tag = "2")]
+        Processing(super::Processing),
+        #[prost(message, tag = "3")]
+        Cache(super::Cache),
+        #[prost(message, tag = "4")]
+        IoDevice(super::IoDevice),
+        #[prost(message, tag = "6")]
+        Unknown(super::Unknown),
+    }
+}
+
+/// See [`crate::Element::Machine`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Machine {
+    /// Unset for topologies that have not been detected.
+    #[prost(message, optional, tag = "1")]
+    pub info: Option<MachineInfo>,
+}
+
+/// See [`crate::MachineInfo`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MachineInfo {
+    #[prost(string, optional, tag = "1")]
+    pub hostname: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub architecture: Option<String>,
+    #[prost(uint64, tag = "3")]
+    pub total_memory: u64,
+    #[prost(string, optional, tag = "4")]
+    pub os_name: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub os_release: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub hwloc_version: Option<String>,
+}
+
+/// See [`crate::Element::Processing`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Processing {
+    #[prost(enumeration = "ProcessingKind", tag = "1")]
+    pub kind: i32,
+    #[prost(uint32, tag = "2")]
+    pub os_index: u32,
+    /// Only for NUMA nodes.
+    #[prost(message, optional, tag = "3")]
+    pub memory: Option<NumaAttributes>,
+    /// Only for physical cores and hardware threads of hybrid CPUs.
+    #[prost(enumeration = "CoreKind", tag = "4")]
+    pub core_kind: i32,
+    /// Only for hardware threads.
+    #[prost(bool, tag = "5")]
+    pub offline: bool,
+    /// Only for physical cores and hardware threads.
+    #[prost(message, optional, tag = "6")]
+    pub frequency: Option<CpuFrequency>,
+}
+
+/// See [`crate::ProcessingElement`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ProcessingKind {
+    Unspecified = 0,
+    Package = 1,
+    Die = 2,
+    NumaNode = 3,
+    Core = 4,
+    Thread = 5,
+}
+
+/// See [`crate::CoreKind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CoreKind {
+    Unspecified = 0,
+    Performance = 1,
+    Efficiency = 2,
+}
+
+/// See [`crate::NumaAttributes`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NumaAttributes {
+    #[prost(uint64, tag = "1")]
+    pub local_memory: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub page_types: Vec<PageType>,
+    #[prost(enumeration = "MemoryTier", tag = "3")]
+    pub tier: i32,
+    #[prost(btree_map = "uint32, uint32", tag = "4")]
+    pub distances: std::collections::BTreeMap<u32, u32>,
+}
+
+/// See [`crate::MemoryTier`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MemoryTier {
+    Unspecified = 0,
+    Dram = 1,
+    Hbm = 2,
+    Cxl = 3,
+    Nvm = 4,
+    Other = 5,
+}
+
+/// See [`crate::CpuFrequency`].
+#[derive(Clone, Copy, PartialEq, Eq, ::prost::Message)]
+pub struct CpuFrequency {
+    #[prost(uint32, optional, tag = "1")]
+    pub base: Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    pub max: Option<u32>,
+}
+
+/// See [`crate::PageType`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PageType {
+    #[prost(uint64, tag = "1")]
+    pub size: u64,
+    #[prost(uint64, tag = "2")]
+    pub count: u64,
+}
+
+/// See [`crate::Element::Cache`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Cache {
+    #[prost(enumeration = "CacheLevel", tag = "1")]
+    pub level: i32,
+    #[prost(uint32, tag = "2")]
+    pub logical_index: u32,
+    #[prost(message, optional, tag = "3")]
+    pub attributes: Option<CacheAttributes>,
+    #[prost(enumeration = "CacheKind", tag = "4")]
+    pub kind: i32,
+}
+
+/// See [`crate::CacheKind`]; `Unspecified` is decoded as [`crate::CacheKind::Unified`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CacheKind {
+    Unspecified = 0,
+    Data = 1,
+    Instruction = 2,
+    Unified = 3,
+}
+
+/// See [`crate::CacheLevel`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CacheLevel {
+    Unspecified = 0,
+    L1 = 1,
+    L2 = 2,
+    L3 = 3,
+    L4 = 4,
+    L5 = 5,
+}
+
+/// See [`crate::CacheAttributes`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CacheAttributes {
+    #[prost(uint64, tag = "1")]
+    pub size: u64,
+    #[prost(uint32, tag = "2")]
+    pub linesize: u32,
+    #[prost(int32, tag = "3")]
+    pub associativity: i32,
+}
+
+/// See [`crate::Element::IoDevice`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IoDevice {
+    #[prost(enumeration = "IoDeviceKind", tag = "1")]
+    pub kind: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+/// See [`crate::Element::Unknown`].
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Unknown {
+    #[prost(string, tag = "1")]
+    pub kind: String,
+    /// The rest of the element, as JSON.
+    #[prost(string, tag = "2")]
+    pub data: String,
+}
+
+/// See [`crate::IoDeviceKind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum IoDeviceKind {
+    Unspecified = 0,
+    Block = 1,
+    Gpu = 2,
+    Network = 3,
+    OpenFabrics = 4,
+    Dma = 5,
+    CoProcessor = 6,
+    Other = 7,
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    Conversions
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+impl From<&CrateTopology> for Topology {
+    fn from(topology: &CrateTopology) -> Self {
+        fn push_preorder(
+            tree: &Tree<CrateElement>,
+            id: &NodeId,
+            parent: Option<u32>,
+            nodes: &mut Vec<Node>,
+        ) {
+            let position = nodes.len() as u32;
+            nodes.push(Node {
+                element: tree.get_by_id(id).map(Element::from),
+                parent,
+            });
+            for child_id in tree
+                .immediate_descendant_ids(id)
+                .expect("NodeId out of bounds")
+            {
+                push_preorder(tree, &child_id, Some(position), nodes);
+            }
+        }
+
+        let tree = topology.tree();
+        let mut nodes = Vec::with_capacity(tree.len());
+        if let Some(root_id) = tree.root_id() {
+            push_preorder(tree, &root_id, None, &mut nodes);
+        }
+        Self { nodes }
+    }
+}
+
+impl TryFrom<Topology> for CrateTopology {
+    type Error = Error;
+
+    /// # Errors
+    ///
+    /// An [`Error::Decoding`] is returned if the nodes are not in pre-order (e.g., a node refers to
+    /// a parent that appears after it), or if any of the elements is invalid. An
+    /// [`Error::InvalidRoot`] is returned if the root element is not the [`Machine`].
+    ///
+    /// [`Machine`]: CrateElement::Machine
+    fn try_from(topology: Topology) -> Result<Self, Self::Error> {
+        let mut nodes = topology.nodes.into_iter();
+        let root = nodes.next().ok_or(Error::EmptyTopology).and_then(|node| {
+            node.element
+                .ok_or(Error::Decoding("missing root element".into()))
+        })?;
+        let (mut tree, root_id) = Tree::with_root(CrateElement::try_from(root)?);
+        let mut ids = vec![root_id];
+        for (position, node) in nodes.enumerate() {
+            let parent_id = node
+                .parent
+                .and_then(|parent| ids.get(parent as usize))
+                .ok_or_else(|| {
+                    Error::Decoding(format!("invalid parent of node {}", position + 1))
+                })?;
+            let element = node.element.ok_or_else(|| {
+                Error::Decoding(format!("missing element of node {}", position + 1))
+            })?;
+            ids.push(tree.insert(
+                CrateElement::try_from(element)?,
+                InsertMode::Under(parent_id),
+            )?);
+        }
+        CrateTopology::validated(tree)
+    }
+}
+
+impl From<&CrateElement> for Element {
+    fn from(element: &CrateElement) -> Self {
+        let kind = match element {
+            CrateElement::Machine { info, .. } => element::Kind::Machine(Machine {
+                info: info.as_ref().map(|info| MachineInfo {
+                    hostname: info.hostname.clone(),
+                    architecture: info.architecture.clone(),
+                    total_memory: info.total_memory,
+                    os_name: info.os_name.clone(),
+                    os_release: info.os_release.clone(),
+                    hwloc_version: info.hwloc_version.clone(),
+                }),
+            }),
+            CrateElement::Processing {
+                element,
+                memory,
+                core_kind,
+                frequency,
+                offline,
+                ..
+            } => {
+                let (kind, os_index) = match *element {
+                    ProcessingElement::Package(i) => (ProcessingKind::Package, i),
+                    ProcessingElement::Die(i) => (ProcessingKind::Die, i),
+                    ProcessingElement::NumaNode(i) => (ProcessingKind::NumaNode, i),
+                    ProcessingElement::Core(i) => (ProcessingKind::Core, i),
+                    ProcessingElement::Thread(i) => (ProcessingKind::Thread, i),
+                };
+                element::Kind::Processing(Processing {
+                    kind: kind as i32,
+                    os_index,
+                    memory: memory.as_ref().map(|memory| NumaAttributes {
+                        local_memory: memory.local_memory(),
+                        page_types: memory
+                            .page_types()
+                            .iter()
+                            .map(|pt| PageType {
+                                size: pt.size(),
+                                count: pt.count(),
+                            })
+                            .collect(),
+                        tier: match memory.tier() {
+                            None => MemoryTier::Unspecified,
+                            Some(CrateMemoryTier::Dram) => MemoryTier::Dram,
+                            Some(CrateMemoryTier::Hbm) => MemoryTier::Hbm,
+                            Some(CrateMemoryTier::Cxl) => MemoryTier::Cxl,
+                            Some(CrateMemoryTier::Nvm) => MemoryTier::Nvm,
+                            Some(CrateMemoryTier::Other) => MemoryTier::Other,
+                        } as i32,
+                        distances: memory.distances().clone(),
+                    }),
+                    core_kind: match core_kind {
+                        None => CoreKind::Unspecified,
+                        Some(CrateCoreKind::Performance) => CoreKind::Performance,
+                        Some(CrateCoreKind::Efficiency) => CoreKind::Efficiency,
+                    } as i32,
+                    offline: *offline,
+                    frequency: frequency.map(|frequency| CpuFrequency {
+                        base: frequency.base,
+                        max: frequency.max,
+                    }),
+                })
+            }
+            CrateElement::Cache {
+                level,
+                kind,
+                logical_index,
+                attributes,
+                ..
+            } => element::Kind::Cache(Cache {
+                level: match level {
+                    CrateCacheLevel::L1 => CacheLevel::L1,
+                    CrateCacheLevel::L2 => CacheLevel::L2,
+                    CrateCacheLevel::L3 => CacheLevel::L3,
+                    CrateCacheLevel::L4 => CacheLevel::L4,
+                    CrateCacheLevel::L5 => CacheLevel::L5,
+                } as i32,
+                logical_index: *logical_index,
+                attributes: Some(CacheAttributes {
+                    size: attributes.size(),
+                    linesize: attributes.line(),
+                    associativity: attributes.associativity(),
+                }),
+                kind: match kind {
+                    CrateCacheKind::Data => CacheKind::Data,
+                    CrateCacheKind::Instruction => CacheKind::Instruction,
+                    CrateCacheKind::Unified => CacheKind::Unified,
+                } as i32,
+            }),
+            CrateElement::IoDevice { kind, name, .. } => element::Kind::IoDevice(IoDevice {
+                kind: match kind {
+                    CrateIoDeviceKind::Block => IoDeviceKind::Block,
+                    CrateIoDeviceKind::Gpu => IoDeviceKind::Gpu,
+                    CrateIoDeviceKind::Network => IoDeviceKind::Network,
+                    CrateIoDeviceKind::OpenFabrics => IoDeviceKind::OpenFabrics,
+                    CrateIoDeviceKind::Dma => IoDeviceKind::Dma,
+                    CrateIoDeviceKind::CoProcessor => IoDeviceKind::CoProcessor,
+                    CrateIoDeviceKind::Other => IoDeviceKind::Other,
+                } as i32,
+                name: name.clone(),
+            }),
+            CrateElement::Unknown { kind, data, .. } => element::Kind::Unknown(Unknown {
+                kind: kind.clone(),
+                data: data.clone(),
+            }),
+        };
+        Self {
+            kind: Some(kind),
+            cpuset: element.cpuset().to_string(),
+        }
+    }
+}
+
+impl TryFrom<Element> for CrateElement {
+    type Error = Error;
+
+    /// # Errors
+    ///
+    /// An [`Error::Decoding`] is returned if the kind of the element is missing or unknown, or if
+    /// its cpuset is not a valid cpulist.
+    fn try_from(element: Element) -> Result<Self, Self::Error> {
+        let invalid = |what: &str, value: i32| Error::Decoding(format!("invalid {what}: {value}"));
+        let mut elem = match element
+            .kind
+            .ok_or(Error::Decoding("missing element kind".into()))?
+        {
+            element::Kind::Machine(machine) => CrateElement::Machine {
+                cpuset: Default::default(),
+                info: machine.info.map(|info| CrateMachineInfo {
+                    hostname: info.hostname,
+                    architecture: info.architecture,
+                    total_memory: info.total_memory,
+                    os_name: info.os_name,
+                    os_release: info.os_release,
+                    hwloc_version: info.hwloc_version,
+                }),
+            },
+            element::Kind::Processing(processing) => {
+                let os_index = processing.os_index;
+                let pe = match ProcessingKind::from_i32(processing.kind) {
+                    Some(ProcessingKind::Package) => ProcessingElement::Package(os_index),
+                    Some(ProcessingKind::Die) => ProcessingElement::Die(os_index),
+                    Some(ProcessingKind::NumaNode) => ProcessingElement::NumaNode(os_index),
+                    Some(ProcessingKind::Core) => ProcessingElement::Core(os_index),
+                    Some(ProcessingKind::Thread) => ProcessingElement::Thread(os_index),
+                    _ => return Err(invalid("processing element kind", processing.kind)),
+                };
+                let mut elem = CrateElement::from(pe);
+                if let CrateElement::Processing {
+                    memory,
+                    core_kind,
+                    frequency,
+                    offline,
+                    ..
+                } = &mut elem
+                {
+                    *memory = match processing.memory {
+                        Some(memory) => {
+                            let tier = match MemoryTier::from_i32(memory.tier) {
+                                Some(MemoryTier::Unspecified) => None,
+                                Some(MemoryTier::Dram) => Some(CrateMemoryTier::Dram),
+                                Some(MemoryTier::Hbm) => Some(CrateMemoryTier::Hbm),
+                                Some(MemoryTier::Cxl) => Some(CrateMemoryTier::Cxl),
+                                Some(MemoryTier::Nvm) => Some(CrateMemoryTier::Nvm),
+                                Some(MemoryTier::Other) => Some(CrateMemoryTier::Other),
+                                None => return Err(invalid("memory tier", memory.tier)),
+                            };
+                            let page_types = memory
+                                .page_types
+                                .iter()
+                                .map(|pt| CratePageType::new(pt.size, pt.count))
+                                .collect();
+                            Some(
+                                CrateNumaAttributes::new(memory.local_memory, page_types)
+                                    .with_tier(tier)
+                                    .with_distances(memory.distances),
+                            )
+                        }
+                        None => None,
+                    };
+                    *core_kind = match CoreKind::from_i32(processing.core_kind) {
+                        Some(CoreKind::Unspecified) => None,
+                        Some(CoreKind::Performance) => Some(CrateCoreKind::Performance),
+                        Some(CoreKind::Efficiency) => Some(CrateCoreKind::Efficiency),
+                        None => return Err(invalid("core kind", processing.core_kind)),
+                    };
+                    *frequency = processing.frequency.map(|frequency| CrateCpuFrequency {
+                        base: frequency.base,
+                        max: frequency.max,
+                    });
+                    *offline = processing.offline;
+                }
+                elem
+            }
+            element::Kind::Cache(cache) => CrateElement::Cache {
+                level: match CacheLevel::from_i32(cache.level) {
+                    Some(CacheLevel::L1) => CrateCacheLevel::L1,
+                    Some(CacheLevel::L2) => CrateCacheLevel::L2,
+                    Some(CacheLevel::L3) => CrateCacheLevel::L3,
+                    Some(CacheLevel::L4) => CrateCacheLevel::L4,
+                    Some(CacheLevel::L5) => CrateCacheLevel::L5,
+                    _ => return Err(invalid("cache level", cache.level)),
+                },
+                kind: match CacheKind::from_i32(cache.kind) {
+                    Some(CacheKind::Data) => CrateCacheKind::Data,
+                    Some(CacheKind::Instruction) => CrateCacheKind::Instruction,
+                    Some(CacheKind::Unified | CacheKind::Unspecified) => CrateCacheKind::Unified,
+                    None => return Err(invalid("cache kind", cache.kind)),
+                },
+                logical_index: cache.logical_index,
+                attributes: cache.attributes.map_or_else(Default::default, |attrs| {
+                    CrateCacheAttributes::new(attrs.size, attrs.linesize, attrs.associativity)
+                }),
+                cpuset: Default::default(),
+            },
+            element::Kind::IoDevice(io_device) => CrateElement::IoDevice {
+                kind: match IoDeviceKind::from_i32(io_device.kind) {
+                    Some(IoDeviceKind::Block) => CrateIoDeviceKind::Block,
+                    Some(IoDeviceKind::Gpu) => CrateIoDeviceKind::Gpu,
+                    Some(IoDeviceKind::Network) => CrateIoDeviceKind::Network,
+                    Some(IoDeviceKind::OpenFabrics) => CrateIoDeviceKind::OpenFabrics,
+                    Some(IoDeviceKind::Dma) => CrateIoDeviceKind::Dma,
+                    Some(IoDeviceKind::CoProcessor) => CrateIoDeviceKind::CoProcessor,
+                    Some(IoDeviceKind::Other) => CrateIoDeviceKind::Other,
+                    _ => return Err(invalid("I/O device kind", io_device.kind)),
+                },
+                name: io_device.name,
+                cpuset: Default::default(),
+            },
+            element::Kind::Unknown(unknown) => CrateElement::Unknown {
+                kind: unknown.kind,
+                cpuset: Default::default(),
+                data: unknown.data,
+            },
+        };
+        elem.set_cpuset(element.cpuset.parse()?);
+        Ok(elem)
+    }
+}
@@ -0,0 +1,109 @@
+use immutree::NodeId;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{ArrayValidation, InstanceType, Metadata, ObjectValidation, Schema, SchemaObject},
+    JsonSchema,
+};
+
+use crate::{CpuSet, Element, ProcessingElement, Topology};
+
+impl JsonSchema for CpuSet {
+    fn schema_name() -> String {
+        "CpuSet".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some("A cpulist string (e.g., `0-5,12-17`).".to_owned()),
+                ..Default::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl JsonSchema for ProcessingElement {
+    fn schema_name() -> String {
+        "ProcessingElement".to_owned()
+    }
+
+    /// Describes all kinds of [`ProcessingElement`]s through a single object, rather than through
+    /// one per kind (as a derived schema would), since the latter cannot be flattened into the
+    /// schemas of Kubernetes custom resources, which must be structural.
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut object = ObjectValidation::default();
+        object.required.insert("kind".to_owned());
+        object.required.insert("id".to_owned());
+        object.properties.insert(
+            "kind".to_owned(),
+            SchemaObject {
+                instance_type: Some(InstanceType::String.into()),
+                enum_values: Some(
+                    ["package", "die", "numanode", "core", "thread"]
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                ),
+                ..Default::default()
+            }
+            .into(),
+        );
+        object
+            .properties
+            .insert("id".to_owned(), gen.subschema_for::<u32>());
+        SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(object)),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl JsonSchema for Topology {
+    fn schema_name() -> String {
+        "Topology".to_owned()
+    }
+
+    /// Describes the serialized representation of the inner `Tree<Element>`; i.e., its nodes in
+    /// order of [`NodeId`], each of them along with the [`NodeId`]s of its children.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut node = ObjectValidation::default();
+        node.required.insert("data".to_owned());
+        node.properties
+            .insert("data".to_owned(), gen.subschema_for::<Element>());
+        node.properties
+            .insert("desc".to_owned(), gen.subschema_for::<Vec<NodeId>>());
+        let node = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(node)),
+            ..Default::default()
+        };
+
+        let mut tree = ObjectValidation::default();
+        tree.required.insert("nodes".to_owned());
+        tree.properties.insert(
+            "nodes".to_owned(),
+            SchemaObject {
+                instance_type: Some(InstanceType::Array.into()),
+                array: Some(Box::new(ArrayValidation {
+                    items: Some(Schema::from(node).into()),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }
+            .into(),
+        );
+        SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(tree)),
+            ..Default::default()
+        }
+        .into()
+    }
+}
@@ -0,0 +1,114 @@
+use proptest::{
+    arbitrary::Arbitrary,
+    collection::vec,
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use crate::{CacheLevel, ElementBuilder, Topology, TopologyBuilder};
+
+/// Bounds on the shape of the random [`Topology`]s that are generated through its [`Arbitrary`]
+/// implementation (e.g., `any_with::<Topology>(bounds)`), for property-based testing.
+///
+/// Each generated [`Topology`] consists of at least one of each kind of [`ProcessingElement`] per
+/// parent, up to the given maximum; e.g., each physical core has its own number of hardware
+/// threads (i.e., SMT may be asymmetric), and each NUMA node may or may not group its physical
+/// cores under a shared L3 cache.
+///
+/// [`Arbitrary`]: proptest::arbitrary::Arbitrary
+/// [`ProcessingElement`]: crate::ProcessingElement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopologyBounds {
+    /// The maximum number of [`Package`]s of the machine.
+    ///
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub max_packages: usize,
+
+    /// The maximum number of [`NumaNode`]s per [`Package`].
+    ///
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`Package`]: crate::ProcessingElement::Package
+    pub max_numa_nodes_per_package: usize,
+
+    /// The maximum number of [`Core`]s per [`NumaNode`].
+    ///
+    /// [`Core`]: crate::ProcessingElement::Core
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    pub max_cores_per_numa_node: usize,
+
+    /// The maximum number of [`Thread`]s per [`Core`].
+    ///
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    /// [`Core`]: crate::ProcessingElement::Core
+    pub max_threads_per_core: usize,
+}
+
+impl Default for TopologyBounds {
+    /// Up to 2 packages of up to 4 NUMA nodes each (i.e., up to 8 NUMA nodes in total), of up to
+    /// 8 physical cores each, of up to 2 hardware threads each.
+    fn default() -> Self {
+        Self {
+            max_packages: 2,
+            max_numa_nodes_per_package: 4,
+            max_cores_per_numa_node: 8,
+            max_threads_per_core: 2,
+        }
+    }
+}
+
+/// The shape of a NUMA node; i.e., whether its physical cores share an L3 cache, and the number
+/// of hardware threads of each one of them.
+type NumaShape = (bool, Vec<usize>);
+
+impl Arbitrary for Topology {
+    type Parameters = TopologyBounds;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(bounds: Self::Parameters) -> Self::Strategy {
+        let core = 1..=bounds.max_threads_per_core.max(1);
+        let numa_node = (
+            proptest::bool::ANY,
+            vec(core, 1..=bounds.max_cores_per_numa_node.max(1)),
+        );
+        let package = vec(numa_node, 1..=bounds.max_numa_nodes_per_package.max(1));
+        vec(package, 1..=bounds.max_packages.max(1))
+            .prop_map(|packages| build(&packages))
+            .boxed()
+    }
+}
+
+/// Builds a [`Topology`] of the given shape, assigning OS indices to its elements sequentially
+/// (those of [`Core`]s per [`Package`], as Linux does).
+///
+/// [`Core`]: crate::ProcessingElement::Core
+/// [`Package`]: crate::ProcessingElement::Package
+fn build(packages: &[Vec<NumaShape>]) -> Topology {
+    let (mut next_numa_node, mut next_thread) = (0, 0);
+    let mut builder = TopologyBuilder::machine();
+    for (os_index, numa_nodes) in packages.iter().enumerate() {
+        builder = builder.package(os_index as u32, |p| {
+            let mut next_core = 0;
+            for (shared_l3, cores) in numa_nodes {
+                p.numa(next_numa_node, |n| {
+                    let mut add_cores = |b: &mut ElementBuilder<'_>| {
+                        for threads in cores {
+                            b.core(next_core, |c| {
+                                for _ in 0..*threads {
+                                    c.thread(next_thread);
+                                    next_thread += 1;
+                                }
+                            });
+                            next_core += 1;
+                        }
+                    };
+                    if *shared_l3 {
+                        n.cache(CacheLevel::L3, add_cores);
+                    } else {
+                        add_cores(n);
+                    }
+                });
+                next_numa_node += 1;
+            }
+        });
+    }
+    builder.build()
+}
@@ -0,0 +1,144 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use immutree::NodeId;
+
+use crate::{CacheKind, CacheLevel, Element, IoDeviceKind, ProcessingElement, Topology};
+
+/// The mount point of the Linux kernel's sysfs.
+const SYSFS: &str = "/sys";
+
+/// The root of sysfs' hierarchy for CPUs, relative to its mount point.
+const SYSFS_CPU: &str = "devices/system/cpu";
+
+/// The root of sysfs' hierarchy for NUMA nodes, relative to its mount point.
+const SYSFS_NODE: &str = "devices/system/node";
+
+/// The root of sysfs' hierarchy for device classes, relative to its mount point.
+const SYSFS_CLASS: &str = "class";
+
+/// The device classes under sysfs' `class` directory (e.g., `/sys/class/net`) that correspond to
+/// the kinds of [`IoDevice`]s; co-processors and other kinds of devices have no such class.
+///
+/// [`IoDevice`]: crate::Element::IoDevice
+pub(crate) const IO_DEVICE_CLASSES: &[(IoDeviceKind, &str)] = &[
+    (IoDeviceKind::Block, "block"),
+    (IoDeviceKind::Gpu, "drm"),
+    (IoDeviceKind::Network, "net"),
+    (IoDeviceKind::OpenFabrics, "infiniband"),
+    (IoDeviceKind::Dma, "dma"),
+];
+
+impl Topology {
+    /// Returns the Linux sysfs path of the element stored under the given [`NodeId`] (e.g., for
+    /// cross-checking the state of the kernel), or `None` if it does not correspond to an element
+    /// of the topology, or if the element has no such path. In particular:
+    ///
+    /// - a [`Thread`] maps to its CPU directory (e.g., `/sys/devices/system/cpu/cpu7`);
+    /// - a [`NumaNode`] maps to its node directory (e.g., `/sys/devices/system/node/node1`);
+    /// - a [`Core`], [`Die`] or [`Package`] maps to the `topology` directory of its first hardware
+    ///   thread, which describes it through its `core_id`, `die_id` or `physical_package_id`,
+    ///   respectively (e.g., `/sys/devices/system/cpu/cpu2/topology`);
+    /// - a [`Cache`] maps to its directory under its first hardware thread (e.g.,
+    ///   `/sys/devices/system/cpu/cpu0/cache/index3`);
+    /// - an [`IoDevice`] maps to its directory under `/sys/class` (e.g., `/sys/class/net/eth0`),
+    ///   unless it is of [`IoDeviceKind::CoProcessor`] or [`IoDeviceKind::Other`] kind.
+    ///
+    /// The [`Machine`] and elements of unknown kinds have no sysfs path.
+    ///
+    /// # Note
+    ///
+    /// Since the kernel does not number the directories of caches consistently across
+    /// architectures (e.g., `index1` is the L1 instruction cache when L1 is split, but the L2
+    /// cache when it is unified), the directory of a [`Cache`] is looked up in sysfs, as the one
+    /// whose `level` and `type` files match the level and the [`CacheKind`] of the [`Cache`];
+    /// hence, caches only have a sysfs path on the machine that the topology was detected on.
+    /// Caches whose kind is unknown (i.e., deserialized as [`CacheKind::Unified`]) map to the data
+    /// cache of their level, if there is no unified one.
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    /// [`Thread`]: crate::ProcessingElement::Thread
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`Core`]: crate::ProcessingElement::Core
+    /// [`Die`]: crate::ProcessingElement::Die
+    /// [`Package`]: crate::ProcessingElement::Package
+    /// [`Cache`]: crate::Element::Cache
+    /// [`IoDevice`]: crate::Element::IoDevice
+    /// [`Machine`]: crate::Element::Machine
+    pub fn sysfs_path(&self, id: NodeId) -> Option<PathBuf> {
+        self.sysfs_path_under(id, Path::new(SYSFS))
+    }
+
+    /// Returns the sysfs path of the element stored under the given [`NodeId`], assuming sysfs is
+    /// mounted at the given `sysfs` path (see [`Topology::sysfs_path`]).
+    ///
+    /// [`NodeId`]: immutree::NodeId
+    pub(crate) fn sysfs_path_under(&self, id: NodeId, sysfs: &Path) -> Option<PathBuf> {
+        let elem = self.tree.get_by_id(&id)?;
+        let cpu_dir = |cpu: u32| sysfs.join(SYSFS_CPU).join(format!("cpu{cpu}"));
+        let first_cpu = || elem.cpuset().iter().next().map(cpu_dir);
+        match elem {
+            Element::Machine { .. } | Element::Unknown { .. } => None,
+            Element::Processing { element, .. } => match element {
+                ProcessingElement::Thread(os_index) => Some(cpu_dir(*os_index)),
+                ProcessingElement::NumaNode(os_index) => {
+                    Some(sysfs.join(SYSFS_NODE).join(format!("node{os_index}")))
+                }
+                ProcessingElement::Core(_)
+                | ProcessingElement::Die(_)
+                | ProcessingElement::Package(_) => Some(first_cpu()?.join("topology")),
+            },
+            Element::Cache { level, kind, .. } => {
+                cache_dir(&first_cpu()?.join("cache"), *level, *kind)
+            }
+            Element::IoDevice { kind, name, .. } => {
+                let (_, class) = IO_DEVICE_CLASSES.iter().find(|(k, _)| k == kind)?;
+                Some(sysfs.join(SYSFS_CLASS).join(class).join(name))
+            }
+        }
+    }
+}
+
+/// Returns the `index*` directories under the given `cache` directory of a CPU, in ascending
+/// order, along with the level and the type (e.g., `Data`) of the cache that each of them
+/// describes, as reported by their `level` and `type` files.
+pub(crate) fn cache_indices(cpu_cache_dir: &Path) -> Vec<(PathBuf, u8, String)> {
+    let read = |dir: &Path, name: &str| fs::read_to_string(dir.join(name)).ok();
+    let mut indices: Vec<_> = fs::read_dir(cpu_cache_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("index"))
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let level = read(&dir, "level")?.trim().parse().ok()?;
+            let type_ = read(&dir, "type")?.trim().to_owned();
+            Some((dir, level, type_))
+        })
+        .collect();
+    indices.sort();
+    indices
+}
+
+/// Returns the directory of the cache of the given level and kind among the `index*` ones under
+/// the given `cache` directory of a CPU (see [`cache_indices`]).
+///
+/// A [`CacheKind::Unified`] cache falls back to the data cache of its level, if there is no
+/// unified one (see [`Topology::sysfs_path`]).
+fn cache_dir(cpu_cache_dir: &Path, level: CacheLevel, kind: CacheKind) -> Option<PathBuf> {
+    let indices = cache_indices(cpu_cache_dir);
+    let level = level as u8 + 1;
+    let find = |type_: &str| {
+        indices
+            .iter()
+            .find(|(_, l, t)| *l == level && t == type_)
+            .map(|(dir, _, _)| dir.clone())
+    };
+    match kind {
+        CacheKind::Data => find("Data"),
+        CacheKind::Instruction => find("Instruction"),
+        CacheKind::Unified => find("Unified").or_else(|| find("Data")),
+    }
+}
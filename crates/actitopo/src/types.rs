@@ -1,8 +1,15 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
+#[cfg(feature = "detect")]
 use hwloc2::{object::Attributes, ObjectType};
-use serde::{Deserialize, Serialize};
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
+use crate::CpuSet;
+#[cfg(feature = "detect")]
 use crate::Error;
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
@@ -12,21 +19,72 @@ use crate::Error;
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
 /// Topology elements, as defined in terms of the Acti- node topology.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+///
+/// Each of them also carries its [`CpuSet`], i.e., the hardware threads that reside under it.
+///
+/// Elements of kinds that are unknown to this version of the crate (e.g., ones introduced by a
+/// newer version, in clusters of mixed versions) are deserialized as [`Element::Unknown`], and
+/// they are serialized back as they were.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(rename = "Element"))]
+#[serde(remote = "Self", rename_all = "lowercase")]
 pub enum Element {
     /// The root element of the topology, representing the whole machine.
-    Machine,
+    Machine {
+        /// The hardware threads of the machine.
+        #[serde(default, skip_serializing_if = "CpuSet::is_empty")]
+        cpuset: CpuSet,
+
+        /// Metadata about the machine (e.g., its hostname), captured at detection time; unset for
+        /// topologies that have not been detected (e.g., synthetic ones).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        info: Option<MachineInfo>,
+    },
 
     /// A computation unit (e.g., physical core, etc).
-    Processing(ProcessingElement),
+    Processing {
+        /// The kind and the physical index of the computation unit.
+        #[serde(flatten)]
+        element: ProcessingElement,
+
+        /// The hardware threads that reside under the computation unit (for NUMA nodes, the ones
+        /// that are local to it).
+        #[serde(default, skip_serializing_if = "CpuSet::is_empty")]
+        cpuset: CpuSet,
+
+        /// Attributes of the memory of the computation unit, detected by `libhwloc2-rs` (only for
+        /// NUMA nodes).
+        #[serde(rename = "mem", default, skip_serializing_if = "Option::is_none")]
+        memory: Option<NumaAttributes>,
 
-    /// A data caching element (e.g., L3 cache, etc).
+        /// The kind of the computation unit on hybrid CPUs, as reported by the Linux kernel in
+        /// `/sys/devices/` (only for physical cores and hardware threads).
+        #[serde(rename = "corekind", default, skip_serializing_if = "Option::is_none")]
+        core_kind: Option<CoreKind>,
+
+        /// The nominal frequencies of the computation unit, as reported by the Linux kernel in
+        /// `/sys/devices/system/cpu/` (only for physical cores and hardware threads).
+        #[serde(rename = "freq", default, skip_serializing_if = "Option::is_none")]
+        frequency: Option<CpuFrequency>,
+
+        /// Whether the computation unit is offline, hence it must not be pinned to (only for
+        /// hardware threads).
+        #[serde(default, skip_serializing_if = "is_false")]
+        offline: bool,
+    },
+
+    /// A caching element (e.g., L3 cache, etc).
     Cache {
         /// The level of the cache.
         #[serde(rename = "lvl")]
         level: CacheLevel,
 
+        /// The kind of the cache (i.e., data, instruction or unified); caches that were
+        /// serialized before it was introduced are deserialized as unified.
+        #[serde(default, skip_serializing_if = "CacheKind::is_unified")]
+        kind: CacheKind,
+
         /// The logical index of the cache, assigned by `libhwloc2-rs`.
         #[serde(rename = "li")]
         logical_index: u32,
@@ -34,59 +92,295 @@ pub enum Element {
         /// Attributes of the cache, detected by `libhwloc2-rs`.
         #[serde(rename = "attrs")]
         attributes: CacheAttributes,
+
+        /// The hardware threads that share the cache.
+        #[serde(default, skip_serializing_if = "CpuSet::is_empty")]
+        cpuset: CpuSet,
     },
+
+    /// An I/O device (e.g., network interface, NVMe drive, GPU, etc), i.e., an OS device in terms
+    /// of `libhwloc2-rs`.
+    IoDevice {
+        /// The kind of the I/O device.
+        kind: IoDeviceKind,
+
+        /// The name of the I/O device, as assigned by the operating system (e.g., `eth0`).
+        name: String,
+
+        /// The hardware threads that are local to the I/O device.
+        #[serde(default, skip_serializing_if = "CpuSet::is_empty")]
+        cpuset: CpuSet,
+    },
+
+    /// An element of a kind that is unknown to this version of the crate, preserved as it was
+    /// serialized.
+    #[serde(skip)]
+    Unknown {
+        /// The kind of the element, as serialized (e.g., `accelerator`).
+        kind: String,
+
+        /// The hardware threads of the element, if it was serialized with any.
+        cpuset: CpuSet,
+
+        /// The rest of the element, as JSON.
+        data: String,
+    },
+}
+
+/// The kinds of the [`Element`]s that this version of the crate knows of, as serialized.
+const KNOWN_ELEMENT_KINDS: [&str; 4] = ["machine", "processing", "cache", "iodevice"];
+
+impl Serialize for Element {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Element::Unknown { kind, cpuset, data } => {
+                // The CpuSet may have been modified since deserialization (e.g., by a restriction)
+                let mut data: serde_json::Value =
+                    serde_json::from_str(data).map_err(ser::Error::custom)?;
+                if let Some(fields) = data.as_object_mut() {
+                    fields.remove("cpuset");
+                    if !cpuset.is_empty() {
+                        fields.insert("cpuset".to_owned(), cpuset.to_string().into());
+                    }
+                }
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(kind, &data)?;
+                map.end()
+            }
+            _ => Element::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        // The Machine was serialized as a unit variant before it carried any fields
+        if value.as_str() == Some("machine") {
+            return Ok(Element::Machine {
+                cpuset: CpuSet::new(),
+                info: None,
+            });
+        }
+        let unknown_kind = match &value {
+            serde_json::Value::Object(fields) if fields.len() == 1 => fields
+                .keys()
+                .find(|kind| !KNOWN_ELEMENT_KINDS.contains(&kind.as_str()))
+                .cloned(),
+            _ => None,
+        };
+        match unknown_kind {
+            Some(kind) => {
+                let data = &value[&kind];
+                let cpuset = match data.get("cpuset").and_then(serde_json::Value::as_str) {
+                    Some(cpuset) => cpuset.parse().map_err(de::Error::custom)?,
+                    None => CpuSet::new(),
+                };
+                Ok(Element::Unknown {
+                    kind,
+                    cpuset,
+                    data: data.to_string(),
+                })
+            }
+            None => Element::deserialize(value).map_err(de::Error::custom),
+        }
+    }
+}
+
+impl Element {
+    /// Returns the [`ProcessingElement`], if this is a computation unit.
+    pub fn processing(&self) -> Option<&ProcessingElement> {
+        match self {
+            Element::Processing { element, .. } => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Returns the attributes of the memory of the element, if it is a NUMA node and they have
+    /// been detected.
+    pub fn memory(&self) -> Option<&NumaAttributes> {
+        match self {
+            Element::Processing { memory, .. } => memory.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`MemoryTier`] of the element, if it is a NUMA node that has been tagged with one.
+    pub fn memory_tier(&self) -> Option<MemoryTier> {
+        self.memory().and_then(NumaAttributes::tier)
+    }
+
+    /// Returns the [`CoreKind`] of the element, if it is a physical core or a hardware thread of a
+    /// hybrid CPU.
+    pub fn core_kind(&self) -> Option<CoreKind> {
+        match self {
+            Element::Processing { core_kind, .. } => *core_kind,
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "detect"), allow(dead_code))]
+    pub(crate) fn set_core_kind(&mut self, new_core_kind: Option<CoreKind>) {
+        if let Element::Processing { core_kind, .. } = self {
+            *core_kind = new_core_kind;
+        }
+    }
+
+    /// Returns the [`CpuFrequency`] of the element, if it is a physical core or a hardware thread
+    /// and its nominal frequencies have been detected.
+    pub fn frequency(&self) -> Option<CpuFrequency> {
+        match self {
+            Element::Processing { frequency, .. } => *frequency,
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "detect"), allow(dead_code))]
+    pub(crate) fn set_frequency(&mut self, new_frequency: Option<CpuFrequency>) {
+        if let Element::Processing { frequency, .. } = self {
+            *frequency = new_frequency;
+        }
+    }
+
+    /// Returns the [`MachineInfo`] of the element, if it is the machine and it has been detected.
+    pub fn machine_info(&self) -> Option<&MachineInfo> {
+        match self {
+            Element::Machine { info, .. } => info.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_machine_info(&mut self, new_info: Option<MachineInfo>) {
+        if let Element::Machine { info, .. } = self {
+            *info = new_info;
+        }
+    }
+
+    /// Returns `true` if the element is an offline hardware thread.
+    pub fn is_offline(&self) -> bool {
+        matches!(self, Element::Processing { offline: true, .. })
+    }
+
+    #[cfg_attr(not(feature = "detect"), allow(dead_code))]
+    pub(crate) fn set_offline(&mut self, new_offline: bool) {
+        if let Element::Processing { offline, .. } = self {
+            *offline = new_offline;
+        }
+    }
+
+    /// Returns the [`CpuSet`] of the element, i.e., the OS indices of the hardware threads that
+    /// reside under it.
+    pub fn cpuset(&self) -> &CpuSet {
+        match self {
+            Element::Machine { cpuset, .. }
+            | Element::Processing { cpuset, .. }
+            | Element::Cache { cpuset, .. }
+            | Element::IoDevice { cpuset, .. }
+            | Element::Unknown { cpuset, .. } => cpuset,
+        }
+    }
+
+    pub(crate) fn set_cpuset(&mut self, new_cpuset: CpuSet) {
+        match self {
+            Element::Machine { cpuset, .. }
+            | Element::Processing { cpuset, .. }
+            | Element::Cache { cpuset, .. }
+            | Element::IoDevice { cpuset, .. }
+            | Element::Unknown { cpuset, .. } => *cpuset = new_cpuset,
+        }
+    }
+}
+
+impl From<ProcessingElement> for Element {
+    /// Creates a computation unit [`Element`] that carries no information other than its kind and
+    /// its physical index.
+    fn from(element: ProcessingElement) -> Self {
+        Element::Processing {
+            element,
+            cpuset: CpuSet::new(),
+            memory: None,
+            core_kind: None,
+            frequency: None,
+            offline: false,
+        }
+    }
 }
 
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+/// Returns the OS indices of the hardware threads (i.e., `ObjectType::PU`s) under the given
+/// `hwloc2::Object` (including itself).
+#[cfg(feature = "detect")]
+pub(crate) fn pu_cpuset(obj: &hwloc2::Object) -> CpuSet {
+    match obj.object_type() {
+        ObjectType::PU => [obj.os_index()].into_iter().collect(),
+        _ => obj
+            .children()
+            .iter()
+            .fold(CpuSet::new(), |mut cpuset, child| {
+                cpuset.union_with(&pu_cpuset(child));
+                cpuset
+            }),
+    }
+}
+
+#[cfg(feature = "detect")]
 impl TryFrom<&hwloc2::Object<'_>> for Element {
     type Error = Error;
 
     fn try_from(obj: &hwloc2::Object) -> Result<Self, Self::Error> {
+        let processing = |element| Element::Processing {
+            element,
+            cpuset: pu_cpuset(obj),
+            memory: None,
+            core_kind: None,
+            frequency: None,
+            offline: false,
+        };
+        let cache = |level| Element::Cache {
+            level,
+            // NOTE: The kind of a cache is filled by the caller
+            kind: CacheKind::Unified,
+            logical_index: obj.logical_index(),
+            attributes: obj.attributes().try_into().unwrap_or_default(),
+            cpuset: pu_cpuset(obj),
+        };
+
         match obj.object_type() {
             //
             // Root
             //
-            ObjectType::Machine => Ok(Element::Machine),
+            ObjectType::Machine => Ok(Element::Machine {
+                cpuset: pu_cpuset(obj),
+                // NOTE: The metadata of the machine are filled by the caller
+                info: None,
+            }),
             //
             // Processing elements
             //
-            ObjectType::Package => Ok(Element::Processing(ProcessingElement::Package(
-                obj.os_index(),
-            ))),
-            ObjectType::NumaNode => Ok(Element::Processing(ProcessingElement::NumaNode(
-                obj.os_index(),
-            ))),
-            ObjectType::Core => Ok(Element::Processing(ProcessingElement::Core(obj.os_index()))),
-            ObjectType::PU => Ok(Element::Processing(ProcessingElement::Thread(
-                obj.os_index(),
-            ))),
+            ObjectType::Package => Ok(processing(ProcessingElement::Package(obj.os_index()))),
+            ObjectType::Die => Ok(processing(ProcessingElement::Die(obj.os_index()))),
+            // NOTE: NUMA nodes are memory children, hence their CpuSet is filled by the caller
+            ObjectType::NumaNode => Ok(Element::Processing {
+                element: ProcessingElement::NumaNode(obj.os_index()),
+                cpuset: CpuSet::new(),
+                memory: None,
+                core_kind: None,
+                frequency: None,
+                offline: false,
+            }),
+            ObjectType::Core => Ok(processing(ProcessingElement::Core(obj.os_index()))),
+            ObjectType::PU => Ok(processing(ProcessingElement::Thread(obj.os_index()))),
             //
             // Caches
             //
-            ObjectType::L1Cache => Ok(Element::Cache {
-                level: CacheLevel::L1,
-                logical_index: obj.logical_index(),
-                attributes: obj.attributes().try_into().unwrap_or_default(),
-            }),
-            ObjectType::L2Cache => Ok(Element::Cache {
-                level: CacheLevel::L2,
-                logical_index: obj.logical_index(),
-                attributes: obj.attributes().try_into().unwrap_or_default(),
-            }),
-            ObjectType::L3Cache => Ok(Element::Cache {
-                level: CacheLevel::L3,
-                logical_index: obj.logical_index(),
-                attributes: obj.attributes().try_into().unwrap_or_default(),
-            }),
-            ObjectType::L4Cache => Ok(Element::Cache {
-                level: CacheLevel::L4,
-                logical_index: obj.logical_index(),
-                attributes: obj.attributes().try_into().unwrap_or_default(),
-            }),
-            ObjectType::L5Cache => Ok(Element::Cache {
-                level: CacheLevel::L5,
-                logical_index: obj.logical_index(),
-                attributes: obj.attributes().try_into().unwrap_or_default(),
-            }),
+            ObjectType::L1Cache => Ok(cache(CacheLevel::L1)),
+            ObjectType::L2Cache => Ok(cache(CacheLevel::L2)),
+            ObjectType::L3Cache => Ok(cache(CacheLevel::L3)),
+            ObjectType::L4Cache => Ok(cache(CacheLevel::L4)),
+            ObjectType::L5Cache => Ok(cache(CacheLevel::L5)),
             //
             // No equivalent element in Acti-topology
             //
@@ -99,17 +393,68 @@ impl fmt::Display for Element {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Element::*;
         match self {
-            Machine => write!(f, "Machine"),
-            Processing(pe) => write!(f, "{pe}"),
+            Machine { .. } => write!(f, "Machine"),
+            Processing { element, .. } => write!(f, "{element}"),
             Cache {
                 level,
+                kind,
                 logical_index,
                 attributes,
-            } => write!(f, "{level} Cache L#{logical_index} ({attributes})"),
+                ..
+            } => {
+                let suffix = match kind {
+                    CacheKind::Data => "d",
+                    CacheKind::Instruction => "i",
+                    CacheKind::Unified => "",
+                };
+                write!(f, "{level}{suffix} Cache L#{logical_index} ({attributes})")
+            }
+            IoDevice { kind, name, .. } => write!(f, "{kind} I/O device {name:?}"),
+            Unknown { kind, .. } => write!(f, "Unknown element {kind:?}"),
         }
     }
 }
 
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    MachineInfo
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Metadata about the machine that a [`Topology`] has been detected on, as reported by the Linux
+/// kernel in `/proc/sys/kernel/`, which allows telling apart the topologies of different nodes.
+///
+/// [`Topology`]: crate::Topology
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MachineInfo {
+    /// The hostname of the machine.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    /// The architecture of the machine (e.g., `x86_64`), as targeted by the detecting binary.
+    #[serde(rename = "arch", default, skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<String>,
+
+    /// The total memory of the machine (i.e., of all of its detected NUMA nodes), in bytes.
+    #[serde(rename = "mem", default)]
+    pub total_memory: u64,
+
+    /// The name of the operating system (e.g., `Linux`).
+    #[serde(rename = "os", default, skip_serializing_if = "Option::is_none")]
+    pub os_name: Option<String>,
+
+    /// The release of the operating system (e.g., the Linux kernel's release).
+    #[serde(rename = "osrelease", default, skip_serializing_if = "Option::is_none")]
+    pub os_release: Option<String>,
+
+    /// The version of `libhwloc` that the topology was detected with, if known; it is not
+    /// reported by the revision of `libhwloc2-rs` that this crate is built with, hence it is only
+    /// set on topologies that were detected otherwise.
+    #[serde(rename = "hwloc", default, skip_serializing_if = "Option::is_none")]
+    pub hwloc_version: Option<String>,
+}
+
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 ////
 ////    ProcessingElement
@@ -121,12 +466,16 @@ impl fmt::Display for Element {
 ///
 /// Each of them also carries its physical index, as assigned by the operating system and retrieved
 /// by `libhwloc2-rs`.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase", tag = "kind", content = "id")]
 pub enum ProcessingElement {
     /// Physical package (i.e., what goes into a physical socket).
     Package(u32),
 
+    /// Die (i.e., one of possibly multiple dies within a physical package, e.g., on AMD EPYC or
+    /// Intel Sapphire Rapids processors).
+    Die(u32),
+
     /// NUMA node (i.e., a set of processors around memory which all processors can directly access
     /// via the same physical link).
     NumaNode(u32),
@@ -144,6 +493,7 @@ impl fmt::Display for ProcessingElement {
         use ProcessingElement::*;
         match self {
             Package(id) => write!(f, "Package P#{id}"),
+            Die(id) => write!(f, "Die P#{id}"),
             NumaNode(id) => write!(f, "NUMA node P#{id}"),
             Core(id) => write!(f, "Physical Core P#{id}"),
             Thread(id) => write!(f, "Hardware Thread P#{id}"),
@@ -151,6 +501,140 @@ impl fmt::Display for ProcessingElement {
     }
 }
 
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    CoreKind
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The kind of a physical core (or hardware thread) on hybrid CPUs (e.g., Intel Alder Lake and
+/// newer), as reported by the Linux kernel through the PMUs of each kind of cores (i.e.,
+/// `/sys/devices/cpu_core` and `/sys/devices/cpu_atom`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CoreKind {
+    /// The most powerful kind of cores (e.g., Intel's P-cores).
+    Performance,
+    /// Any less powerful, but more energy-efficient, kind of cores (e.g., Intel's E-cores).
+    Efficiency,
+}
+
+impl fmt::Display for CoreKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoreKind::Performance => write!(f, "P-core"),
+            CoreKind::Efficiency => write!(f, "E-core"),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    CpuFrequency
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The nominal frequencies of a physical core (or hardware thread), in MHz, as reported by the
+/// Linux kernel's `cpufreq` subsystem.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CpuFrequency {
+    /// The base (i.e., guaranteed) frequency, if reported (e.g., by `intel_pstate`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<u32>,
+
+    /// The maximum (e.g., turbo) frequency, if reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<u32>,
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    IoDeviceKind
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The kind of an I/O device, as reported by the Linux kernel through its device class (e.g.,
+/// `/sys/class/net` for network interfaces).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum IoDeviceKind {
+    /// Block device (e.g., NVMe drive).
+    Block,
+    /// GPU (e.g., as exposed through DRM).
+    Gpu,
+    /// Network interface (e.g., Ethernet NIC).
+    Network,
+    /// OpenFabrics interface (e.g., InfiniBand HCA).
+    OpenFabrics,
+    /// DMA engine.
+    Dma,
+    /// Co-processor (e.g., as exposed through CUDA or OpenCL).
+    CoProcessor,
+    /// Any other (or unknown) kind of I/O device.
+    Other,
+}
+
+impl fmt::Display for IoDeviceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IoDeviceKind::*;
+        match self {
+            Block => write!(f, "Block"),
+            Gpu => write!(f, "GPU"),
+            Network => write!(f, "Network"),
+            OpenFabrics => write!(f, "OpenFabrics"),
+            Dma => write!(f, "DMA"),
+            CoProcessor => write!(f, "Co-Processor"),
+            Other => write!(f, "Other"),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    MemoryTier
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The tier (i.e., kind) of the memory of a NUMA node.
+///
+/// It is not detected by [`Topology::detect`], since neither `libhwloc2-rs` nor sysfs report it
+/// reliably; it is only carried by topologies whose NUMA nodes have been tagged explicitly (see
+/// [`NumaAttributes::with_tier`]).
+///
+/// [`Topology::detect`]: crate::Topology::detect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryTier {
+    /// Conventional, directly attached DRAM.
+    Dram,
+    /// High Bandwidth Memory (e.g., HBM, or Intel Xeon Phi's MCDRAM).
+    Hbm,
+    /// Memory attached through Compute Express Link (e.g., CXL-DRAM).
+    Cxl,
+    /// Non-volatile memory (e.g., Intel Optane DCPMM).
+    Nvm,
+    /// Any other kind of memory (e.g., specific-purpose or GPU memory).
+    Other,
+}
+
+impl fmt::Display for MemoryTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryTier::Dram => write!(f, "DRAM"),
+            MemoryTier::Hbm => write!(f, "HBM"),
+            MemoryTier::Cxl => write!(f, "CXL"),
+            MemoryTier::Nvm => write!(f, "NVM"),
+            MemoryTier::Other => write!(f, "other memory"),
+        }
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 ////
 ////    CacheLevel
@@ -159,6 +643,7 @@ impl fmt::Display for ProcessingElement {
 
 /// The cache level (e.g., L1, L2, etc).
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CacheLevel {
     /// L1 cache.
     L1,
@@ -185,6 +670,49 @@ impl fmt::Display for CacheLevel {
     }
 }
 
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    CacheKind
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The kind of a cache, i.e., the type of the contents that it caches, as reported by the Linux
+/// kernel in the `type` file of its directory under `/sys/devices/system/cpu/cpu<N>/cache/`.
+///
+/// Note that detected topologies include no instruction caches, since `libhwloc2-rs` does not
+/// report them.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    /// Data-only cache (e.g., L1d).
+    Data,
+    /// Instruction-only cache (e.g., L1i).
+    Instruction,
+    /// Cache for both data and instructions (e.g., most L2 and L3 caches).
+    #[default]
+    Unified,
+}
+
+impl CacheKind {
+    /// Returns `true` if this is a [`CacheKind::Unified`] cache.
+    pub fn is_unified(&self) -> bool {
+        *self == CacheKind::Unified
+    }
+}
+
+impl fmt::Display for CacheKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheKind::Data => write!(f, "data"),
+            CacheKind::Instruction => write!(f, "instruction"),
+            CacheKind::Unified => write!(f, "unified"),
+        }
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 ////
 ////    CacheAttributes
@@ -193,6 +721,7 @@ impl fmt::Display for CacheLevel {
 
 /// Attributes of a cache, as detected by `libhwloc2-rs`.
 #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CacheAttributes {
     #[serde(rename = "size")]
     size: u64,
@@ -203,6 +732,16 @@ pub struct CacheAttributes {
 }
 
 impl CacheAttributes {
+    /// Creates new [`CacheAttributes`], given the total size of the cache and the size of its
+    /// line (both in bytes), as well as its associativity (in # ways).
+    pub fn new(size: u64, linesize: u32, associativity: i32) -> Self {
+        Self {
+            size,
+            linesize,
+            associativity,
+        }
+    }
+
     /// Returns the total size of the cache, in bytes.
     pub fn size(&self) -> u64 {
         self.size
@@ -219,6 +758,7 @@ impl CacheAttributes {
     }
 }
 
+#[cfg(feature = "detect")]
 impl TryFrom<Option<Attributes<'_>>> for CacheAttributes {
     type Error = Error;
 
@@ -243,3 +783,110 @@ impl fmt::Display for CacheAttributes {
         )
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////    NumaAttributes
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Attributes of the memory of a NUMA node, as reported by the Linux kernel in
+/// `/sys/devices/system/node/`.
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NumaAttributes {
+    #[serde(rename = "size")]
+    local_memory: u64,
+    #[serde(rename = "pages", default, skip_serializing_if = "Vec::is_empty")]
+    page_types: Vec<PageType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tier: Option<MemoryTier>,
+    #[serde(rename = "dist", default, skip_serializing_if = "BTreeMap::is_empty")]
+    distances: BTreeMap<u32, u32>,
+}
+
+impl NumaAttributes {
+    /// Creates new [`NumaAttributes`], given the size of the memory that is local to the NUMA node
+    /// (in bytes) and the types of its memory pages.
+    pub fn new(local_memory: u64, page_types: Vec<PageType>) -> Self {
+        Self {
+            local_memory,
+            page_types,
+            tier: None,
+            distances: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the [`MemoryTier`] of the NUMA node.
+    pub fn with_tier(mut self, tier: Option<MemoryTier>) -> Self {
+        self.tier = tier;
+        self
+    }
+
+    /// Sets the distances from the NUMA node to (all) NUMA nodes, by their OS indices.
+    pub fn with_distances(mut self, distances: BTreeMap<u32, u32>) -> Self {
+        self.distances = distances;
+        self
+    }
+
+    /// Returns the size of the memory that is local to the NUMA node, in bytes.
+    pub fn local_memory(&self) -> u64 {
+        self.local_memory
+    }
+
+    /// Returns the types of the (huge) memory pages of the NUMA node.
+    pub fn page_types(&self) -> &[PageType] {
+        &self.page_types
+    }
+
+    /// Returns the [`MemoryTier`] of the NUMA node, if it has been tagged with one (see
+    /// [`NumaAttributes::with_tier`]).
+    pub fn tier(&self) -> Option<MemoryTier> {
+        self.tier
+    }
+
+    /// Returns the relative distances (i.e., memory access costs) from the NUMA node to (all)
+    /// NUMA nodes, by their OS indices, as reported by the firmware (e.g., the ACPI SLIT, where
+    /// `10` is the distance of a NUMA node to itself), if they have been detected.
+    pub fn distances(&self) -> &BTreeMap<u32, u32> {
+        &self.distances
+    }
+
+    /// Returns the relative distance from the NUMA node to the NUMA node with the given OS index,
+    /// if it has been detected.
+    pub fn distance_to(&self, os_index: u32) -> Option<u32> {
+        self.distances.get(&os_index).copied()
+    }
+}
+
+impl fmt::Display for NumaAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}B", self.local_memory)
+    }
+}
+
+/// A type of (huge) memory pages of a NUMA node, as reported by the Linux kernel in
+/// `/sys/devices/system/node/node<N>/hugepages/`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PageType {
+    size: u64,
+    count: u64,
+}
+
+impl PageType {
+    /// Creates a new [`PageType`], given the size of its pages (in bytes) and their number.
+    pub fn new(size: u64, count: u64) -> Self {
+        Self { size, count }
+    }
+
+    /// Returns the size of the pages of this type, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the number of the pages of this type.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use serde::{
+    de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{Error, Topology};
+
+/// The version of the schema that [`Topology`] is currently (de)serialized with.
+///
+/// It must be bumped whenever the serialized layout of [`Topology`] (e.g., of [`Element`]) changes
+/// in a way that older consumers cannot handle.
+///
+/// [`Element`]: crate::Element
+pub const SCHEMA_VERSION: u32 = 1;
+
+const FIELDS: &[&str] = &["version", "topology"];
+
+/// A [`Topology`] that is (de)serialized wrapped in an envelope, along with the version of the
+/// schema that it is serialized with (i.e., as `{"version":1,"topology":...}`).
+///
+/// It should be preferred over a bare [`Topology`] whenever the serialized topology is persisted
+/// for long, or consumed by different versions of this crate.
+///
+/// # Errors
+///
+/// Deserialization fails with a clear error (see [`Error::UnsupportedSchemaVersion`]) when the
+/// envelope carries a schema version that this version of the crate cannot handle, instead of
+/// attempting to deserialize a [`Topology`] of an unknown layout. Unknown fields of the envelope
+/// are ignored, for forward compatibility.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionedTopology(pub Topology);
+
+impl VersionedTopology {
+    /// Returns the wrapped [`Topology`].
+    pub fn into_inner(self) -> Topology {
+        self.0
+    }
+
+    fn check_version<E: de::Error>(version: u32) -> Result<(), E> {
+        match version {
+            SCHEMA_VERSION => Ok(()),
+            _ => Err(de::Error::custom(Error::UnsupportedSchemaVersion(version))),
+        }
+    }
+}
+
+impl From<Topology> for VersionedTopology {
+    fn from(topology: Topology) -> Self {
+        Self(topology)
+    }
+}
+
+impl Serialize for VersionedTopology {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VersionedTopologyRef(&self.0).serialize(serializer)
+    }
+}
+
+/// A borrowed [`Topology`] that is serialized exactly like a [`VersionedTopology`].
+pub(crate) struct VersionedTopologyRef<'topo>(pub(crate) &'topo Topology);
+
+impl Serialize for VersionedTopologyRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("VersionedTopology", FIELDS.len())?;
+        state.serialize_field("version", &SCHEMA_VERSION)?;
+        state.serialize_field("topology", self.0)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedTopology {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("VersionedTopology", FIELDS, VersionedTopologyVisitor)
+    }
+}
+
+struct VersionedTopologyVisitor;
+
+impl<'de> Visitor<'de> for VersionedTopologyVisitor {
+    type Value = VersionedTopology;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a versioned actitopo::Topology")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        VersionedTopology::check_version(version)?;
+        let topology = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(VersionedTopology(topology))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        // NOTE: The envelope's fields may appear in any order (e.g., after being sorted by some
+        // intermediate); a topology that precedes the version is buffered until the version has
+        // been checked, so that an unsupported version is reported as such, instead of as a
+        // failure to deserialize a topology of an unknown layout.
+        let (mut version, mut topology, mut buffered) = (None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "version" => {
+                    if version.is_some() {
+                        return Err(de::Error::duplicate_field("version"));
+                    }
+                    let v = map.next_value()?;
+                    VersionedTopology::check_version(v)?;
+                    version = Some(v);
+                }
+                "topology" => {
+                    if topology.is_some() || buffered.is_some() {
+                        return Err(de::Error::duplicate_field("topology"));
+                    }
+                    if version.is_some() {
+                        topology = Some(map.next_value()?);
+                    } else {
+                        buffered = Some(map.next_value::<serde_json::Value>()?);
+                    }
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        version.ok_or_else(|| de::Error::missing_field("version"))?;
+        let topology = match (topology, buffered) {
+            (Some(topology), _) => topology,
+            (None, Some(buffered)) => Topology::deserialize(buffered).map_err(de::Error::custom)?,
+            (None, None) => return Err(de::Error::missing_field("topology")),
+        };
+        Ok(VersionedTopology(topology))
+    }
+}
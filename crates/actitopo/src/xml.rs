@@ -0,0 +1,326 @@
+use std::fmt::Write;
+
+use immutree::NodeId;
+
+use crate::{CacheKind, CpuSet, Element, MemoryTier, ProcessingElement, Topology};
+
+impl Topology {
+    /// Exports the topology in the XML format of `libhwloc` (v2), so that it can be re-opened
+    /// with the standard hwloc tools (e.g., `lstopo --input topo.xml`) for visualization and
+    /// debugging.
+    ///
+    /// # Note
+    ///
+    /// The export is lossy, since an Acti-[`Topology`] does not retain everything `libhwloc`
+    /// reports:
+    ///
+    /// - Each [`NumaNode`] that has children is exported as a `Group`, which holds both the
+    ///   NUMA node itself (as a memory child) and the exported children, since NUMA nodes are
+    ///   leaves in `libhwloc`'s hierarchy. Childless NUMA nodes that share their hardware threads
+    ///   with such a sibling are attached to the same `Group`.
+    /// - A single NUMA node is made up for topologies without any (e.g., ones detected with
+    ///   [`DetectionMode::CoresOnly`]), since `libhwloc` requires at least one.
+    /// - I/O devices are not exported, since their PCI hierarchy is not retained; neither are
+    ///   elements of unknown kinds (see [`Element::Unknown`]).
+    ///
+    /// [`NumaNode`]: crate::ProcessingElement::NumaNode
+    /// [`DetectionMode::CoresOnly`]: crate::DetectionMode::CoresOnly
+    pub fn to_hwloc_xml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE topology SYSTEM \"hwloc2.dtd\">\n",
+            "<topology version=\"2.0\">\n",
+        ));
+        if let Some(root_id) = self.tree.root_id() {
+            XmlExporter::new(self).export(&mut xml, root_id, 1);
+        }
+        xml.push_str("</topology>\n");
+        xml
+    }
+}
+
+/// Exports the elements of a [`Topology`] as `libhwloc` XML objects (see
+/// [`Topology::to_hwloc_xml`]).
+struct XmlExporter<'topo> {
+    topo: &'topo Topology,
+    /// The OS indices and the hardware threads of all NUMA nodes, to compute nodesets with.
+    numa_nodes: Vec<(u32, &'topo CpuSet)>,
+    /// The next global persistent index to assign to an object.
+    next_gp_index: u64,
+}
+
+impl<'topo> XmlExporter<'topo> {
+    fn new(topo: &'topo Topology) -> Self {
+        let numa_nodes = topo
+            .numa_node_ids()
+            .filter_map(|id| topo.tree.get_by_id(&id))
+            .filter_map(|elem| match elem.processing() {
+                Some(ProcessingElement::NumaNode(os_index)) => Some((*os_index, elem.cpuset())),
+                _ => None,
+            })
+            .collect();
+        Self {
+            topo,
+            numa_nodes,
+            next_gp_index: 1,
+        }
+    }
+
+    /// Exports the element stored under the given [`NodeId`], along with its descendants.
+    fn export(&mut self, xml: &mut String, id: NodeId, depth: usize) {
+        let elem = self.topo.tree.get_by_id(&id).expect("NodeId out of bounds");
+        match elem {
+            Element::Machine { info, .. } => {
+                self.open(xml, depth, "Machine", Some(0), elem.cpuset(), "");
+                if let Some(info) = info {
+                    let infos = [
+                        ("HostName", &info.hostname),
+                        ("Architecture", &info.architecture),
+                        ("OSName", &info.os_name),
+                        ("OSRelease", &info.os_release),
+                        ("hwlocVersion", &info.hwloc_version),
+                    ];
+                    for (name, value) in infos {
+                        if let Some(value) = value {
+                            indent(xml, depth + 1);
+                            let value = escape(value);
+                            let _ = writeln!(xml, "<info name=\"{name}\" value=\"{value}\"/>");
+                        }
+                    }
+                }
+                if self.numa_nodes.is_empty() {
+                    let local_memory = info.as_ref().map_or(0, |info| info.total_memory);
+                    let attrs = format!(" local_memory=\"{local_memory}\"");
+                    self.open(xml, depth + 1, "NUMANode", Some(0), elem.cpuset(), &attrs);
+                    close(xml, depth + 1);
+                }
+                self.export_children(xml, id, depth + 1);
+                close(xml, depth);
+            }
+            Element::Processing {
+                element: ProcessingElement::NumaNode(_),
+                ..
+            } => {
+                if self.is_childless_numa_node(id) {
+                    self.export_numa_node(xml, elem, depth);
+                } else {
+                    self.open(xml, depth, "Group", None, elem.cpuset(), "");
+                    self.export_numa_node(xml, elem, depth + 1);
+                    for sibling in self.childless_numa_siblings(id) {
+                        self.export_numa_node(xml, sibling, depth + 1);
+                    }
+                    self.export_children(xml, id, depth + 1);
+                    close(xml, depth);
+                }
+            }
+            Element::Processing { element, .. } => {
+                let (object_type, os_index) = match element {
+                    ProcessingElement::Package(os_index) => ("Package", os_index),
+                    ProcessingElement::Die(os_index) => ("Die", os_index),
+                    ProcessingElement::Core(os_index) => ("Core", os_index),
+                    ProcessingElement::Thread(os_index) => ("PU", os_index),
+                    ProcessingElement::NumaNode(_) => unreachable!("NUMA node exported above"),
+                };
+                self.open(xml, depth, object_type, Some(*os_index), elem.cpuset(), "");
+                self.export_children(xml, id, depth + 1);
+                close(xml, depth);
+            }
+            Element::Cache {
+                level,
+                kind,
+                attributes,
+                ..
+            } => {
+                let level = *level as usize + 1;
+                let (object_type, cache_type) = match kind {
+                    CacheKind::Unified => (format!("L{level}Cache"), 0),
+                    CacheKind::Data => (format!("L{level}Cache"), 1),
+                    CacheKind::Instruction => (format!("L{level}iCache"), 2),
+                };
+                let attrs = format!(
+                    " cache_size=\"{}\" depth=\"{level}\" cache_linesize=\"{}\" \
+                     cache_associativity=\"{}\" cache_type=\"{cache_type}\"",
+                    attributes.size(),
+                    attributes.line(),
+                    attributes.associativity(),
+                );
+                self.open(xml, depth, &object_type, None, elem.cpuset(), &attrs);
+                self.export_children(xml, id, depth + 1);
+                close(xml, depth);
+            }
+            Element::IoDevice { .. } | Element::Unknown { .. } => (),
+        }
+    }
+
+    /// Exports the children of the element stored under the given [`NodeId`], except for the
+    /// childless NUMA nodes that are exported along with a sibling NUMA node.
+    fn export_children(&mut self, xml: &mut String, id: NodeId, depth: usize) {
+        let child_ids = self.topo.tree.child_ids_slice(&id).unwrap_or_default();
+        for &child_id in child_ids {
+            let grouped = self.is_childless_numa_node(child_id)
+                && child_ids.iter().any(|&sibling_id| {
+                    sibling_id != child_id
+                        && self.is_numa_node(sibling_id)
+                        && !self.is_childless_numa_node(sibling_id)
+                        && self.cpuset_of(sibling_id) == self.cpuset_of(child_id)
+                });
+            if !grouped {
+                self.export(xml, child_id, depth);
+            }
+        }
+    }
+
+    /// Returns the childless NUMA nodes that are siblings of the given one and share its hardware
+    /// threads.
+    fn childless_numa_siblings(&self, id: NodeId) -> Vec<&'topo Element> {
+        let tree = &self.topo.tree;
+        let sibling_ids = tree
+            .parent_id(&id)
+            .and_then(|parent_id| tree.child_ids_slice(&parent_id).ok())
+            .unwrap_or_default();
+        sibling_ids
+            .iter()
+            .filter(|&&sibling_id| {
+                sibling_id != id
+                    && self.is_childless_numa_node(sibling_id)
+                    && self.cpuset_of(sibling_id) == self.cpuset_of(id)
+            })
+            .filter_map(|sibling_id| tree.get_by_id(sibling_id))
+            .collect()
+    }
+
+    fn export_numa_node(&mut self, xml: &mut String, elem: &Element, depth: usize) {
+        let os_index = match elem.processing() {
+            Some(ProcessingElement::NumaNode(os_index)) => *os_index,
+            _ => return,
+        };
+        let memory = elem.memory();
+        let local_memory = memory.map_or(0, |memory| memory.local_memory());
+        let mut attrs = format!(" local_memory=\"{local_memory}\"");
+        let subtype = match elem.memory_tier() {
+            Some(MemoryTier::Dram) => Some("DRAM"),
+            Some(MemoryTier::Hbm) => Some("HBM"),
+            Some(MemoryTier::Cxl) => Some("CXL-DRAM"),
+            Some(MemoryTier::Nvm) => Some("NVM"),
+            Some(MemoryTier::Other) | None => None,
+        };
+        if let Some(subtype) = subtype {
+            let _ = write!(attrs, " subtype=\"{subtype}\"");
+        }
+        self.open(
+            xml,
+            depth,
+            "NUMANode",
+            Some(os_index),
+            elem.cpuset(),
+            &attrs,
+        );
+        for page_type in memory.map(|memory| memory.page_types()).unwrap_or_default() {
+            indent(xml, depth + 1);
+            let _ = writeln!(
+                xml,
+                "<page_type size=\"{}\" count=\"{}\"/>",
+                page_type.size(),
+                page_type.count()
+            );
+        }
+        close(xml, depth);
+    }
+
+    /// Writes the opening tag of an object, with the given type, OS index, cpuset and (already
+    /// formatted) extra attributes; the nodeset of the object is computed out of its cpuset.
+    fn open(
+        &mut self,
+        xml: &mut String,
+        depth: usize,
+        object_type: &str,
+        os_index: Option<u32>,
+        cpuset: &CpuSet,
+        attrs: &str,
+    ) {
+        let nodeset: CpuSet = if self.numa_nodes.is_empty() {
+            std::iter::once(0).collect()
+        } else {
+            self.numa_nodes
+                .iter()
+                .filter(|(_, numa_cpuset)| numa_cpuset.iter().any(|cpu| cpuset.contains(cpu)))
+                .map(|(os_index, _)| *os_index)
+                .collect()
+        };
+        let (cpuset, nodeset) = (hex_bitmap(cpuset), hex_bitmap(&nodeset));
+        indent(xml, depth);
+        let _ = write!(xml, "<object type=\"{object_type}\"");
+        if let Some(os_index) = os_index {
+            let _ = write!(xml, " os_index=\"{os_index}\"");
+        }
+        let _ = writeln!(
+            xml,
+            " cpuset=\"{cpuset}\" complete_cpuset=\"{cpuset}\" nodeset=\"{nodeset}\" \
+             complete_nodeset=\"{nodeset}\" gp_index=\"{}\"{attrs}>",
+            self.next_gp_index
+        );
+        self.next_gp_index += 1;
+    }
+
+    fn is_numa_node(&self, id: NodeId) -> bool {
+        matches!(
+            self.topo.tree.get_by_id(&id).and_then(Element::processing),
+            Some(ProcessingElement::NumaNode(_))
+        )
+    }
+
+    fn is_childless_numa_node(&self, id: NodeId) -> bool {
+        self.is_numa_node(id)
+            && matches!(self.topo.tree.child_ids_slice(&id), Ok(child_ids) if child_ids.is_empty())
+    }
+
+    fn cpuset_of(&self, id: NodeId) -> Option<&'topo CpuSet> {
+        self.topo.tree.get_by_id(&id).map(Element::cpuset)
+    }
+}
+
+fn close(xml: &mut String, depth: usize) {
+    indent(xml, depth);
+    xml.push_str("</object>\n");
+}
+
+fn indent(xml: &mut String, depth: usize) {
+    for _ in 0..depth {
+        xml.push_str("  ");
+    }
+}
+
+/// Formats a [`CpuSet`] as a `libhwloc` bitmap; i.e., as comma-separated 32-bit hexadecimal
+/// words, most significant first (e.g., `0x00000001,0xffffffff`).
+fn hex_bitmap(set: &CpuSet) -> String {
+    let mut words = vec![0u32];
+    for cpu in set.iter() {
+        let word = cpu as usize / 32;
+        if word >= words.len() {
+            words.resize(word + 1, 0);
+        }
+        words[word] |= 1 << (cpu % 32);
+    }
+    words
+        .iter()
+        .rev()
+        .map(|word| format!("0x{word:08x}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Escapes the characters that are not allowed in XML attribute values.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,6 +1,6 @@
 use std::io::{self, StdoutLock, Write};
 
-use acticrds::ActiNode;
+use acticrds::{ActiNode, ActiPlacement, ActiPolicy, ActiReservation};
 use anyhow::{Context, Result};
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::CustomResourceExt;
@@ -18,6 +18,12 @@ fn main() -> Result<()> {
 
     print_crd_yaml(&mut stdout, &ActiNode::crd())
         .with_context(|| "failed to process the CRD for ActiNode")?;
+    print_crd_yaml(&mut stdout, &ActiPolicy::crd())
+        .with_context(|| "failed to process the CRD for ActiPolicy")?;
+    print_crd_yaml(&mut stdout, &ActiReservation::crd())
+        .with_context(|| "failed to process the CRD for ActiReservation")?;
+    print_crd_yaml(&mut stdout, &ActiPlacement::crd())
+        .with_context(|| "failed to process the CRD for ActiPlacement")?;
 
     Ok(())
 }
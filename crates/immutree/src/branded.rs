@@ -0,0 +1,105 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{Error, NodeId, Tree};
+
+/// The source of the brands assigned to [`Tree`]s; each [`Tree`] gets the next value.
+static NEXT_BRAND: AtomicUsize = AtomicUsize::new(0);
+
+/// An opaque value, unique to each [`Tree`] in the process, which is carried by the
+/// [`BrandedNodeId`]s issued by that [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Brand(usize);
+
+impl Brand {
+    /// Returns a new [`Brand`], which has not been assigned to any other [`Tree`] before.
+    pub(crate) fn new() -> Self {
+        Self(NEXT_BRAND.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A [`NodeId`] which also carries the brand of the [`Tree`] that issued it.
+///
+/// Using a [`BrandedNodeId`] against any [`Tree`] other than the one that issued it results in
+/// [`Error::ForeignNodeId`], rather than silently referring to the wrong element.
+///
+/// Note that each [`Tree`] gets a new brand when it is constructed, cloned or deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BrandedNodeId {
+    id: NodeId,
+    brand: Brand,
+}
+
+impl BrandedNodeId {
+    /// Returns the plain [`NodeId`], stripped of its brand.
+    #[inline]
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+}
+
+impl<T> Tree<T> {
+    /// Returns a [`BrandedNodeId`] for the element stored in the [`Tree`] under the provided
+    /// [`NodeId`], if it exists; `None` otherwise.
+    pub fn branded_id(&self, id: &NodeId) -> Option<BrandedNodeId> {
+        self.contains_id(id).then_some(BrandedNodeId {
+            id: *id,
+            brand: self.brand,
+        })
+    }
+
+    /// Returns the plain [`NodeId`] of the provided [`BrandedNodeId`], after making sure that it
+    /// has been issued by this [`Tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ForeignNodeId`] if the provided [`BrandedNodeId`] has been issued by
+    /// another [`Tree`].
+    pub fn unbrand(&self, id: &BrandedNodeId) -> Result<NodeId, Error> {
+        if id.brand != self.brand {
+            return Err(Error::ForeignNodeId(id.id));
+        }
+        Ok(id.id)
+    }
+
+    /// Returns a reference to the element stored in the [`Tree`] under the provided
+    /// [`BrandedNodeId`].
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::ForeignNodeId`] if the provided [`BrandedNodeId`] has been issued by
+    /// another [`Tree`].
+    /// - Returns [`Error::InvalidNodeId`] if the provided [`BrandedNodeId`] does not correspond to
+    /// an element currently stored in the [`Tree`].
+    pub fn get_by_branded_id(&self, id: &BrandedNodeId) -> Result<&T, Error> {
+        let id = self.unbrand(id)?;
+        self.get_by_id(&id).ok_or(Error::InvalidNodeId(id))
+    }
+
+    /// Returns a mutable reference to the element stored in the [`Tree`] under the provided
+    /// [`BrandedNodeId`].
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::ForeignNodeId`] if the provided [`BrandedNodeId`] has been issued by
+    /// another [`Tree`].
+    /// - Returns [`Error::InvalidNodeId`] if the provided [`BrandedNodeId`] does not correspond to
+    /// an element currently stored in the [`Tree`].
+    pub fn get_mut_by_branded_id(&mut self, id: &BrandedNodeId) -> Result<&mut T, Error> {
+        let id = self.unbrand(id)?;
+        self.get_mut_by_id(&id).ok_or(Error::InvalidNodeId(id))
+    }
+}
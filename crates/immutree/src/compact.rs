@@ -0,0 +1,203 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A space-efficient serialization layout for [`Tree`]s.
+//!
+//! Instead of storing a vector of children [`NodeId`]s along with each element, the compact layout
+//! consists of two flat arrays:
+//!
+//! - `parents`, where the `i`-th entry is the [`NodeId`] of the parent of the element stored under
+//! [`NodeId`] `i + 1` (the root element, stored under [`NodeId`] `0`, has no parent);
+//! - `data`, where the `i`-th entry is the element stored under [`NodeId`] `i`.
+//!
+//! By default, the children of each element are restored in ascending [`NodeId`] order, which is
+//! also the order in which they have been inserted.
+//! If that is not the case (e.g., because they have been sorted through
+//! [`Tree::sort_children_by`], or inserted through [`InsertMode::UnderAt`]), a third array is also
+//! serialized:
+//!
+//! - `ranks`, where the `i`-th entry is the position of the element stored under [`NodeId`]
+//! `i + 1` among its siblings.
+//!
+//! The layout can be selected either by wrapping the [`Tree`] in a [`Compact`], or through serde's
+//! `with` attribute:
+//!
+//! ```
+//! use immutree::Tree;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Wrapper {
+//!     #[serde(with = "immutree::compact")]
+//!     tree: Tree<u32>,
+//! }
+//! ```
+//!
+//! [`InsertMode::UnderAt`]: super::InsertMode::UnderAt
+
+use alloc::{vec, vec::Vec};
+
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Error, NodeId, Tree, TreeNode};
+
+/// A wrapper around a [`Tree`], which is serialized and deserialized in the [compact] layout.
+///
+/// [compact]: self
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Compact<T>(pub Tree<T>);
+
+impl<T> From<Tree<T>> for Compact<T> {
+    fn from(tree: Tree<T>) -> Self {
+        Self(tree)
+    }
+}
+
+impl<T> From<Compact<T>> for Tree<T> {
+    fn from(compact: Compact<T>) -> Self {
+        compact.0
+    }
+}
+
+impl<T: Serialize> Serialize for Compact<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Compact<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Self)
+    }
+}
+
+/// Serialize the provided [`Tree`] in the [compact] layout.
+///
+/// [compact]: self
+pub fn serialize<T, S>(tree: &Tree<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let parents = tree
+        .nodes
+        .iter()
+        .skip(1)
+        .map(|tn| {
+            tn.parent
+                .expect("all elements but the root must have a parent")
+        })
+        .collect::<Vec<_>>();
+
+    let ranks = sibling_ranks(tree);
+    let mut state = serializer.serialize_struct("Tree", 2 + ranks.is_some() as usize)?;
+    state.serialize_field("parents", &parents)?;
+    state.serialize_field("data", &Data(&tree.nodes))?;
+    match ranks {
+        Some(ranks) => state.serialize_field("ranks", &ranks)?,
+        None => state.skip_field("ranks")?,
+    }
+    state.end()
+}
+
+/// Deserialize a [`Tree`] from the [compact] layout, validating its structure.
+///
+/// [compact]: self
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Tree<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let repr = CompactRepr::<T>::deserialize(deserializer)?;
+    if repr.parents.len() + 1 != repr.data.len().max(1) {
+        return Err(de::Error::invalid_length(
+            repr.parents.len(),
+            &"one parent NodeId per non-root element",
+        ));
+    }
+
+    if let Some(ranks) = &repr.ranks {
+        if ranks.len() != repr.parents.len() {
+            return Err(de::Error::invalid_length(
+                ranks.len(),
+                &"one rank per non-root element",
+            ));
+        }
+    }
+
+    let mut nodes: Vec<TreeNode<T>> = repr.data.into_iter().map(TreeNode::from).collect();
+    for (child_id, parent_id) in (1..).zip(repr.parents) {
+        nodes
+            .get_mut(parent_id as usize)
+            .ok_or(Error::InvalidNodeId(parent_id))
+            .map_err(de::Error::custom)?
+            .add_child_id(&child_id);
+    }
+
+    if let Some(ranks) = repr.ranks {
+        let rank_of = |id: &NodeId| ranks[*id as usize - 1];
+        for node in &mut nodes {
+            if let Some(children) = node.children.as_mut() {
+                children.sort_by_key(rank_of);
+                if !children.iter().map(rank_of).eq(0..children.len()) {
+                    return Err(de::Error::custom(
+                        "sibling ranks must be a permutation of their positions",
+                    ));
+                }
+            }
+        }
+    }
+
+    Tree::from_nodes(nodes).map_err(de::Error::custom)
+}
+
+/// Returns the position of each non-root element among its siblings, or `None` if all children
+/// are stored in ascending [`NodeId`] order (and therefore the ranks need not be serialized).
+fn sibling_ranks<T>(tree: &Tree<T>) -> Option<Vec<usize>> {
+    let ascending = tree.nodes.iter().all(|tn| {
+        tn.children
+            .iter()
+            .flatten()
+            .zip(tn.children.iter().flatten().skip(1))
+            .all(|(a, b)| a < b)
+    });
+    if ascending {
+        return None;
+    }
+
+    let mut ranks = vec![0; tree.nodes.len().saturating_sub(1)];
+    for tn in &tree.nodes {
+        for (rank, &child_id) in tn.children.iter().flatten().enumerate() {
+            ranks[child_id as usize - 1] = rank;
+        }
+    }
+    Some(ranks)
+}
+
+/// Serializes the elements of a [`Tree`] as a flat sequence, without cloning them.
+struct Data<'tree, T>(&'tree [TreeNode<T>]);
+
+impl<'tree, T: Serialize> Serialize for Data<'tree, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|tn| &tn.data))
+    }
+}
+
+#[derive(Deserialize)]
+struct CompactRepr<T> {
+    parents: Vec<NodeId>,
+    data: Vec<T>,
+    #[serde(default)]
+    ranks: Option<Vec<usize>>,
+}
@@ -0,0 +1,136 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Error, NodeId, Tree};
+
+/// A read-only cursor pointing at an element stored in a [`Tree`], for manually navigating it
+/// towards the parent, the children or the siblings of that element.
+///
+/// It is created by [`Tree::cursor`] or [`Tree::root_cursor`]; moving it around returns a new
+/// cursor, as it is merely a cheap-to-copy pair of a reference to the [`Tree`] and a [`NodeId`].
+#[derive(Debug)]
+pub struct Cursor<'tree, T> {
+    tree: &'tree Tree<T>,
+    id: NodeId,
+}
+
+impl<T> Tree<T> {
+    /// Returns a [`Cursor`] pointing at the element stored under the provided [`NodeId`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element stored in the [`Tree`].
+    pub fn cursor(&self, id: &NodeId) -> Result<Cursor<'_, T>, Error> {
+        if !self.contains_id(id) {
+            return Err(Error::InvalidNodeId(*id));
+        }
+        Ok(Cursor {
+            tree: self,
+            id: *id,
+        })
+    }
+
+    /// Returns a [`Cursor`] pointing at the root element of the [`Tree`], or `None` if it is
+    /// empty.
+    pub fn root_cursor(&self) -> Option<Cursor<'_, T>> {
+        self.root_id().map(|id| Cursor { tree: self, id })
+    }
+}
+
+impl<'tree, T> Cursor<'tree, T> {
+    /// Returns the [`NodeId`] of the element the cursor points at.
+    pub fn node_id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the element the cursor points at.
+    pub fn data(&self) -> &'tree T {
+        &self.tree.nodes[self.id as usize].data
+    }
+
+    /// Returns a cursor pointing at the parent of the current element, or `None` if it is the
+    /// root.
+    pub fn parent(&self) -> Option<Self> {
+        self.tree.parent_id(&self.id).map(|id| self.at(id))
+    }
+
+    /// Returns a cursor pointing at the first child of the current element, or `None` if it is a
+    /// leaf.
+    pub fn first_child(&self) -> Option<Self> {
+        self.children_of(self.id)
+            .and_then(|children| children.first())
+            .map(|&id| self.at(id))
+    }
+
+    /// Returns a cursor pointing at the last child of the current element, or `None` if it is a
+    /// leaf.
+    pub fn last_child(&self) -> Option<Self> {
+        self.children_of(self.id)
+            .and_then(|children| children.last())
+            .map(|&id| self.at(id))
+    }
+
+    /// Returns a cursor pointing at the next sibling of the current element, or `None` if it is
+    /// the last child of its parent (or the root).
+    ///
+    /// # Note
+    ///
+    /// The underlying algorithm's time complexity is linear in the arity of the parent.
+    pub fn next_sibling(&self) -> Option<Self> {
+        let siblings = self.siblings()?;
+        let pos = siblings.iter().position(|&id| id == self.id)?;
+        siblings.get(pos + 1).map(|&id| self.at(id))
+    }
+
+    /// Returns a cursor pointing at the previous sibling of the current element, or `None` if it
+    /// is the first child of its parent (or the root).
+    ///
+    /// # Note
+    ///
+    /// The underlying algorithm's time complexity is linear in the arity of the parent.
+    pub fn prev_sibling(&self) -> Option<Self> {
+        let siblings = self.siblings()?;
+        let pos = siblings.iter().position(|&id| id == self.id)?;
+        pos.checked_sub(1).map(|pos| self.at(siblings[pos]))
+    }
+
+    /// Returns the [`NodeId`]s of the children of the parent of the current element.
+    fn siblings(&self) -> Option<&'tree [NodeId]> {
+        self.tree
+            .parent_id(&self.id)
+            .and_then(|parent_id| self.children_of(parent_id))
+    }
+
+    /// Returns the [`NodeId`]s of the children of the element stored under the provided
+    /// [`NodeId`].
+    fn children_of(&self, id: NodeId) -> Option<&'tree [NodeId]> {
+        self.tree.nodes[id as usize].children.as_deref()
+    }
+
+    fn at(&self, id: NodeId) -> Self {
+        Self {
+            tree: self.tree,
+            id,
+        }
+    }
+}
+
+impl<'tree, T> Clone for Cursor<'tree, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'tree, T> Copy for Cursor<'tree, T> {}
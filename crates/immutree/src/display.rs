@@ -0,0 +1,83 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use super::{NodeId, Tree};
+
+/// A helper for printing the elements stored in a [`Tree`] one per line, indented according to
+/// their depth with box-drawing characters.
+///
+/// It is created by [`Tree::display`]; e.g., for a [`Tree`] of integers:
+///
+/// ```text
+/// 0
+/// ├── 1
+/// │   ├── 3
+/// │   └── 4
+/// └── 2
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayTree<'tree, T>(&'tree Tree<T>);
+
+impl<T> Tree<T> {
+    /// Returns an object that implements [`Display`](fmt::Display) for printing the [`Tree`] with
+    /// indentation, as long as the elements stored in it implement it as well.
+    pub fn display(&self) -> DisplayTree<'_, T> {
+        DisplayTree(self)
+    }
+}
+
+impl<'tree, T: fmt::Display> fmt::Display for DisplayTree<'tree, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tree = self.0;
+        let root_id = match tree.root_id() {
+            Some(root_id) => root_id,
+            None => return Ok(()),
+        };
+
+        // Each entry holds the NodeId, the depth of the element and whether it is the last child
+        // of its parent.
+        let mut stack: Vec<(NodeId, usize, bool)> = vec![(root_id, 0, true)];
+        // For each depth on the path to the current element, whether it was the last child.
+        let mut lasts: Vec<bool> = Vec::new();
+        while let Some((id, depth, last)) = stack.pop() {
+            let node = &tree.nodes[id as usize];
+            if depth == 0 {
+                write!(f, "{}", node.data)?;
+            } else {
+                lasts.truncate(depth - 1);
+                f.write_str("\n")?;
+                for &ancestor_last in &lasts {
+                    f.write_str(if ancestor_last { "    " } else { "│   " })?;
+                }
+                write!(f, "{}{}", if last { "└── " } else { "├── " }, node.data)?;
+                lasts.push(last);
+            }
+
+            if let Some(children_ids) = &node.children {
+                let n = children_ids.len();
+                stack.extend(
+                    children_ids
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .map(|(i, &child_id)| (child_id, depth + 1, i + 1 == n)),
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use super::{NodeId, Tree};
+
+impl<T> Tree<T> {
+    /// Returns a description of the [`Tree`] in the Graphviz DOT language, i.e., a `digraph` with
+    /// one node per element, and one edge from each element to each of its children.
+    ///
+    /// DOT nodes are named after the [`NodeId`]s of the elements (e.g., `n0` for the root), while
+    /// their labels are produced by the provided closure, and escaped as needed.
+    pub fn to_dot<F, L>(&self, mut label_fn: F) -> String
+    where
+        F: FnMut(NodeId, &T) -> L,
+        L: AsRef<str>,
+    {
+        let mut dot = String::from("digraph {\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = label_fn(id as NodeId, &node.data);
+            // Writing into a String cannot fail.
+            let _ = write!(dot, "    n{id} [label=\"");
+            escape_into(&mut dot, label.as_ref());
+            dot.push_str("\"];\n");
+        }
+        for (parent_id, child_id) in self.edges() {
+            let _ = writeln!(dot, "    n{parent_id} -> n{child_id};");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Appends the provided label to `dot`, escaped to be used within a DOT double-quoted string.
+fn escape_into(dot: &mut String, label: &str) {
+    for c in label.chars() {
+        match c {
+            '"' => dot.push_str("\\\""),
+            '\\' => dot.push_str("\\\\"),
+            '\n' => dot.push_str("\\n"),
+            c => dot.push(c),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+use serde::{Serialize, Serializer};
+
+use super::Tree;
+
+/// A read-only, cheaply cloneable handle to a [`Tree`], which can be shared across threads (as
+/// long as the elements stored in it can).
+///
+/// It is created by [`Tree::freeze`], and it provides the whole read-only API of the [`Tree`]
+/// through [`Deref`]; cloning it only bumps a reference count, rather than cloning the elements.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct FrozenTree<T>(Arc<Tree<T>>);
+
+impl<T> Tree<T> {
+    /// Consume the [`Tree`] and return a [`FrozenTree`], i.e., a read-only handle to it that can
+    /// be cheaply cloned and shared across threads.
+    pub fn freeze(self) -> FrozenTree<T> {
+        FrozenTree(Arc::new(self))
+    }
+}
+
+impl<T> FrozenTree<T> {
+    /// Returns `true` if both handles refer to the same [`Tree`] (rather than to two equal ones).
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T: Clone> FrozenTree<T> {
+    /// Consume the handle and return the underlying [`Tree`], cloning it only if it is still
+    /// shared by other handles.
+    pub fn thaw(self) -> Tree<T> {
+        Arc::try_unwrap(self.0).unwrap_or_else(|shared| Tree::clone(&shared))
+    }
+}
+
+impl<T> Clone for FrozenTree<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for FrozenTree<T> {
+    type Target = Tree<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> AsRef<Tree<T>> for FrozenTree<T> {
+    fn as_ref(&self) -> &Tree<T> {
+        &self.0
+    }
+}
+
+impl<T> From<Tree<T>> for FrozenTree<T> {
+    fn from(tree: Tree<T>) -> Self {
+        tree.freeze()
+    }
+}
+
+/// A [`FrozenTree`] is serialized exactly like the [`Tree`] it refers to.
+impl<T: Serialize> Serialize for FrozenTree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
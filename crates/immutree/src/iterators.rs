@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use core::iter::FusedIterator;
+use alloc::vec;
+use alloc::vec::{IntoIter, Vec};
+use core::iter::{Enumerate, FusedIterator};
 use core::slice::Iter;
 
 use super::{Error, NodeId, Tree, TreeNode};
@@ -47,8 +49,16 @@ impl<'tree> Iterator for ImmediateDescendantIds<'tree> {
     fn next(&mut self) -> Option<Self::Item> {
         self.0.as_mut().and_then(|inner| inner.next().copied())
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0
+            .as_ref()
+            .map_or((0, Some(0)), |inner| inner.size_hint())
+    }
 }
 
+impl<'tree> ExactSizeIterator for ImmediateDescendantIds<'tree> {}
+
 impl<'tree> FusedIterator for ImmediateDescendantIds<'tree> {}
 
 impl<'tree> DoubleEndedIterator for ImmediateDescendantIds<'tree> {
@@ -88,6 +98,10 @@ impl<'tree, T> Iterator for ImmediateDescendants<'tree, T> {
             .next()
             .and_then(|ref child_id| self.tree.get_by_id(child_id))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
 }
 
 impl<'tree, T> DoubleEndedIterator for ImmediateDescendants<'tree, T> {
@@ -98,6 +112,8 @@ impl<'tree, T> DoubleEndedIterator for ImmediateDescendants<'tree, T> {
     }
 }
 
+impl<'tree, T> ExactSizeIterator for ImmediateDescendants<'tree, T> {}
+
 impl<'tree, T> FusedIterator for ImmediateDescendants<'tree, T> {}
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
@@ -135,7 +151,8 @@ impl<'tree, T> Iterator for LeafIds<'tree, T> {
         while let Some((id, tn)) = self.stack.pop() {
             if let Some(children) = tn.children.as_ref() {
                 // SAFETY: We safely `unwrap` because `child_id` is retrieved from the `TreeNode`,
-                // which has been sanitized during insertions, and the `Tree` is immutable.
+                // which has been sanitized during insertions (or deserialization), and the `Tree` is
+                // immutable.
                 self.stack.extend(
                     children.iter().map(|child_id| {
                         (*child_id, self.tree.nodes.get(*child_id as usize).unwrap())
@@ -147,6 +164,12 @@ impl<'tree, T> Iterator for LeafIds<'tree, T> {
         }
         None
     }
+
+    /// Each pending subtree yields at least one leaf, while no more leaves than the total number
+    /// of elements in the [`Tree`] may ever be yielded.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.stack.len(), Some(self.tree.nodes.len()))
+    }
 }
 
 impl<'tree, T> FusedIterator for LeafIds<'tree, T> {}
@@ -183,6 +206,10 @@ impl<'tree, T> Iterator for Leaves<'tree, T> {
         }
         None
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids_iter.size_hint()
+    }
 }
 
 impl<'tree, T> FusedIterator for Leaves<'tree, T> {}
@@ -198,12 +225,10 @@ impl<'tree, T> FusedIterator for Leaves<'tree, T> {}
 ///
 /// # Note
 ///
-/// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+/// Each step of the iterator takes `O(1)` time, following the parent link of the element.
 #[derive(Debug, Clone)]
 pub struct AncestorIds<'tree, T> {
     tree: &'tree Tree<T>,
-    found: bool,
-    parents: Vec<Option<NodeId>>,
     curr: Option<NodeId>,
 }
 
@@ -211,8 +236,6 @@ impl<'tree, T> AncestorIds<'tree, T> {
     pub(super) fn new(tree: &'tree Tree<T>, id: &NodeId) -> Self {
         Self {
             tree,
-            found: false,
-            parents: vec![None; tree.nodes.len()],
             curr: Some(*id),
         }
     }
@@ -222,22 +245,7 @@ impl<'tree, T> Iterator for AncestorIds<'tree, T> {
     type Item = NodeId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if !self.found {
-            //self.parents.resize(self.tree.nodes.len(), None);
-            for (parent_id, tn) in self.tree.nodes.iter().enumerate() {
-                if let Some(children) = tn.children.as_ref() {
-                    for &child_id in children {
-                        self.parents[child_id as usize] = Some(parent_id as NodeId);
-                    }
-                }
-            }
-            self.found = true;
-        }
-        //self.stack.pop().map(|(id, _)| id)
-
-        if let Some(curr) = self.curr {
-            self.curr = self.parents[curr as usize];
-        }
+        self.curr = self.curr.and_then(|curr| self.tree.parent_id(&curr));
         self.curr
     }
 }
@@ -255,7 +263,7 @@ impl<'tree, T> FusedIterator for AncestorIds<'tree, T> {}
 ///
 /// # Note
 ///
-/// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+/// Each step of the iterator takes `O(1)` time, following the parent link of the element.
 #[derive(Debug, Clone)]
 pub struct Ancestors<'tree, T> {
     tree: &'tree Tree<T>,
@@ -281,3 +289,135 @@ impl<'tree, T> Iterator for Ancestors<'tree, T> {
         None
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////  Nodes
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An iterator over all elements stored in the [`Tree`] along with their [`NodeId`]s, in
+/// ascending [`NodeId`] order.
+#[derive(Debug, Clone)]
+pub struct Nodes<'tree, T>(Enumerate<Iter<'tree, TreeNode<T>>>);
+
+impl<'tree, T> Nodes<'tree, T> {
+    pub(super) fn new(tree: &'tree Tree<T>) -> Self {
+        Self(tree.nodes.iter().enumerate())
+    }
+}
+
+impl<'tree, T> Iterator for Nodes<'tree, T> {
+    type Item = (NodeId, &'tree T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(id, tn)| (id as NodeId, &tn.data))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'tree, T> DoubleEndedIterator for Nodes<'tree, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(id, tn)| (id as NodeId, &tn.data))
+    }
+}
+
+impl<'tree, T> ExactSizeIterator for Nodes<'tree, T> {}
+
+impl<'tree, T> FusedIterator for Nodes<'tree, T> {}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////  IntoNodes
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An owning iterator over all elements stored in the [`Tree`] along with their [`NodeId`]s, in
+/// ascending [`NodeId`] order.
+#[derive(Debug, Clone)]
+pub struct IntoNodes<T>(Enumerate<IntoIter<TreeNode<T>>>);
+
+impl<T> IntoNodes<T> {
+    pub(super) fn new(tree: Tree<T>) -> Self {
+        Self(tree.nodes.into_iter().enumerate())
+    }
+}
+
+impl<T> Iterator for IntoNodes<T> {
+    type Item = (NodeId, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(id, tn)| (id as NodeId, tn.data))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoNodes<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(id, tn)| (id as NodeId, tn.data))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoNodes<T> {}
+
+impl<T> FusedIterator for IntoNodes<T> {}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////
+////  Edges
+////
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An iterator over all `(parent, child)` pairs of [`NodeId`]s in the [`Tree`], grouped by parent
+/// in ascending [`NodeId`] order, and then in the order in which the children are stored.
+#[derive(Debug, Clone)]
+pub struct Edges<'tree, T> {
+    nodes: Enumerate<Iter<'tree, TreeNode<T>>>,
+    children: Option<(NodeId, Iter<'tree, NodeId>)>,
+    remaining: usize,
+}
+
+impl<'tree, T> Edges<'tree, T> {
+    pub(super) fn new(tree: &'tree Tree<T>) -> Self {
+        Self {
+            nodes: tree.nodes.iter().enumerate(),
+            children: None,
+            // All elements but the root have exactly one parent.
+            remaining: tree.nodes.len().saturating_sub(1),
+        }
+    }
+}
+
+impl<'tree, T> Iterator for Edges<'tree, T> {
+    type Item = (NodeId, NodeId);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((parent_id, children)) = self.children.as_mut() {
+                if let Some(&child_id) = children.next() {
+                    self.remaining -= 1;
+                    return Some((*parent_id, child_id));
+                }
+            }
+            let (id, tn) = self.nodes.next()?;
+            self.children = tn
+                .children
+                .as_ref()
+                .map(|children| (id as NodeId, children.iter()));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'tree, T> ExactSizeIterator for Edges<'tree, T> {}
+
+impl<'tree, T> FusedIterator for Edges<'tree, T> {}
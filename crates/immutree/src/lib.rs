@@ -21,22 +21,66 @@
 //!
 //! One of the main goals of the crate is to provide a tree data structure that is dead-simple to
 //! serialize and deserialize.
-use serde::{Deserialize, Serialize};
+//!
+//! # Features
+//!
+//! - `std` (enabled by default): Implements `std::error::Error` for [`Error`]. Without it, the
+//! crate is `no_std`, only depending on `alloc`.
+//! - `u64-node-ids`: Makes [`NodeId`] a `u64` rather than a `u32`, so that [`Tree`]s with more
+//! than `u32::MAX` elements, or externally assigned 64-bit IDs, can be represented.
+//! - `rayon`: Provides parallel iterators over the elements stored in a [`Tree`] (e.g.,
+//! `Tree::par_iter`), backed by `rayon`.
+//! - `smallvec`: Stores the children [`NodeId`]s of each element in a `SmallVec`, so that elements
+//! with up to 4 children do not require a heap allocation of their own.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::{Index, IndexMut, Range};
 
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+mod branded;
+pub mod compact;
+mod cursor;
+mod display;
+mod dot;
+mod frozen;
 mod iterators;
+#[cfg(feature = "rayon")]
+mod par;
+mod renumber;
+mod stats;
 mod types;
+mod walk;
 
+pub use branded::BrandedNodeId;
+pub use compact::Compact;
+pub use cursor::Cursor;
+pub use display::DisplayTree;
+pub use frozen::FrozenTree;
 pub use iterators::AncestorIds;
 pub use iterators::Ancestors;
+pub use iterators::Edges;
 pub use iterators::ImmediateDescendantIds;
 pub use iterators::ImmediateDescendants;
+pub use iterators::IntoNodes;
 pub use iterators::LeafIds;
 pub use iterators::Leaves;
+pub use iterators::Nodes;
+pub use renumber::NodeIdMap;
+pub use stats::SubtreeStats;
 pub use types::Error;
 pub use types::InsertMode;
 pub use types::NodeId;
+pub use walk::Visitor;
+pub use walk::WalkAction;
 
-use types::TreeNode;
+use branded::Brand;
+use types::{TreeNode, TreeRepr};
 
 /// A simple implementation of a tree container structure, generic over the data stored.
 ///
@@ -50,13 +94,17 @@ use types::TreeNode;
 ///
 /// - This data structure is not thread-safe (i.e., it is not meant to be used by multiple threads
 /// concurrently, unless all accesses are read-only).
-/// - A limited number of elements is supported (i.e., `u32::MAX`).
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// - A limited number of elements is supported (i.e., `NodeId::MAX`, which is `u32::MAX` unless
+/// the `u64-node-ids` feature is enabled).
+#[derive(Debug, Serialize)]
 pub struct Tree<T> {
     pub(crate) nodes: Vec<TreeNode<T>>,
 
     #[serde(skip)]
-    next_node_id: u32,
+    next_node_id: NodeId,
+
+    #[serde(skip)]
+    brand: Brand,
 }
 
 impl<T> Tree<T> {
@@ -65,14 +113,28 @@ impl<T> Tree<T> {
         Self {
             nodes: Vec::new(),
             next_node_id: 0,
+            brand: Brand::new(),
         }
     }
 
+    /// Allocate a new [`Tree`] with the provided `element` as its root, and return it along with
+    /// the [`NodeId`] assigned to the root element.
+    ///
+    /// This is equivalent to inserting the `element` into a new empty [`Tree`] with
+    /// [`InsertMode::AsRoot`], which cannot fail.
+    pub fn with_root(element: T) -> (Self, NodeId) {
+        let mut tree = Self::new();
+        tree.nodes.push(TreeNode::new(element, None));
+        tree.next_node_id = 1;
+        (tree, 0)
+    }
+
     /// Allocate a new empty [`Tree`], allocating as much as possible a priori.
     pub fn with_capacity(size: usize) -> Self {
         Self {
             nodes: Vec::with_capacity(size),
             next_node_id: 0,
+            brand: Brand::new(),
         }
     }
 
@@ -88,18 +150,118 @@ impl<T> Tree<T> {
         0 == self.nodes.len()
     }
 
+    /// Returns the number of elements the [`Tree`] can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// Reserve capacity for at least `additional` more elements to be inserted in the [`Tree`].
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional)
+    }
+
+    /// Remove all elements from the [`Tree`], retaining its allocated capacity, so that it can be
+    /// reused to store a new hierarchy.
+    ///
+    /// Previously issued [`NodeId`]s must not be used anymore; [`BrandedNodeId`]s, in particular,
+    /// are rejected with [`Error::ForeignNodeId`].
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.next_node_id = 0;
+        self.brand = Brand::new();
+    }
+
+    /// Shrink the capacity of the [`Tree`], as well as that of the children [`NodeId`]s of each
+    /// element stored in it, as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        for tn in &mut self.nodes {
+            if let Some(children) = tn.children.as_mut() {
+                children.shrink_to_fit();
+            }
+        }
+    }
+
     /// Returns a reference to the root element stored of the [`Tree`], if it exists; `None`
     /// otherwise.
     pub fn root(&self) -> Option<&T> {
         self.nodes.get(0).map(|tn| &tn.data)
     }
 
+    /// Returns the [`NodeId`] of the root element of the [`Tree`], if it exists; `None` otherwise.
+    #[inline]
+    pub fn root_id(&self) -> Option<NodeId> {
+        (!self.is_empty()).then_some(0)
+    }
+
+    /// Returns `true` if the provided [`NodeId`] corresponds to an element currently stored in the
+    /// [`Tree`]; `false` otherwise.
+    #[inline]
+    pub fn contains_id(&self, id: &NodeId) -> bool {
+        (*id as usize) < self.nodes.len()
+    }
+
     /// Returns a reference to the element stored in the [`Tree`] under the provided [`NodeId`], if
     /// it exists; `None` otherwise.
     pub fn get_by_id(&self, id: &NodeId) -> Option<&T> {
         self.nodes.get(*id as usize).map(|tn| &tn.data)
     }
 
+    /// Returns a mutable reference to the root element stored in the [`Tree`], if it exists;
+    /// `None` otherwise.
+    ///
+    /// Only the stored element may be mutated; the structure of the [`Tree`] remains immutable.
+    pub fn root_mut(&mut self) -> Option<&mut T> {
+        self.nodes.get_mut(0).map(|tn| &mut tn.data)
+    }
+
+    /// Returns a mutable reference to the element stored in the [`Tree`] under the provided
+    /// [`NodeId`], if it exists; `None` otherwise.
+    ///
+    /// Only the stored element may be mutated; the structure of the [`Tree`] remains immutable.
+    pub fn get_mut_by_id(&mut self, id: &NodeId) -> Option<&mut T> {
+        self.nodes.get_mut(*id as usize).map(|tn| &mut tn.data)
+    }
+
+    /// Returns the [`NodeId`] of the first element (in ascending [`NodeId`] order) stored in the
+    /// [`Tree`] that satisfies the provided predicate, if any; `None` otherwise.
+    pub fn find<P>(&self, mut predicate: P) -> Option<NodeId>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.nodes
+            .iter()
+            .position(|tn| predicate(&tn.data))
+            .map(|idx| idx as NodeId)
+    }
+
+    /// Returns the [`NodeId`]s of all elements stored in the [`Tree`] that satisfy the provided
+    /// predicate, in ascending [`NodeId`] order.
+    pub fn find_all<P>(&self, mut predicate: P) -> Vec<NodeId>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .filter_map(|(id, data)| predicate(data).then_some(id))
+            .collect()
+    }
+
+    /// Returns an iterator over all elements stored in the [`Tree`] along with their [`NodeId`]s,
+    /// in ascending [`NodeId`] order.
+    #[inline]
+    pub fn iter(&self) -> Nodes<'_, T> {
+        Nodes::new(self)
+    }
+
+    /// Returns an iterator over all `(parent, child)` pairs of [`NodeId`]s in the [`Tree`], i.e.,
+    /// one per element other than the root.
+    #[inline]
+    pub fn edges(&self) -> Edges<'_, T> {
+        Edges::new(self)
+    }
+
     /// Returns an iterator over the [`NodeId`]s that correspond to the immediate descendant
     /// (i.e., the children) elements of the element stored in the [`Tree`] under the provided
     /// `id`.
@@ -125,6 +287,47 @@ impl<T> Tree<T> {
         ImmediateDescendants::try_new(self, id)
     }
 
+    /// Returns the [`NodeId`]s that correspond to the immediate descendant (i.e., the children)
+    /// elements of the element stored in the [`Tree`] under the provided `id`, as a slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element currently stored in the [`Tree`].
+    pub fn child_ids_slice(&self, id: &NodeId) -> Result<&[NodeId], Error> {
+        Ok(self
+            .nodes
+            .get(*id as usize)
+            .ok_or(Error::InvalidNodeId(*id))?
+            .children
+            .as_deref()
+            .unwrap_or_default())
+    }
+
+    /// Returns the number of immediate descendants (i.e., the children) of the element stored in
+    /// the [`Tree`] under the provided `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element currently stored in the [`Tree`].
+    #[inline]
+    pub fn arity(&self, id: &NodeId) -> Result<usize, Error> {
+        self.child_ids_slice(id).map(<[NodeId]>::len)
+    }
+
+    /// Returns `true` if the element stored in the [`Tree`] under the provided `id` has no
+    /// immediate descendants (i.e., children); `false` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element currently stored in the [`Tree`].
+    #[inline]
+    pub fn is_leaf(&self, id: &NodeId) -> Result<bool, Error> {
+        self.child_ids_slice(id).map(<[NodeId]>::is_empty)
+    }
+
     /// Returns an iterator over the [`NodeId`]s that correspond to the elements at the leaves of
     /// the [`Tree`], which are also descendants of the provided `id`.
     ///
@@ -163,7 +366,7 @@ impl<T> Tree<T> {
     ///
     /// # Note
     ///
-    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    /// Each step of the iterator takes `O(1)` time, following the parent link of the element.
     #[inline]
     pub fn ancestor_ids(&self, id: &NodeId) -> AncestorIds<T> {
         AncestorIds::new(self, id)
@@ -174,7 +377,7 @@ impl<T> Tree<T> {
     ///
     /// # Note
     ///
-    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    /// Each step of the iterator takes `O(1)` time, following the parent link of the element.
     #[inline]
     pub fn ancestors(&self, id: &NodeId) -> Ancestors<T> {
         Ancestors::new(self, id)
@@ -182,29 +385,104 @@ impl<T> Tree<T> {
 
     /// Returns the [`NodeId`] of the immediate ancestor (i.e., the parent) element of the element
     /// stored in the [`Tree`] under `id`, or `None` for the root element.
+    pub fn parent_id(&self, id: &NodeId) -> Option<NodeId> {
+        self.nodes.get(*id as usize).and_then(|tn| tn.parent)
+    }
+
+    /// Returns the [`NodeId`] of the lowest common ancestor of the elements stored in the [`Tree`]
+    /// under `a` and `b`; i.e., the deepest element that is an ancestor of both.
+    ///
+    /// Note that each element is considered to be an ancestor of itself; e.g., if `a` is an
+    /// ancestor of `b`, then `a` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if any of the provided [`NodeId`]s does not correspond to
+    /// an element currently stored in the [`Tree`].
     ///
     /// # Note
     ///
-    /// The underlying algorithm's time complexity is `O(|V|)`.
-    pub fn parent_id(&self, id: &NodeId) -> Option<NodeId> {
-        self.nodes
-            .iter()
-            .enumerate()
-            .find(|&(_, tn)| tn.children.is_some() && tn.children.as_ref().unwrap().contains(id))
-            .map(|(parent_id, _)| parent_id as NodeId)
+    /// The underlying algorithm's time complexity is linear in the depths of the two elements.
+    pub fn lca(&self, a: &NodeId, b: &NodeId) -> Result<NodeId, Error> {
+        for id in [a, b] {
+            if !self.contains_id(id) {
+                return Err(Error::InvalidNodeId(*id));
+            }
+        }
+
+        let parent_of = |id: NodeId| {
+            self.nodes[id as usize]
+                .parent
+                .expect("all elements in the Tree must descend from its root")
+        };
+        let depth_of = |id: NodeId| self.ancestor_ids(&id).count();
+
+        let (mut a, mut b) = (*a, *b);
+        let (mut depth_a, mut depth_b) = (depth_of(a), depth_of(b));
+        while depth_a > depth_b {
+            a = parent_of(a);
+            depth_a -= 1;
+        }
+        while depth_b > depth_a {
+            b = parent_of(b);
+            depth_b -= 1;
+        }
+        while a != b {
+            a = parent_of(a);
+            b = parent_of(b);
+        }
+        Ok(a)
     }
 
-    /// Returns the immediate ancestor (i.e., the parent) element of the element stored in the
-    /// [`Tree`] under `id`, or `None` for the root element.
+    /// Returns `true` if the element stored under `a` is an ancestor of the element stored under
+    /// `b`; `false` otherwise.
+    ///
+    /// Consistent with [`Tree::ancestor_ids`], an element is not considered an ancestor of itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if any of the provided [`NodeId`]s does not correspond to
+    /// an element currently stored in the [`Tree`].
+    ///
+    /// # Note
+    ///
+    /// The underlying algorithm's time complexity is linear in the depth of `b`.
+    pub fn is_ancestor_of(&self, a: &NodeId, b: &NodeId) -> Result<bool, Error> {
+        for id in [a, b] {
+            if !self.contains_id(id) {
+                return Err(Error::InvalidNodeId(*id));
+            }
+        }
+        Ok(self.ancestor_ids(b).any(|id| id == *a))
+    }
+
+    /// Returns the [`NodeId`]s of all elements stored in the [`Tree`], grouped per depth (i.e., the
+    /// `i`-th group holds the elements at distance `i` from the root), in breadth-first order.
+    ///
+    /// An empty [`Tree`] has no levels.
     ///
     /// # Note
     ///
-    /// The underlying algorithm's time complexity is `O(|V|)`.
+    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    pub fn levels(&self) -> Vec<Vec<NodeId>> {
+        let mut levels: Vec<Vec<NodeId>> = Vec::new();
+        let mut level = Vec::from_iter(self.root_id());
+        while !level.is_empty() {
+            let next = level
+                .iter()
+                .flat_map(|&id| self.nodes[id as usize].children.iter().flatten().copied())
+                .collect();
+            levels.push(level);
+            level = next;
+        }
+        levels
+    }
+
+    /// Returns the immediate ancestor (i.e., the parent) element of the element stored in the
+    /// [`Tree`] under `id`, or `None` for the root element.
     pub fn parent(&self, id: &NodeId) -> Option<&T> {
-        self.nodes
-            .iter()
-            .find(|&tn| tn.children.is_some() && tn.children.as_ref().unwrap().contains(id))
-            .map(|tn| &tn.data)
+        self.parent_id(id)
+            .map(|parent_id| &self.nodes[parent_id as usize].data)
     }
 
     /// Returns a `Vec` of the [`NodeId`]s that correspond to the children of the element stored in
@@ -214,7 +492,7 @@ impl<T> Tree<T> {
     pub fn children_ids(&self, id: &NodeId) -> Option<Vec<NodeId>> {
         self.nodes
             .get(*id as usize)
-            .and_then(|tn| tn.children.clone())
+            .and_then(|tn| tn.children.as_deref().map(<[NodeId]>::to_vec))
     }
 
     /// Returns a `Vec` of the children elements of the element stored in the [`Tree`] under the
@@ -257,7 +535,8 @@ impl<T> Tree<T> {
         while let Some((id, tn)) = stack.pop() {
             if let Some(children) = tn.children.as_ref() {
                 // SAFETY: We safely `unwrap` because `child_id` is retrieved from the `TreeNode`,
-                // which has been sanitized during insertions (and the `Tree` is immutable).
+                // which has been sanitized during insertions or deserialization (and the `Tree` is
+                // immutable).
                 stack.extend(
                     children
                         .iter()
@@ -307,23 +586,21 @@ impl<T> Tree<T> {
     /// [`Tree`] while there is one already.
     /// - Returns [`Error::NonExistentParent`] if the parent's [`NodeId`] (provided by the caller)
     /// does not correspond to an element currently stored in the [`Tree`].
+    /// - Returns [`Error::InvalidPosition`] if the position provided via [`InsertMode::UnderAt`]
+    /// is greater than the number of the parent's children.
     pub fn insert(&mut self, element: T, mode: InsertMode) -> Result<NodeId, Error> {
-        // If next_node_id equals to 0 but the Tree is not empty, then this Tree must have been
-        // constructed via deserialization, where next_node_id is ignored. Therefore, it must
-        // be calculated again: it should be equal to the length of the Tree (because 0-indexed).
-        // Note that this is not expected to be a common case in our targeted scenarios, since we
-        // do not expect the need to insert any new nodes after deserializing such a Tree.
-        if 0 == self.next_node_id && !self.is_empty() {
-            self.next_node_id = self.len() as u32;
-        }
-
         // Fail fast if attempted to change root after first insertion
         if matches!(mode, InsertMode::AsRoot) && 0 != self.next_node_id {
             return Err(Error::RootReplacement);
         }
 
         // Update self.nodes
-        if let InsertMode::Under(&parent_id) = mode {
+        let (parent_id, position) = match mode {
+            InsertMode::AsRoot => (None, None),
+            InsertMode::Under(&parent_id) => (Some(parent_id), None),
+            InsertMode::UnderAt(&parent_id, position) => (Some(parent_id), Some(position)),
+        };
+        if let Some(parent_id) = parent_id {
             // We reach every node through its parent, therefore the latter should already be
             // present in our `node_map`; if not, return an error.
             if parent_id >= self.next_node_id {
@@ -333,15 +610,377 @@ impl<T> Tree<T> {
             // SAFETY: We checked that `parent_id < self.next_node_id`, therefore some previous
             // insertion has resized `self.node_map` and `self.children` to accommodate at least
             // `parent_id` entries; hence the unchecked indexing.
-            self.nodes[parent_id as usize].add_child_id(&self.next_node_id);
+            let parent = &mut self.nodes[parent_id as usize];
+            match position {
+                None => parent.add_child_id(&self.next_node_id),
+                Some(position) => {
+                    if position
+                        > parent
+                            .children
+                            .as_ref()
+                            .map_or(0, |children| children.len())
+                    {
+                        return Err(Error::InvalidPosition(parent_id, position));
+                    }
+                    parent.insert_child_id(position, &self.next_node_id)
+                }
+            }
         }
-        self.nodes.push(element.into());
+        self.nodes.push(TreeNode::new(element, parent_id));
 
         // Update self.next_node_id
         self.next_node_id += 1;
 
         Ok(self.next_node_id - 1)
     }
+
+    /// Insert a new `element` into the [`Tree`], like [`Tree::insert`], and return the [`NodeId`]
+    /// assigned to it along with a mutable reference to the stored element.
+    ///
+    /// # Errors
+    ///
+    /// See [`Tree::insert`].
+    pub fn insert_get(&mut self, element: T, mode: InsertMode) -> Result<(NodeId, &mut T), Error> {
+        let id = self.insert(element, mode)?;
+        Ok((id, &mut self.nodes[id as usize].data))
+    }
+
+    /// Insert all provided `elements` into the [`Tree`] as children of the element stored under
+    /// `parent_id`, and return the [`NodeId`]s assigned to them, in insertion order.
+    ///
+    /// See [`Tree::extend_under`], which avoids allocating the returned `Vec`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NonExistentParent`] if the parent's [`NodeId`] (provided by the caller)
+    /// does not correspond to an element currently stored in the [`Tree`]; in that case, none of
+    /// the `elements` is inserted.
+    pub fn insert_children<I>(
+        &mut self,
+        parent_id: &NodeId,
+        elements: I,
+    ) -> Result<Vec<NodeId>, Error>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.extend_under(parent_id, elements)
+            .map(Iterator::collect)
+    }
+
+    /// Insert all provided `elements` into the [`Tree`] as children of the element stored under
+    /// `parent_id`, in a single pass, and return the range of the (consecutive) [`NodeId`]s
+    /// assigned to them, in insertion order.
+    ///
+    /// Storage is reserved once, based on the lower bound of the iterator's `size_hint`, rather
+    /// than on each insertion.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NonExistentParent`] if the parent's [`NodeId`] (provided by the caller)
+    /// does not correspond to an element currently stored in the [`Tree`]; in that case, none of
+    /// the `elements` is inserted.
+    pub fn extend_under<I>(
+        &mut self,
+        parent_id: &NodeId,
+        elements: I,
+    ) -> Result<Range<NodeId>, Error>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        if *parent_id >= self.next_node_id {
+            return Err(Error::NonExistentParent(*parent_id));
+        }
+
+        let elements = elements.into_iter();
+        let (additional, _) = elements.size_hint();
+        self.nodes.reserve(additional);
+        self.nodes[*parent_id as usize].reserve_children(additional);
+
+        let first_id = self.next_node_id;
+        for element in elements {
+            // SAFETY: We checked that `parent_id < self.next_node_id` above; hence the unchecked
+            // indexing.
+            self.nodes[*parent_id as usize].add_child_id(&self.next_node_id);
+            self.nodes.push(TreeNode::new(element, Some(*parent_id)));
+            self.next_node_id += 1;
+        }
+
+        Ok(first_id..self.next_node_id)
+    }
+
+    /// Sort the children of the element stored under the provided [`NodeId`], using the provided
+    /// comparator function on the children elements.
+    ///
+    /// Only the order in which the children are stored (and therefore iterated over and
+    /// serialized) changes; all elements retain their [`NodeId`]s.
+    /// The sort is stable, i.e., children that compare equal retain their relative order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element stored in the [`Tree`].
+    pub fn sort_children_by<F>(&mut self, id: &NodeId, mut compare: F) -> Result<(), Error>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if !self.contains_id(id) {
+            return Err(Error::InvalidNodeId(*id));
+        }
+        self.sort_children_of(*id as usize, &mut compare);
+        Ok(())
+    }
+
+    /// Sort the children of every element stored in the [`Tree`], using the provided comparator
+    /// function on the children elements.
+    ///
+    /// See [`Tree::sort_children_by`] for details.
+    pub fn sort_all_children_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        for idx in 0..self.nodes.len() {
+            self.sort_children_of(idx, &mut compare);
+        }
+    }
+
+    fn sort_children_of<F>(&mut self, idx: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // The children are temporarily taken out, so that the elements can be borrowed while
+        // sorting them.
+        if let Some(mut children) = self.nodes[idx].children.take() {
+            children.sort_by(|a, b| {
+                compare(&self.nodes[*a as usize].data, &self.nodes[*b as usize].data)
+            });
+            self.nodes[idx].children = Some(children);
+        }
+    }
+
+    /// Construct a new [`Tree`] out of its parent-array representation; i.e., the `i`-th item
+    /// holds the element to be stored under [`NodeId`] `i`, along with the [`NodeId`] of its
+    /// parent (`None` for the root element, which must be the first item).
+    ///
+    /// The children of each element are stored in ascending [`NodeId`] order.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`Error::NonExistentParent`] if an item's parent [`NodeId`] does not correspond
+    /// to any of the provided items.
+    /// - Returns [`Error::RootAsChild`] if the first item has a parent.
+    /// - Returns [`Error::Unreachable`] if an item other than the first one has no parent, or if
+    /// items form a cycle.
+    pub fn from_parent_array(items: Vec<(Option<NodeId>, T)>) -> Result<Self, Error> {
+        let parents: Vec<Option<NodeId>> = items.iter().map(|(parent_id, _)| *parent_id).collect();
+        let mut nodes: Vec<TreeNode<T>> = items
+            .into_iter()
+            .map(|(_, element)| TreeNode::from(element))
+            .collect();
+        for (id, parent_id) in parents.into_iter().enumerate() {
+            match parent_id {
+                None if 0 == id => {}
+                None => return Err(Error::Unreachable(id as NodeId)),
+                Some(parent_id) if 0 == id => return Err(Error::RootAsChild(parent_id)),
+                Some(parent_id) => nodes
+                    .get_mut(parent_id as usize)
+                    .ok_or(Error::NonExistentParent(parent_id))?
+                    .add_child_id(&(id as NodeId)),
+            }
+        }
+        Self::from_nodes(nodes)
+    }
+
+    /// Returns the parent-array representation of the [`Tree`], as accepted by
+    /// [`Tree::from_parent_array`], cloning its elements.
+    ///
+    /// # Note
+    ///
+    /// The order of siblings is not part of the representation; i.e., a [`Tree`] whose children
+    /// are not stored in ascending [`NodeId`] order (e.g., after [`Tree::sort_children_by`]) is not
+    /// restored as is.
+    pub fn to_parent_array(&self) -> Vec<(Option<NodeId>, T)>
+    where
+        T: Clone,
+    {
+        self.nodes
+            .iter()
+            .map(|tn| (tn.parent, tn.data.clone()))
+            .collect()
+    }
+
+    /// Consume the [`Tree`] and return its parent-array representation, as accepted by
+    /// [`Tree::from_parent_array`].
+    ///
+    /// See [`Tree::to_parent_array`] for details.
+    pub fn into_parent_array(self) -> Vec<(Option<NodeId>, T)> {
+        self.nodes
+            .into_iter()
+            .map(|tn| (tn.parent, tn.data))
+            .collect()
+    }
+
+    /// Construct a new [`Tree`] out of the provided `nodes`, after validating its structure and
+    /// restoring the parent links of its elements.
+    ///
+    /// Empty lists of children (e.g., `"desc": []`) are normalized to none at all, as is the case
+    /// for the leaves of [`Tree`]s that have been constructed through insertions.
+    pub(crate) fn from_nodes(nodes: Vec<TreeNode<T>>) -> Result<Self, Error> {
+        let mut tree = Self {
+            next_node_id: nodes.len() as NodeId,
+            nodes,
+            brand: Brand::new(),
+        };
+        tree.validate()?;
+        for parent_id in 0..tree.nodes.len() {
+            // The children are temporarily taken out, so that their elements can be updated.
+            if let Some(children) = tree.nodes[parent_id]
+                .children
+                .take()
+                .filter(|children| !children.is_empty())
+            {
+                for &child_id in &children {
+                    tree.nodes[child_id as usize].parent = Some(parent_id as NodeId);
+                }
+                tree.nodes[parent_id].children = Some(children);
+            }
+        }
+        Ok(tree)
+    }
+
+    /// Validate the structure of the [`Tree`]; i.e., make sure that:
+    ///
+    /// - all children [`NodeId`]s correspond to elements stored in the [`Tree`];
+    /// - no element is listed as the child of more than one element, or more than once;
+    /// - the root element (i.e., the one stored under [`NodeId`] `0`) is nobody's child;
+    /// - all elements are reachable from the root element (hence, there are no cycles either).
+    ///
+    /// This is meant for [`Tree`]s that have not been constructed through insertions (e.g., those
+    /// that have been deserialized), since insertions always maintain a valid structure.
+    ///
+    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    fn validate(&self) -> Result<(), Error> {
+        let mut has_parent = vec![false; self.nodes.len()];
+        for (parent_id, tn) in self.nodes.iter().enumerate() {
+            for &child_id in tn.children.iter().flatten() {
+                match has_parent.get_mut(child_id as usize) {
+                    None => return Err(Error::DanglingChild(parent_id as NodeId, child_id)),
+                    Some(_) if 0 == child_id => {
+                        return Err(Error::RootAsChild(parent_id as NodeId))
+                    }
+                    Some(true) => return Err(Error::MultipleParents(child_id)),
+                    Some(has_parent) => *has_parent = true,
+                }
+            }
+        }
+
+        // Since each element has at most one parent, and the root has none, a traversal starting
+        // from the root can never visit an element twice.
+        let mut visited = vec![false; self.nodes.len()];
+        let mut stack = Vec::from_iter(self.root_id());
+        while let Some(id) = stack.pop() {
+            visited[id as usize] = true;
+            stack.extend(self.nodes[id as usize].children.iter().flatten());
+        }
+        if let Some(id) = visited.iter().position(|&visited| !visited) {
+            return Err(Error::Unreachable(id as NodeId));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cloned [`Tree`]s get a brand of their own; i.e., [`BrandedNodeId`]s issued by the original
+/// [`Tree`] cannot be used against the clone, and vice versa.
+impl<T: Clone> Clone for Tree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            next_node_id: self.next_node_id,
+            brand: Brand::new(),
+        }
+    }
+}
+
+/// Deserialized [`Tree`]s are validated before being returned; i.e., children [`NodeId`]s must
+/// correspond to elements stored in the [`Tree`], each element must be listed as a child at most
+/// once, and all elements must be reachable from the root element (i.e., the one stored under
+/// [`NodeId`] `0`).
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = TreeRepr::deserialize(deserializer)?;
+        Self::from_nodes(repr.nodes).map_err(de::Error::custom)
+    }
+}
+
+/// Two [`Tree`]s are equal if they have the same structure (i.e., the same elements are stored
+/// under the same [`NodeId`]s, with the same children in the same order).
+impl<T: PartialEq> PartialEq for Tree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+    }
+}
+
+impl<T: Eq> Eq for Tree<T> {}
+
+/// Consistent with the implementation of [`PartialEq`], the hash of a [`Tree`] depends on both its
+/// structure and the elements stored in it.
+impl<T: Hash> Hash for Tree<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nodes.hash(state)
+    }
+}
+
+impl<'tree, T> IntoIterator for &'tree Tree<T> {
+    type Item = (NodeId, &'tree T);
+    type IntoIter = Nodes<'tree, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Tree<T> {
+    type Item = (NodeId, T);
+    type IntoIter = IntoNodes<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoNodes::new(self)
+    }
+}
+
+impl<T> Index<NodeId> for Tree<T> {
+    type Output = T;
+
+    /// Returns a reference to the element stored in the [`Tree`] under the provided [`NodeId`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided [`NodeId`] does not correspond to an element currently stored in the
+    /// [`Tree`].
+    fn index(&self, id: NodeId) -> &Self::Output {
+        self.get_by_id(&id)
+            .unwrap_or_else(|| panic!("{}", Error::InvalidNodeId(id)))
+    }
+}
+
+impl<T> IndexMut<NodeId> for Tree<T> {
+    /// Returns a mutable reference to the element stored in the [`Tree`] under the provided
+    /// [`NodeId`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided [`NodeId`] does not correspond to an element currently stored in the
+    /// [`Tree`].
+    fn index_mut(&mut self, id: NodeId) -> &mut Self::Output {
+        self.get_mut_by_id(&id)
+            .unwrap_or_else(|| panic!("{}", Error::InvalidNodeId(id)))
+    }
 }
 
 #[cfg(test)]
@@ -382,4 +1021,841 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_insert_children() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let n1 = t.insert(1, InsertMode::Under(&n0))?;
+
+        let ids = t.insert_children(&n1, 2..6)?;
+        assert_eq!(ids, vec![2, 3, 4, 5]);
+        assert_eq!(t.immediate_descendant_ids(&n1)?.collect::<Vec<_>>(), ids);
+        assert_eq!(t.insert(6, InsertMode::Under(&n0))?, 6);
+
+        assert!(t.insert_children(&42, 7..9).is_err());
+        assert_eq!(t.len(), 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_mut_by_id() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let n1 = t.insert(1, InsertMode::Under(&n0))?;
+
+        *t.root_mut().expect("root not found") += 10;
+        *t.get_mut_by_id(&n1).expect("n1 not found") += 10;
+        assert_eq!(t.root(), Some(&10));
+        assert_eq!(t.get_by_id(&n1), Some(&11));
+        assert_eq!(t.get_mut_by_id(&42), None);
+        assert_eq!(
+            t.immediate_descendant_ids(&n0)?.collect::<Vec<_>>(),
+            vec![n1]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let n1 = t.insert(1, InsertMode::Under(&n0))?;
+
+        t[n1] += 10;
+        assert_eq!(t[n0], 0);
+        assert_eq!(t[n1], 11);
+
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "NodeId '42' does not exist in the Tree")]
+    fn test_index_invalid_id() {
+        let t: Tree<u32> = Tree::new();
+        let _ = t[42];
+    }
+
+    #[test]
+    fn test_iter() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert('a', InsertMode::AsRoot)?;
+        t.insert_children(&n0, ['b', 'c'])?;
+
+        assert_eq!(t.iter().len(), 3);
+        assert_eq!(
+            t.iter().collect::<Vec<_>>(),
+            vec![(0, &'a'), (1, &'b'), (2, &'c')]
+        );
+        assert_eq!((&t).into_iter().next_back(), Some((2, &'c')));
+        assert_eq!(
+            t.into_iter().collect::<Vec<_>>(),
+            vec![(0, 'a'), (1, 'b'), (2, 'c')]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find() -> Result<()> {
+        let mut t = Tree::new();
+        assert_eq!(t.find(|_: &u32| true), None);
+        assert!(t.find_all(|_: &u32| true).is_empty());
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..4)?;
+        t.insert_children(&ids[2], 4..7)?;
+
+        assert_eq!(t.find(|&e| e > 2), Some(3));
+        assert_eq!(t.find(|&e| e > 42), None);
+        assert_eq!(t.find_all(|&e| e % 2 == 0), vec![0, 2, 4, 6]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_root_id_and_contains_id() -> Result<()> {
+        let mut t = Tree::new();
+        assert_eq!(t.root_id(), None);
+        assert!(!t.contains_id(&0));
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let n1 = t.insert(1, InsertMode::Under(&n0))?;
+        assert_eq!(t.root_id(), Some(n0));
+        assert!(t.contains_id(&n0) && t.contains_id(&n1));
+        assert!(!t.contains_id(&2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_arity_and_is_leaf() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..4)?;
+
+        assert_eq!(t.child_ids_slice(&n0)?, ids.as_slice());
+        assert_eq!(t.arity(&n0)?, 3);
+        assert!(!t.is_leaf(&n0)?);
+
+        assert!(t.child_ids_slice(&ids[0])?.is_empty());
+        assert_eq!(t.arity(&ids[0])?, 0);
+        assert!(t.is_leaf(&ids[0])?);
+
+        assert!(t.arity(&42).is_err());
+        assert!(t.is_leaf(&42).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lca() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let [n1, n2]: [_; 2] = t.insert_children(&n0, 1..3)?.try_into().unwrap();
+        let [n3, n4]: [_; 2] = t.insert_children(&n1, 3..5)?.try_into().unwrap();
+        let n5 = t.insert(5, InsertMode::Under(&n3))?;
+        let n6 = t.insert(6, InsertMode::Under(&n2))?;
+
+        assert_eq!(t.lca(&n5, &n4)?, n1);
+        assert_eq!(t.lca(&n4, &n5)?, n1);
+        assert_eq!(t.lca(&n5, &n6)?, n0);
+        assert_eq!(t.lca(&n3, &n5)?, n3);
+        assert_eq!(t.lca(&n2, &n2)?, n2);
+        assert!(t.lca(&n2, &42).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eq_and_hash() -> Result<()> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |t: &Tree<u32>| {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let mut t1 = Tree::new();
+        let n0 = t1.insert(0, InsertMode::AsRoot)?;
+        t1.insert_children(&n0, 1..4)?;
+
+        let t2: Tree<u32> = serde_json::from_str(&serde_json::to_string(&t1)?)?;
+        assert_eq!(t1, t2);
+        assert_eq!(hash(&t1), hash(&t2));
+
+        let mut t3 = Tree::new();
+        let n0 = t3.insert(0, InsertMode::AsRoot)?;
+        let n1 = t3.insert(1, InsertMode::Under(&n0))?;
+        t3.insert_children(&n1, 2..4)?;
+        assert_ne!(t1, t3);
+        assert_ne!(hash(&t1), hash(&t3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialization_validation() -> Result<()> {
+        let valid = r#"{"nodes":[{"data":0,"desc":[1,2]},{"data":1},{"data":2}]}"#;
+        let t: Tree<u32> = serde_json::from_str(valid)?;
+        assert_eq!(t.len(), 3);
+        assert!(serde_json::from_str::<Tree<u32>>(r#"{"nodes":[]}"#)?.is_empty());
+
+        // Empty lists of children are equivalent to none at all.
+        let empty = r#"{"nodes":[{"data":0,"desc":[1,2]},{"data":1,"desc":[]},{"data":2}]}"#;
+        let t: Tree<u32> = serde_json::from_str(empty)?;
+        assert!(t.is_leaf(&1)?);
+        assert_eq!(t.leaf_descendant_ids(&0)?.count(), 2);
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            assert_eq!(t.par_leaves().count(), 2);
+        }
+        assert_eq!(t, serde_json::from_str(valid)?);
+
+        for (malformed, expected) in [
+            (
+                r#"{"nodes":[{"data":0,"desc":[1,7]},{"data":1}]}"#,
+                "non-existent",
+            ),
+            (
+                r#"{"nodes":[{"data":0,"desc":[1,1]},{"data":1}]}"#,
+                "more than once",
+            ),
+            (
+                r#"{"nodes":[{"data":0,"desc":[1]},{"data":1,"desc":[0]}]}"#,
+                "root is listed",
+            ),
+            (
+                r#"{"nodes":[{"data":0},{"data":1,"desc":[2]},{"data":2,"desc":[1]}]}"#,
+                "not reachable",
+            ),
+            (r#"{"nodes":[{"data":0},{"data":1}]}"#, "not reachable"),
+        ] {
+            let err = serde_json::from_str::<Tree<u32>>(malformed)
+                .expect_err("malformed Tree deserialized successfully");
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact() -> Result<()> {
+        use crate::Compact;
+
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let [n1, n2]: [_; 2] = t.insert_children(&n0, 1..3)?.try_into().unwrap();
+        t.insert_children(&n1, 3..5)?;
+        t.insert_children(&n2, 5..7)?;
+
+        let compact = serde_json::to_string(&Compact(t.clone()))?;
+        assert_eq!(
+            compact,
+            r#"{"parents":[0,0,1,1,2,2],"data":[0,1,2,3,4,5,6]}"#
+        );
+        assert!(compact.len() < serde_json::to_string(&t)?.len() / 2);
+
+        let Compact(de) = serde_json::from_str(&compact)?;
+        assert_eq!(t, de);
+        assert!(
+            serde_json::from_str::<Compact<u32>>(r#"{"parents":[],"data":[]}"#)?
+                .0
+                .is_empty()
+        );
+
+        for malformed in [
+            r#"{"parents":[0,0],"data":[0,1]}"#,
+            r#"{"parents":[0,7],"data":[0,1,2]}"#,
+            r#"{"parents":[2,1],"data":[0,1,2]}"#,
+        ] {
+            assert!(serde_json::from_str::<Compact<u32>>(malformed).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_branded_ids() -> Result<()> {
+        use crate::Error;
+
+        let mut t1 = Tree::new();
+        let n0 = t1.insert(0, InsertMode::AsRoot)?;
+        let n1 = t1.insert(1, InsertMode::Under(&n0))?;
+        let t2 = t1.clone();
+
+        let b1 = t1.branded_id(&n1).expect("n1 not found");
+        assert_eq!(b1.id(), n1);
+        assert_eq!(t1.unbrand(&b1)?, n1);
+        *t1.get_mut_by_branded_id(&b1)? += 10;
+        assert_eq!(t1.get_by_branded_id(&b1)?, &11);
+        assert!(t1.branded_id(&42).is_none());
+
+        assert!(matches!(
+            t2.get_by_branded_id(&b1),
+            Err(Error::ForeignNodeId(id)) if id == n1
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_hints() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..4)?;
+        t.insert_children(&ids[0], 4..6)?;
+
+        let mut children = t.immediate_descendant_ids(&n0)?;
+        assert_eq!(children.len(), 3);
+        children.next();
+        assert_eq!(children.len(), 2);
+        assert_eq!(t.immediate_descendants(&ids[1])?.len(), 0);
+
+        let leaves = t.leaf_descendant_ids(&n0)?;
+        assert_eq!(leaves.size_hint(), (1, Some(t.len())));
+        let (lower, upper) = t.leaf_descendants(&n0)?.size_hint();
+        let count = t.leaf_descendants(&n0)?.count();
+        assert!(lower <= count && count <= upper.expect("no upper bound"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_iter() -> Result<()> {
+        use rayon::prelude::*;
+
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..4)?;
+        t.insert_children(&ids[0], 4..6)?;
+
+        assert_eq!(
+            t.par_iter().collect::<Vec<_>>(),
+            t.iter().collect::<Vec<_>>()
+        );
+
+        let mut leaves = t.par_leaves().map(|(id, _)| id).collect::<Vec<_>>();
+        leaves.sort_unstable();
+        assert_eq!(leaves, vec![2, 3, 4, 5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk() -> Result<()> {
+        use crate::{Error, NodeId, Visitor, WalkAction};
+
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl Visitor<u32> for Recorder {
+            fn enter(&mut self, id: NodeId, data: &u32, depth: usize) -> WalkAction {
+                self.0.push(format!("+{id}:{data}@{depth}"));
+                if *data == 1 {
+                    WalkAction::Prune
+                } else {
+                    WalkAction::Continue
+                }
+            }
+
+            fn exit(&mut self, id: NodeId, _: &u32, _: usize) {
+                self.0.push(format!("-{id}"));
+            }
+        }
+
+        let mut t = Tree::new();
+        let mut rec = Recorder::default();
+        t.walk(&mut rec);
+        assert!(rec.0.is_empty());
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..3)?;
+        t.insert_children(&ids[0], 3..5)?;
+        t.insert_children(&ids[1], 5..7)?;
+
+        t.walk(&mut rec);
+        assert_eq!(
+            rec.0,
+            vec!["+0:0@0", "+1:1@1", "-1", "+2:2@1", "+5:5@2", "-5", "+6:6@2", "-6", "-2", "-0"]
+        );
+
+        let mut rec = Recorder::default();
+        t.walk_from(&ids[1], &mut rec)?;
+        assert_eq!(rec.0, vec!["+2:2@0", "+5:5@1", "-5", "+6:6@1", "-6", "-2"]);
+        assert!(matches!(
+            t.walk_from(&42, &mut rec),
+            Err(Error::InvalidNodeId(42))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<()> {
+        let mut t = Tree::new();
+        assert_eq!(t.display().to_string(), "");
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..3)?;
+        let ids = t.insert_children(&ids[0], 3..5)?;
+        t.insert(5, InsertMode::Under(&ids[0]))?;
+
+        assert_eq!(
+            t.display().to_string(),
+            "0\n├── 1\n│   ├── 3\n│   │   └── 5\n│   └── 4\n└── 2"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_dot() -> Result<()> {
+        let mut t = Tree::new();
+        assert_eq!(t.to_dot(|_, _: &&str| ""), "digraph {\n}\n");
+
+        let n0 = t.insert("root", InsertMode::AsRoot)?;
+        let n1 = t.insert("a \"quoted\"\nlabel", InsertMode::Under(&n0))?;
+        t.insert("b\\c", InsertMode::Under(&n1))?;
+        t.insert("d", InsertMode::Under(&n0))?;
+
+        assert_eq!(
+            t.to_dot(|id, data| format!("{id}: {data}")),
+            concat!(
+                "digraph {\n",
+                "    n0 [label=\"0: root\"];\n",
+                "    n1 [label=\"1: a \\\"quoted\\\"\\nlabel\"];\n",
+                "    n2 [label=\"2: b\\\\c\"];\n",
+                "    n3 [label=\"3: d\"];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n3;\n",
+                "    n1 -> n2;\n",
+                "}\n",
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_children_by() -> Result<()> {
+        use crate::{Compact, Error};
+
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, [3, 1, 2])?;
+        t.insert_children(&ids[0], [6, 5, 4])?;
+
+        t.sort_children_by(&n0, |a, b| a.cmp(b))?;
+        assert_eq!(t.child_ids_slice(&n0)?, &[2, 3, 1]);
+        assert_eq!(t.child_ids_slice(&ids[0])?, &[4, 5, 6]);
+        assert_eq!(t.get_by_id(&ids[0]), Some(&3));
+        assert!(matches!(
+            t.sort_children_by(&42, |a, b| a.cmp(b)),
+            Err(Error::InvalidNodeId(42))
+        ));
+
+        t.sort_all_children_by(|a, b| b.cmp(a));
+        assert_eq!(t.child_ids_slice(&n0)?, &[1, 3, 2]);
+        assert_eq!(t.child_ids_slice(&ids[0])?, &[4, 5, 6]);
+
+        // Sibling order must survive both serialization layouts.
+        let de: Tree<u32> = serde_json::from_str(&serde_json::to_string(&t)?)?;
+        assert_eq!(de, t);
+        let de: Compact<u32> = serde_json::from_str(&serde_json::to_string(&Compact(t.clone()))?)?;
+        assert_eq!(de.0, t);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_subtree_size_and_leaf_count() -> Result<()> {
+        use crate::Error;
+
+        let mut t = Tree::new();
+        assert_eq!(t.subtree_stats(), Default::default());
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..4)?;
+        let ids = t.insert_children(&ids[0], 4..6)?;
+        t.insert_children(&ids[1], 6..9)?;
+
+        let stats = t.subtree_stats();
+        for (id, size, leaves) in [(0, 9, 6), (1, 6, 4), (2, 1, 1), (5, 4, 3), (8, 1, 1)] {
+            assert_eq!(t.subtree_size(&id)?, size);
+            assert_eq!(t.leaf_count(&id)?, leaves);
+            assert_eq!(stats.subtree_size(&id)?, size);
+            assert_eq!(stats.leaf_count(&id)?, leaves);
+        }
+        assert!(matches!(t.subtree_size(&42), Err(Error::InvalidNodeId(42))));
+        assert!(matches!(t.leaf_count(&42), Err(Error::InvalidNodeId(42))));
+        assert!(matches!(
+            stats.subtree_size(&42),
+            Err(Error::InvalidNodeId(42))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parent_links() -> Result<()> {
+        let mut t = Tree::new();
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..3)?;
+        let n3 = t.insert(3, InsertMode::Under(&ids[1]))?;
+
+        // Parent links are not serialized, but restored upon deserialization.
+        let json = serde_json::to_string(&t)?;
+        assert!(!json.contains("parent"));
+        let de: Tree<u32> = serde_json::from_str(&json)?;
+        for t in [&t, &de] {
+            assert_eq!(t.parent_id(&n0), None);
+            assert_eq!(t.parent_id(&n3), Some(ids[1]));
+            assert_eq!(t.parent(&ids[0]), Some(&0));
+            assert_eq!(t.parent_id(&42), None);
+            assert_eq!(t.ancestor_ids(&n3).collect::<Vec<_>>(), vec![ids[1], n0]);
+            assert_eq!(t.ancestors(&n3).collect::<Vec<_>>(), vec![&2, &0]);
+            assert_eq!(t.ancestor_ids(&42).count(), 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_freeze() -> Result<()> {
+        use crate::FrozenTree;
+
+        let mut t = Tree::new();
+        let n0 = t.insert(0u32, InsertMode::AsRoot)?;
+        t.insert_children(&n0, 1..4)?;
+        let json = serde_json::to_string(&t)?;
+
+        let frozen = t.clone().freeze();
+        let shared = frozen.clone();
+        assert!(FrozenTree::ptr_eq(&frozen, &shared));
+        assert_eq!(serde_json::to_string(&frozen)?, json);
+
+        let handle =
+            std::thread::spawn(move || shared.leaf_descendants(&n0).map(|l| l.sum::<u32>()));
+        assert_eq!(handle.join().expect("thread panicked")?, 6);
+        assert_eq!(frozen.arity(&n0)?, 3);
+        assert_eq!(frozen.thaw(), t);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor() -> Result<()> {
+        use crate::Error;
+
+        let mut t = Tree::new();
+        assert!(t.root_cursor().is_none());
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.insert_children(&n0, 1..4)?;
+        t.insert_children(&ids[1], 4..6)?;
+
+        let root = t.root_cursor().expect("root not found");
+        assert_eq!(root.node_id(), n0);
+        assert!(root.parent().is_none() && root.next_sibling().is_none());
+
+        let first = root.first_child().expect("no children");
+        assert_eq!(*first.data(), 1);
+        assert!(first.first_child().is_none() && first.prev_sibling().is_none());
+
+        let second = first.next_sibling().expect("no next sibling");
+        assert_eq!(second.node_id(), ids[1]);
+        assert_eq!(second.last_child().map(|c| *c.data()), Some(5));
+        assert_eq!(second.prev_sibling().map(|c| c.node_id()), Some(ids[0]));
+        assert_eq!(second.parent().map(|c| c.node_id()), Some(n0));
+
+        let third = second.next_sibling().expect("no next sibling");
+        assert!(third.next_sibling().is_none());
+        assert_eq!(t.cursor(&ids[2])?.data(), third.data());
+        assert!(matches!(t.cursor(&42), Err(Error::InvalidNodeId(42))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_root() -> Result<()> {
+        use crate::Error;
+
+        let (mut t, root_id) = Tree::with_root(0);
+        assert_eq!(t.root_id(), Some(root_id));
+        assert_eq!(t.len(), 1);
+        assert!(matches!(
+            t.insert(1, InsertMode::AsRoot),
+            Err(Error::RootReplacement)
+        ));
+
+        let n1 = t.insert(1, InsertMode::Under(&root_id))?;
+        assert_eq!(n1, 1);
+        assert_eq!(t.parent_id(&n1), Some(root_id));
+
+        let mut expected = Tree::new();
+        let n0 = expected.insert(0, InsertMode::AsRoot)?;
+        expected.insert(1, InsertMode::Under(&n0))?;
+        assert_eq!(t, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_get() -> Result<()> {
+        use crate::Error;
+
+        let (mut t, n0) = Tree::with_root(String::from("root"));
+        let (n1, data) = t.insert_get(String::from("child"), InsertMode::Under(&n0))?;
+        data.push_str(" (updated)");
+        assert_eq!(t[n1], "child (updated)");
+        assert!(matches!(
+            t.insert_get(String::new(), InsertMode::Under(&42)),
+            Err(Error::NonExistentParent(42))
+        ));
+        assert_eq!(t.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parent_array() -> Result<()> {
+        use crate::Error;
+
+        let mut t = Tree::new();
+        let n0 = t.insert('a', InsertMode::AsRoot)?;
+        let n1 = t.insert('b', InsertMode::Under(&n0))?;
+        t.insert('c', InsertMode::Under(&n1))?;
+        t.insert('d', InsertMode::Under(&n0))?;
+
+        let array = t.to_parent_array();
+        assert_eq!(
+            array,
+            vec![(None, 'a'), (Some(0), 'b'), (Some(1), 'c'), (Some(0), 'd')]
+        );
+        assert_eq!(Tree::from_parent_array(array)?, t);
+        assert_eq!(Tree::from_parent_array(t.clone().into_parent_array())?, t);
+        assert!(Tree::<char>::from_parent_array(vec![])?.is_empty());
+
+        for (items, expected) in [
+            (vec![(None, 'a'), (Some(5), 'b')], "Parent NodeId '5'"),
+            (vec![(Some(1), 'a'), (Some(0), 'b')], "root is listed"),
+            (
+                vec![(None, 'a'), (None, 'b')],
+                "NodeId '1' is not reachable",
+            ),
+            (
+                vec![(None, 'a'), (Some(2), 'b'), (Some(1), 'c')],
+                "is not reachable",
+            ),
+        ] {
+            let err = Tree::from_parent_array(items).unwrap_err();
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+        assert!(matches!(
+            Tree::from_parent_array(vec![(None, 'a'), (None, 'b')]),
+            Err(Error::Unreachable(1))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_capacity() -> Result<()> {
+        let (mut t, n0) = Tree::with_root(0);
+        t.reserve(10);
+        assert!(t.capacity() >= 11);
+
+        t.insert_children(&n0, 1..4)?;
+        t.shrink_to_fit();
+        assert_eq!(t.capacity(), 4);
+        assert_eq!(t.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend_under() -> Result<()> {
+        use crate::Error;
+
+        let (mut t, n0) = Tree::with_root(0);
+        assert_eq!(t.extend_under(&n0, 1..4)?, 1..4);
+        assert_eq!(t.extend_under(&2, std::iter::empty())?, 4..4);
+        assert!(t.is_leaf(&2)?);
+        assert_eq!(t.extend_under(&2, [4, 5])?, 4..6);
+        assert_eq!(t.child_ids_slice(&2)?, &[4, 5]);
+        assert_eq!(t.parent_id(&5), Some(2));
+        assert!(matches!(
+            t.extend_under(&42, [6]),
+            Err(Error::NonExistentParent(42))
+        ));
+        assert_eq!(t.len(), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear() -> Result<()> {
+        use crate::Error;
+
+        let (mut t, n0) = Tree::with_root(0);
+        t.extend_under(&n0, 1..10)?;
+        let branded = t.branded_id(&n0).expect("n0 not found");
+        let capacity = t.capacity();
+
+        t.clear();
+        assert!(t.is_empty());
+        assert_eq!(t.capacity(), capacity);
+        assert!(matches!(
+            t.insert(1, InsertMode::Under(&n0)),
+            Err(Error::NonExistentParent(0))
+        ));
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        assert_eq!(n0, 0);
+        assert!(matches!(
+            t.get_by_branded_id(&branded),
+            Err(Error::ForeignNodeId(0))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_levels() -> Result<()> {
+        let mut t = Tree::new();
+        assert!(t.levels().is_empty());
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        let ids = t.extend_under(&n0, 1..3)?;
+        t.extend_under(&ids.start, 3..5)?;
+        t.extend_under(&(ids.end - 1), 5..6)?;
+        t.extend_under(&5, 6..7)?;
+
+        assert_eq!(
+            t.levels(),
+            vec![vec![0], vec![1, 2], vec![3, 4, 5], vec![6]]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_ancestor_of() -> Result<()> {
+        use crate::Error;
+
+        let (mut t, n0) = Tree::with_root(0);
+        let ids = t.extend_under(&n0, 1..3)?;
+        let n3 = t.insert(3, InsertMode::Under(&ids.start))?;
+
+        assert!(t.is_ancestor_of(&n0, &n3)?);
+        assert!(t.is_ancestor_of(&ids.start, &n3)?);
+        assert!(!t.is_ancestor_of(&(ids.end - 1), &n3)?);
+        assert!(!t.is_ancestor_of(&n3, &n0)?);
+        assert!(!t.is_ancestor_of(&n3, &n3)?);
+        assert!(matches!(
+            t.is_ancestor_of(&n0, &42),
+            Err(Error::InvalidNodeId(42))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edges() -> Result<()> {
+        let mut t = Tree::new();
+        assert_eq!(t.edges().len(), 0);
+        assert_eq!(t.edges().next(), None);
+
+        let n0 = t.insert(0, InsertMode::AsRoot)?;
+        assert_eq!(t.edges().next(), None);
+        let ids = t.extend_under(&n0, 1..3)?;
+        t.extend_under(&(ids.end - 1), 3..5)?;
+        t.insert(5, InsertMode::Under(&ids.start))?;
+
+        let mut edges = t.edges();
+        assert_eq!(edges.len(), 5);
+        assert_eq!(edges.next(), Some((0, 1)));
+        assert_eq!(edges.len(), 4);
+        assert_eq!(
+            edges.collect::<Vec<_>>(),
+            vec![(0, 2), (1, 5), (2, 3), (2, 4)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_renumber_preorder() -> Result<()> {
+        use crate::Error;
+
+        // The same hierarchy, inserted breadth-first and depth-first.
+        let (mut bfs, n0) = Tree::with_root('a');
+        let ids = bfs.extend_under(&n0, ['b', 'e'])?;
+        bfs.extend_under(&ids.start, ['c', 'd'])?;
+        bfs.extend_under(&(ids.end - 1), ['f'])?;
+
+        let (mut dfs, n0) = Tree::with_root('a');
+        let n1 = dfs.insert('b', InsertMode::Under(&n0))?;
+        dfs.extend_under(&n1, ['c', 'd'])?;
+        let n4 = dfs.insert('e', InsertMode::Under(&n0))?;
+        dfs.insert('f', InsertMode::Under(&n4))?;
+        assert_ne!(bfs, dfs);
+
+        let (renumbered, map) = bfs.clone().renumber_preorder();
+        assert_eq!(renumbered, dfs);
+        assert_eq!(
+            serde_json::to_string(&renumbered)?,
+            serde_json::to_string(&dfs)?
+        );
+        assert_eq!(map.len(), 6);
+        for (old_id, new_id) in map.iter() {
+            assert_eq!(bfs[old_id], renumbered[new_id]);
+        }
+        assert_eq!(map.get(&2)?, 4);
+        assert!(matches!(map.get(&42), Err(Error::InvalidNodeId(42))));
+
+        let (empty, map) = Tree::<char>::new().renumber_preorder();
+        assert!(empty.is_empty() && map.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_under_at() -> Result<()> {
+        use crate::{Compact, Error};
+
+        let (mut t, n0) = Tree::with_root(0);
+        let n1 = t.insert(1, InsertMode::UnderAt(&n0, 0))?;
+        let n2 = t.insert(2, InsertMode::UnderAt(&n0, 0))?;
+        let n3 = t.insert(3, InsertMode::UnderAt(&n0, 1))?;
+        let n4 = t.insert(4, InsertMode::UnderAt(&n0, 3))?;
+        assert_eq!(t.child_ids_slice(&n0)?, &[n2, n3, n1, n4]);
+        assert_eq!(t.parent_id(&n3), Some(n0));
+
+        assert!(matches!(
+            t.insert(5, InsertMode::UnderAt(&n0, 5)),
+            Err(Error::InvalidPosition(0, 5))
+        ));
+        assert!(matches!(
+            t.insert(5, InsertMode::UnderAt(&n4, 1)),
+            Err(Error::InvalidPosition(4, 1))
+        ));
+        assert!(matches!(
+            t.insert(5, InsertMode::UnderAt(&42, 0)),
+            Err(Error::NonExistentParent(42))
+        ));
+        assert_eq!(t.len(), 5);
+
+        let de: Compact<u32> = serde_json::from_str(&serde_json::to_string(&Compact(t.clone()))?)?;
+        assert_eq!(de.0, t);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,43 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rayon::prelude::*;
+
+use super::{NodeId, Tree};
+
+impl<T: Sync> Tree<T> {
+    /// Returns a parallel iterator over all elements stored in the [`Tree`] along with their
+    /// [`NodeId`]s.
+    ///
+    /// This is the parallel counterpart of [`Tree::iter`], backed by `rayon`.
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = (NodeId, &T)> + '_ {
+        self.nodes
+            .par_iter()
+            .enumerate()
+            .map(|(id, tn)| (id as NodeId, &tn.data))
+    }
+
+    /// Returns a parallel iterator over all elements stored at the leaves of the [`Tree`] along
+    /// with their [`NodeId`]s.
+    ///
+    /// Unlike [`Tree::leaf_descendants`], this scans the whole [`Tree`] in parallel, backed by
+    /// `rayon`, and yields the leaves in no particular order.
+    pub fn par_leaves(&self) -> impl ParallelIterator<Item = (NodeId, &T)> + '_ {
+        self.nodes
+            .par_iter()
+            .enumerate()
+            .filter(|(_, tn)| tn.children.is_none())
+            .map(|(id, tn)| (id as NodeId, &tn.data))
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{vec, vec::Vec};
+
+use super::{Error, NodeId, Tree, TreeNode};
+
+/// A mapping from the [`NodeId`]s of a [`Tree`] to the ones assigned to the same elements after
+/// renumbering it.
+///
+/// It is created by [`Tree::renumber_preorder`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NodeIdMap(Vec<NodeId>);
+
+impl NodeIdMap {
+    /// Returns the new [`NodeId`] of the element that was stored under the provided (old) one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] did not correspond to an element
+    /// stored in the original [`Tree`].
+    pub fn get(&self, old_id: &NodeId) -> Result<NodeId, Error> {
+        self.0
+            .get(*old_id as usize)
+            .copied()
+            .ok_or(Error::InvalidNodeId(*old_id))
+    }
+
+    /// Returns the number of [`NodeId`]s in the mapping.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the mapping is empty; `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over all `(old, new)` pairs of [`NodeId`]s, in ascending order of the
+    /// old ones.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(old_id, &new_id)| (old_id as NodeId, new_id))
+    }
+}
+
+impl<T> Tree<T> {
+    /// Consume the [`Tree`] and return an equivalent one, whose [`NodeId`]s follow the depth-first
+    /// pre-order of its elements (respecting the order in which the children of each element are
+    /// stored), along with the mapping from the old [`NodeId`]s to the new ones.
+    ///
+    /// Two [`Tree`]s with the same hierarchy of equal elements (with siblings stored in the same
+    /// order) are renumbered identically, no matter the order in which the elements have been
+    /// inserted; hence, they are serialized identically, too.
+    ///
+    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    pub fn renumber_preorder(self) -> (Tree<T>, NodeIdMap) {
+        let mut preorder = Vec::with_capacity(self.nodes.len());
+        let mut stack = Vec::from_iter(self.root_id());
+        while let Some(id) = stack.pop() {
+            preorder.push(id);
+            if let Some(children) = self.nodes[id as usize].children.as_ref() {
+                stack.extend(children.iter().rev());
+            }
+        }
+
+        let mut map = vec![0; self.nodes.len()];
+        for (new_id, &old_id) in preorder.iter().enumerate() {
+            map[old_id as usize] = new_id as NodeId;
+        }
+
+        let mut old_nodes: Vec<Option<TreeNode<T>>> = self.nodes.into_iter().map(Some).collect();
+        let nodes = preorder
+            .iter()
+            .map(|&old_id| {
+                let mut tn = old_nodes[old_id as usize]
+                    .take()
+                    .expect("each element is visited exactly once");
+                if let Some(children) = tn.children.as_mut() {
+                    for child_id in children.iter_mut() {
+                        *child_id = map[*child_id as usize];
+                    }
+                }
+                tn
+            })
+            .collect();
+
+        let tree = Tree::from_nodes(nodes).expect("renumbering preserves the Tree's structure");
+        (tree, NodeIdMap(map))
+    }
+}
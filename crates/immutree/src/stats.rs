@@ -0,0 +1,135 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{vec, vec::Vec};
+
+use super::{Error, NodeId, Tree};
+
+/// Precomputed sizes and leaf counts of all subtrees of a [`Tree`], so that they can be looked up
+/// in `O(1)` rather than by traversing the subtree on each call.
+///
+/// It is created by [`Tree::subtree_stats`].
+///
+/// # Note
+///
+/// It reflects the [`Tree`] at the time of its creation; i.e., elements inserted afterwards are
+/// not accounted for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SubtreeStats {
+    sizes: Vec<usize>,
+    leaf_counts: Vec<usize>,
+}
+
+impl SubtreeStats {
+    /// Returns the number of elements in the subtree rooted at the provided [`NodeId`], including
+    /// the element stored under it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element that was stored in the [`Tree`] when the stats were computed.
+    pub fn subtree_size(&self, id: &NodeId) -> Result<usize, Error> {
+        self.sizes
+            .get(*id as usize)
+            .copied()
+            .ok_or(Error::InvalidNodeId(*id))
+    }
+
+    /// Returns the number of leaves in the subtree rooted at the provided [`NodeId`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element that was stored in the [`Tree`] when the stats were computed.
+    pub fn leaf_count(&self, id: &NodeId) -> Result<usize, Error> {
+        self.leaf_counts
+            .get(*id as usize)
+            .copied()
+            .ok_or(Error::InvalidNodeId(*id))
+    }
+}
+
+impl<T> Tree<T> {
+    /// Returns the number of elements in the subtree rooted at the provided [`NodeId`], including
+    /// the element stored under it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element currently stored in the [`Tree`].
+    ///
+    /// # Note
+    ///
+    /// The whole subtree is traversed on each call; see [`Tree::subtree_stats`] for repeated
+    /// queries.
+    pub fn subtree_size(&self, id: &NodeId) -> Result<usize, Error> {
+        if !self.contains_id(id) {
+            return Err(Error::InvalidNodeId(*id));
+        }
+
+        let mut size = 0;
+        let mut stack = vec![*id];
+        while let Some(id) = stack.pop() {
+            size += 1;
+            stack.extend(self.nodes[id as usize].children.iter().flatten());
+        }
+        Ok(size)
+    }
+
+    /// Returns the number of leaves in the subtree rooted at the provided [`NodeId`] (i.e., `1` if
+    /// it corresponds to a leaf itself).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element currently stored in the [`Tree`].
+    ///
+    /// # Note
+    ///
+    /// The whole subtree is traversed on each call; see [`Tree::subtree_stats`] for repeated
+    /// queries.
+    pub fn leaf_count(&self, id: &NodeId) -> Result<usize, Error> {
+        Ok(self.leaf_descendant_ids(id)?.count())
+    }
+
+    /// Computes the sizes and leaf counts of all subtrees of the [`Tree`] at once.
+    ///
+    /// The underlying algorithm's space and time complexities both are `Θ(|V|)`.
+    pub fn subtree_stats(&self) -> SubtreeStats {
+        // Visiting the elements in reverse pre-order guarantees that all children are accounted
+        // for before their parent.
+        let mut preorder = Vec::with_capacity(self.nodes.len());
+        let mut stack = Vec::from_iter(self.root_id());
+        while let Some(id) = stack.pop() {
+            preorder.push(id);
+            stack.extend(self.nodes[id as usize].children.iter().flatten());
+        }
+
+        let mut stats = SubtreeStats {
+            sizes: vec![1; self.nodes.len()],
+            leaf_counts: vec![1; self.nodes.len()],
+        };
+        for &id in preorder.iter().rev() {
+            if let Some(children) = self.nodes[id as usize].children.as_ref() {
+                let id = id as usize;
+                stats.leaf_counts[id] = 0;
+                for &child_id in children {
+                    stats.sizes[id] += stats.sizes[child_id as usize];
+                    stats.leaf_counts[id] += stats.leaf_counts[child_id as usize];
+                }
+            }
+        }
+        stats
+    }
+}
@@ -12,41 +12,120 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use alloc::vec::Vec;
+use core::fmt;
+
 use serde::{Deserialize, Serialize};
 
 /// An error type returned by calls to the API exposed by this crate.
-#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[derive(Debug, Clone, Copy)]
 pub enum Error {
     /// Returned on an attempt to insert a root element in the [`Tree`], while a root element
     /// already exists (the root element can only be the first insertion).
     ///
     /// [`Tree`]: super::Tree
-    #[error("Tree's root can be set only at the first insertion")]
     RootReplacement,
 
     /// Returned when the user-provided [`NodeId`] for a parent [`Tree`] node does not actually
     /// reside in the [`Tree`].
     ///
     /// [`Tree`]: super::Tree
-    #[error("Parent NodeId '{0}' does not exist in the Tree")]
     NonExistentParent(NodeId),
 
     /// Returned when a user-provided [`NodeId`] does not correspond to an element stored in the
     /// [`Tree`].
     ///
     /// [`Tree`]: super::Tree
-    #[error("NodeId '{0}' does not exist in the Tree")]
     InvalidNodeId(NodeId),
+
+    /// Returned when an element of a [`Tree`] that is being validated (e.g., during
+    /// deserialization) lists as its child a [`NodeId`] that does not reside in the [`Tree`].
+    ///
+    /// [`Tree`]: super::Tree
+    DanglingChild(NodeId, NodeId),
+
+    /// Returned when an element of a [`Tree`] that is being validated (e.g., during
+    /// deserialization) is listed as a child more than once.
+    ///
+    /// [`Tree`]: super::Tree
+    MultipleParents(NodeId),
+
+    /// Returned when the root element of a [`Tree`] that is being validated (e.g., during
+    /// deserialization) is listed as the child of another element.
+    ///
+    /// [`Tree`]: super::Tree
+    RootAsChild(NodeId),
+
+    /// Returned when an element of a [`Tree`] that is being validated (e.g., during
+    /// deserialization) is not reachable from the root element.
+    ///
+    /// [`Tree`]: super::Tree
+    Unreachable(NodeId),
+
+    /// Returned on an attempt to insert an element as the child of another at a position greater
+    /// than the number of its children.
+    InvalidPosition(NodeId, usize),
+
+    /// Returned when a user-provided [`BrandedNodeId`] has been issued by another [`Tree`].
+    ///
+    /// [`BrandedNodeId`]: super::BrandedNodeId
+    /// [`Tree`]: super::Tree
+    ForeignNodeId(NodeId),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match self {
+            RootReplacement => write!(f, "Tree's root can be set only at the first insertion"),
+            NonExistentParent(id) => write!(f, "Parent NodeId '{id}' does not exist in the Tree"),
+            InvalidNodeId(id) => write!(f, "NodeId '{id}' does not exist in the Tree"),
+            DanglingChild(parent_id, child_id) => write!(
+                f,
+                "NodeId '{parent_id}' lists non-existent NodeId '{child_id}' as a child"
+            ),
+            MultipleParents(id) => write!(f, "NodeId '{id}' is listed as a child more than once"),
+            RootAsChild(parent_id) => {
+                write!(
+                    f,
+                    "Tree's root is listed as a child of NodeId '{parent_id}'"
+                )
+            }
+            Unreachable(id) => write!(f, "NodeId '{id}' is not reachable from the Tree's root"),
+            InvalidPosition(parent_id, position) => write!(
+                f,
+                "Position '{position}' exceeds the number of children of NodeId '{parent_id}'"
+            ),
+            ForeignNodeId(id) => write!(f, "NodeId '{id}' has been issued by another Tree"),
+        }
+    }
 }
 
+#[cfg(any(feature = "std", test))]
+impl std::error::Error for Error {}
+
 /// The type of the unique ID assigned to each node at the time of insertion in the [`Tree`].
 ///
 /// It is also needed by various methods when there is a need to refer to a specific element in the
 /// [`Tree`].
 ///
+/// It is a `u32`, unless the `u64-node-ids` feature is enabled.
+///
 /// [`Tree`]: super::Tree
+#[cfg(not(feature = "u64-node-ids"))]
 pub type NodeId = u32;
 
+/// The type of the unique ID assigned to each node at the time of insertion in the [`Tree`].
+///
+/// It is also needed by various methods when there is a need to refer to a specific element in the
+/// [`Tree`].
+///
+/// It is a `u64`, since the `u64-node-ids` feature is enabled.
+///
+/// [`Tree`]: super::Tree
+#[cfg(feature = "u64-node-ids")]
+pub type NodeId = u64;
+
 /// This is supplied to [`Tree::insert`], aiming to regulate the insertion of a new element in the
 /// [`Tree`].
 ///
@@ -68,27 +147,83 @@ pub enum InsertMode<'insertion> {
     ///
     /// [`Tree`]: super::Tree
     Under(&'insertion NodeId),
+
+    /// This is used when the new element should be inserted in the [`Tree`] as the child of
+    /// another node at a specific position among its children (e.g., `0` to become its first
+    /// child), rather than after all existing children.
+    /// A reference to that node's (i.e., the parent's) [`NodeId`] must be also supplied, as in
+    /// [`InsertMode::Under`].
+    ///
+    /// [`Tree`]: super::Tree
+    UnderAt(&'insertion NodeId, usize),
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// The container of the [`NodeId`]s of the children of each element stored in the [`Tree`].
+///
+/// [`Tree`]: super::Tree
+#[cfg(not(feature = "smallvec"))]
+pub(crate) type Children = Vec<NodeId>;
+
+/// The container of the [`NodeId`]s of the children of each element stored in the [`Tree`].
+///
+/// Up to 4 children are stored inline, to avoid a heap allocation per internal element.
+///
+/// [`Tree`]: super::Tree
+#[cfg(feature = "smallvec")]
+pub(crate) type Children = smallvec::SmallVec<[NodeId; 4]>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub(crate) struct TreeNode<T> {
     pub(super) data: T,
 
     #[serde(rename = "desc", skip_serializing_if = "Option::is_none")]
-    pub(crate) children: Option<Vec<NodeId>>,
+    pub(crate) children: Option<Children>,
+
+    /// The [`NodeId`] of the parent element (`None` for the root); it is not serialized, but it is
+    /// restored from the children [`NodeId`]s upon deserialization.
+    #[serde(skip)]
+    pub(crate) parent: Option<NodeId>,
+}
+
+/// The serialized representation of a [`Tree`], which has to be validated before being converted
+/// into a [`Tree`] during deserialization.
+///
+/// [`Tree`]: super::Tree
+#[derive(Deserialize)]
+pub(crate) struct TreeRepr<T> {
+    pub(crate) nodes: Vec<TreeNode<T>>,
 }
 
 impl<T> TreeNode<T> {
+    pub(super) fn new(data: T, parent: Option<NodeId>) -> Self {
+        Self {
+            data,
+            children: None,
+            parent,
+        }
+    }
+
     pub(super) fn add_child_id(&mut self, id: &NodeId) {
-        self.children.get_or_insert(vec![]).push(*id)
+        self.children.get_or_insert_with(Children::new).push(*id)
+    }
+
+    pub(super) fn insert_child_id(&mut self, position: usize, id: &NodeId) {
+        self.children
+            .get_or_insert_with(Children::new)
+            .insert(position, *id)
+    }
+
+    pub(super) fn reserve_children(&mut self, additional: usize) {
+        if additional > 0 {
+            self.children
+                .get_or_insert_with(Children::new)
+                .reserve(additional)
+        }
     }
 }
 
 impl<T> From<T> for TreeNode<T> {
     fn from(data: T) -> Self {
-        Self {
-            data,
-            children: None,
-        }
+        Self::new(data, None)
     }
 }
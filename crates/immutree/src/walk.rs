@@ -0,0 +1,97 @@
+// Copyright 2022 Christos Katsakioris
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec;
+
+use super::{Error, NodeId, Tree};
+
+/// Determines how a walk over a [`Tree`] proceeds after a [`Visitor`] has entered an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WalkAction {
+    /// Walk down into the children of the element that has just been entered.
+    Continue,
+    /// Skip the subtree rooted at the element that has just been entered; its
+    /// [`Visitor::exit`] callback is still invoked.
+    Prune,
+}
+
+/// A visitor of the elements stored in a [`Tree`], driven by [`Tree::walk`].
+///
+/// Elements are visited in depth-first pre-order; each element is entered before any of its
+/// descendants, and exited after all of them.
+/// The `depth` provided to both callbacks is relative to the element the walk started from.
+pub trait Visitor<T> {
+    /// Called when the walk reaches the element stored under the provided [`NodeId`], before any
+    /// of its descendants.
+    ///
+    /// The returned [`WalkAction`] determines whether its subtree is walked or pruned.
+    fn enter(&mut self, _id: NodeId, _data: &T, _depth: usize) -> WalkAction {
+        WalkAction::Continue
+    }
+
+    /// Called when the walk leaves the element stored under the provided [`NodeId`], after all of
+    /// its (non-pruned) descendants.
+    fn exit(&mut self, _id: NodeId, _data: &T, _depth: usize) {}
+}
+
+impl<T> Tree<T> {
+    /// Walk the whole [`Tree`] in depth-first pre-order, starting from its root element, invoking
+    /// the provided [`Visitor`]'s callbacks for each element.
+    ///
+    /// Walking an empty [`Tree`] is a no-op.
+    pub fn walk<V: Visitor<T>>(&self, visitor: &mut V) {
+        if let Some(root_id) = self.root_id() {
+            self.walk_inner(root_id, visitor);
+        }
+    }
+
+    /// Walk the subtree rooted at the element stored under the provided [`NodeId`] in depth-first
+    /// pre-order, invoking the provided [`Visitor`]'s callbacks for each element.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidNodeId`] if the provided [`NodeId`] does not correspond to an
+    /// element stored in the [`Tree`].
+    pub fn walk_from<V: Visitor<T>>(&self, id: &NodeId, visitor: &mut V) -> Result<(), Error> {
+        if !self.contains_id(id) {
+            return Err(Error::InvalidNodeId(*id));
+        }
+        self.walk_inner(*id, visitor);
+        Ok(())
+    }
+
+    fn walk_inner<V: Visitor<T>>(&self, start_id: NodeId, visitor: &mut V) {
+        // Each entry is marked as to whether the element has already been entered, in which case
+        // it is exited the next time it is popped.
+        let mut stack = vec![(start_id, 0, false)];
+        while let Some((id, depth, entered)) = stack.pop() {
+            let node = &self.nodes[id as usize];
+            if entered {
+                visitor.exit(id, &node.data, depth);
+                continue;
+            }
+
+            let action = visitor.enter(id, &node.data, depth);
+            stack.push((id, depth, true));
+            if let (WalkAction::Continue, Some(children_ids)) = (action, &node.children) {
+                stack.extend(
+                    children_ids
+                        .iter()
+                        .rev()
+                        .map(|&child_id| (child_id, depth + 1, false)),
+                );
+            }
+        }
+    }
+}
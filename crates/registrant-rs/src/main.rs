@@ -6,9 +6,11 @@ use anyhow::{anyhow, Context, Result};
 use clap::Parser;
 use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
+use actitopo::CpuSet;
+
 use registrant::Registrant;
 
-#[derive(Debug, Default, Parser, Clone, Copy)]
+#[derive(Debug, Default, Parser, Clone)]
 #[clap(author, version, about, long_about = None)]
 #[clap(propagate_version = true)]
 pub struct Args {
@@ -16,6 +18,16 @@ pub struct Args {
     /// other value is interpreted as 'all'.
     #[clap(short = 'm', long = "mode", required = false, default_value = "all")]
     pub mode: Mode,
+
+    /// Restrict the detected hardware topology to the hardware threads and NUMA nodes that the
+    /// registrant is allowed to use (e.g., due to its cgroup's cpuset).
+    #[clap(short = 'r', long = "restrict-to-allowed")]
+    pub restrict_to_allowed: bool,
+
+    /// Exclude the given hardware threads (e.g., the ones passed to the kubelet through
+    /// '--reserved-cpus', in the same cpulist format) from the detected hardware topology.
+    #[clap(long = "reserved-cpus", required = false, default_value = "")]
+    pub reserved_cpus: CpuSet,
 }
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -4,12 +4,16 @@ use std::{
 };
 
 use anyhow::{Context, Result};
-use kube::{Api, Client};
+use kube::{
+    api::{Patch, PatchParams},
+    Api, Client,
+};
+use serde_json::json;
 use tracing::{info, instrument, trace, Level};
 use validator::Validate;
 
 use acticrds::ActiNode;
-use actitopo::{DetectionMode, Topology};
+use actitopo::{CpuSet, DetectionMode, DetectionOptions, Topology};
 
 use crate::{Args, Mode};
 
@@ -22,12 +26,6 @@ const APP_K8S_IO_VERSION: &str = env!("CARGO_PKG_VERSION");
 const APP_K8S_IO_COMPONENT: &str = "actinodes";
 const APP_K8S_IO_PART_OF: &str = "actik8s";
 
-//
-// ActiK8s annotations' keys
-//
-const ACTI_FULL_TOPO_ANNOTATION_KEY: &str = "acti.cslab.ece.ntua.gr/full-topology";
-const ACTI_PART_TOPO_ANNOTATION_KEY: &str = "acti.cslab.ece.ntua.gr/partial-topology";
-
 //
 // Environment variables expected to be set at runtime by CRI
 //
@@ -37,6 +35,8 @@ const ACTI_K8S_NAMESPACE_ENV: &str = "ACTI_NAMESPACE";
 #[derive(Debug, Clone)]
 pub struct Registrant {
     mode: Mode,
+    restrict_to_allowed: bool,
+    reserved_cpus: CpuSet,
     node_name: String,
     namespace: String,
 }
@@ -46,6 +46,8 @@ impl Registrant {
     pub fn new(args: Args) -> Result<Self> {
         Ok(Self {
             mode: args.mode,
+            restrict_to_allowed: args.restrict_to_allowed,
+            reserved_cpus: args.reserved_cpus,
             node_name: env::var(ACTI_K8S_NODE_NAME_ENV).with_context(|| {
                 format!("environment variable {ACTI_K8S_NODE_NAME_ENV:?} not found",)
             })?,
@@ -58,12 +60,22 @@ impl Registrant {
     /// node where we are running on.
     #[instrument(level = Level::DEBUG, skip(self))]
     fn detect_topology(&self) -> Result<(Option<Topology>, Option<Topology>)> {
+        let options = |mode| {
+            let options = DetectionOptions::new(mode)
+                .numa_distances()
+                .exclude_cpus(self.reserved_cpus.clone());
+            if self.restrict_to_allowed {
+                options.restrict_to_allowed()
+            } else {
+                options
+            }
+        };
         let full = || {
-            Topology::detect(DetectionMode::Full)
+            Topology::detect_with(options(DetectionMode::Full))
                 .with_context(|| "failed to detect the full underlying hardware topology")
         };
         let partial = || {
-            Topology::detect(DetectionMode::IsolationBoundariesOnly)
+            Topology::detect_with(options(DetectionMode::IsolationBoundariesOnly))
                 .with_context(|| "failed to detect the partial underlying hardware topology")
         };
         Ok(match self.mode {
@@ -73,20 +85,22 @@ impl Registrant {
         })
     }
 
-    /// Allocates, properly initializes and returns a (local, in-memory) `ActiNode`.
-    #[instrument(level = Level::DEBUG, skip(self, acti_annotations))]
-    fn init_actinode(&self, acti_annotations: ActiAnnotations) -> Result<ActiNode> {
+    /// Allocates, properly initializes and returns a (local, in-memory) `ActiNode`, which carries
+    /// the provided full and partial (respectively) hardware topologies.
+    #[instrument(level = Level::DEBUG, skip(self, full_topo, partial_topo))]
+    fn init_actinode(
+        &self,
+        (full_topo, partial_topo): (Option<Topology>, Option<Topology>),
+    ) -> Result<ActiNode> {
         let mut an = ActiNode::new(self.node_name.as_str(), Default::default());
         an.metadata.namespace = Some(self.namespace.clone());
         an.metadata
             .labels
             .get_or_insert_with(Default::default)
             .extend(ActiLabels::new(self.node_name.as_str()).into_iter());
-        an.metadata
-            .annotations
-            .get_or_insert_with(Default::default)
-            .extend(acti_annotations.into_iter());
-        an.status = Some(Default::default());
+        an.spec.topology = full_topo;
+        an.spec.partial_topology = partial_topo;
+        an.refresh_summary();
 
         an.spec
             .validate()
@@ -110,6 +124,16 @@ impl Registrant {
             .await
             .with_context(|| "failed to create new ActiNode K8s API Object")?;
 
+        // Status is a subresource, hence it is ignored on creation; write it (i.e., its summary,
+        // as displayed by `kubectl get actinodes`) through the status subresource
+        let name = upstream_an.metadata.name.as_deref();
+        let name = name.expect("upstream ActiNode Object's name is None");
+        let status = Patch::Merge(json!({ "status": actinode.status }));
+        let upstream_an = actinodes
+            .patch_status(name, &PatchParams::default(), &status)
+            .await
+            .with_context(|| "failed to update the status of the new ActiNode K8s API Object")?;
+
         // Log success
         let ns = upstream_an.metadata.namespace.as_ref();
         let name = upstream_an.metadata.name.as_ref();
@@ -128,10 +152,8 @@ impl Registrant {
     pub async fn run(self) -> Result<()> {
         let actinode = self
             .detect_topology()
-            .with_context(|| "failed to detect hardware topology")?
-            .try_into()
-            .with_context(|| "could not convert Topology objects into ActiAnnotations")
-            .and_then(|acti_annotations| self.init_actinode(acti_annotations))
+            .with_context(|| "failed to detect hardware topology")
+            .and_then(|topologies| self.init_actinode(topologies))
             .with_context(|| "failed to initialize local ActiNode struct")?;
         self.register_node(actinode)
             .await
@@ -139,39 +161,6 @@ impl Registrant {
     }
 }
 
-#[derive(Debug, Default, Clone)]
-struct ActiAnnotations(BTreeMap<String, String>);
-
-impl TryFrom<(Option<Topology>, Option<Topology>)> for ActiAnnotations {
-    type Error = anyhow::Error;
-
-    fn try_from(
-        (full_topo, partial_topo): (Option<Topology>, Option<Topology>),
-    ) -> Result<Self, Self::Error> {
-        let mut ret = BTreeMap::new();
-        if let Some(full) = full_topo {
-            let full = serde_json::to_string(&full)
-                .with_context(|| "could not serialize Topology (full)")?;
-            let _ = ret.insert(ACTI_FULL_TOPO_ANNOTATION_KEY.to_owned(), full);
-        }
-        if let Some(partial) = partial_topo {
-            let partial = serde_json::to_string(&partial)
-                .with_context(|| "could not serialize Topology (partial)")?;
-            let _ = ret.insert(ACTI_PART_TOPO_ANNOTATION_KEY.to_owned(), partial);
-        }
-        Ok(Self(ret))
-    }
-}
-
-impl IntoIterator for ActiAnnotations {
-    type Item = (String, String);
-    type IntoIter = btree_map::IntoIter<String, String>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
-    }
-}
-
 struct ActiLabels(BTreeMap<String, String>);
 
 impl ActiLabels {